@@ -30,6 +30,8 @@ pub enum LaunchpadError {
     InvalidDispenserAddress,
     #[msg("Dispensing accounts should have the same decimals")]
     InvalidDispenserDecimals,
+    #[msg("Invalid payment custody")]
+    InvalidPaymentCustody,
     #[msg("Invalid seller's balance address")]
     InvalidSellerBalanceAddress,
     #[msg("New auctions are not allowed at this time")]
@@ -60,6 +62,12 @@ pub enum LaunchpadError {
     AuctionWithFixedAmount,
     #[msg("Auction is still in progress")]
     AuctionInProgress,
+    #[msg("Auction hasn't been settled")]
+    AuctionNotSettled,
+    #[msg("Auction has been already settled")]
+    AuctionAlreadySettled,
+    #[msg("Instruction is not supported by the auction pricing model")]
+    UnsupportedPricingModel,
     #[msg("Overflow in arithmetic operation")]
     MathOverflow,
     #[msg("Unsupported price oracle")]
@@ -78,10 +86,36 @@ pub enum LaunchpadError {
     BidAmountTooLarge,
     #[msg("Bid price is too small")]
     BidPriceTooSmall,
+    #[msg("Bid price is not within the auction price range")]
+    InvalidBidPrice,
+    #[msg("Previous bid hasn't been settled yet")]
+    BidAlreadyPlaced,
+    #[msg("Bid has unclaimed funds")]
+    BidNotSettled,
+    #[msg("Auction has reached the minimum raise")]
+    SoftCapMet,
+    #[msg("Only sealed bids are accepted by this auction")]
+    SealedBidsOnly,
+    #[msg("Auction doesn't accept sealed bids")]
+    SealedBidsNotAllowed,
+    #[msg("Sealed bid can't be revealed at this time")]
+    RevealNotAllowed,
+    #[msg("Revealed bid doesn't match the commitment")]
+    InvalidBidCommitment,
     #[msg("Fill limit exceeded")]
     FillAmountLimit,
     #[msg("Unexpected price calculation error")]
     PriceCalcError,
     #[msg("This instruction must be all alone in the transaction")]
     MustBeSingleInstruction,
+    #[msg("Unsupported randomness source")]
+    UnsupportedRandomnessSource,
+    #[msg("Randomness is not available yet")]
+    RandomnessNotAvailable,
+    #[msg("Auction doesn't accept limit bids")]
+    LimitBidsNotAllowed,
+    #[msg("Limit order book is full")]
+    LimitBookFull,
+    #[msg("Invalid VRF account")]
+    InvalidVrfAccount,
 }
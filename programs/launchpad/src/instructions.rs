@@ -11,6 +11,7 @@ pub mod withdraw_fees;
 // test instructions
 pub mod set_test_oracle_price;
 pub mod set_test_time;
+pub mod set_test_vrf;
 pub mod test_init;
 
 // seller instructions
@@ -26,29 +27,52 @@ pub mod withdraw_funds;
 
 // buyer instructions
 pub mod cancel_bid;
+pub mod cancel_limit_bid;
+pub mod claim_refund;
+pub mod claim_tokens;
+pub mod claim_vested;
+pub mod commit_bid;
+pub mod crank_fills;
+pub mod dispense_tokens;
+pub mod finalize_auction;
 pub mod get_auction_amount;
 pub mod get_auction_price;
+pub mod get_quote;
 pub mod place_bid;
+pub mod reveal_bid;
+pub mod settle_auction;
 
 // bring everything in scope
 pub use add_tokens::*;
 pub use cancel_bid::*;
+pub use cancel_limit_bid::*;
+pub use claim_refund::*;
+pub use claim_tokens::*;
+pub use claim_vested::*;
+pub use commit_bid::*;
+pub use crank_fills::*;
 pub use delete_auction::*;
 pub use disable_auction::*;
+pub use dispense_tokens::*;
 pub use enable_auction::*;
+pub use finalize_auction::*;
 pub use get_auction_amount::*;
 pub use get_auction_price::*;
+pub use get_quote::*;
 pub use init::*;
 pub use init_auction::*;
 pub use init_custody::*;
 pub use place_bid::*;
 pub use remove_tokens::*;
+pub use reveal_bid::*;
 pub use set_admin_signers::*;
 pub use set_fees::*;
 pub use set_oracle_config::*;
 pub use set_permissions::*;
 pub use set_test_oracle_price::*;
 pub use set_test_time::*;
+pub use set_test_vrf::*;
+pub use settle_auction::*;
 pub use test_init::*;
 pub use update_auction::*;
 pub use whitelist_add::*;
@@ -3,10 +3,11 @@
 use {
     crate::{
         error::LaunchpadError,
+        math,
         state::{auction::Auction, launchpad::Launchpad},
     },
     anchor_lang::prelude::*,
-    anchor_spl::token::{Mint, Token, TokenAccount},
+    anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface},
 };
 
 #[derive(Accounts)]
@@ -19,7 +20,7 @@ pub struct AddTokens<'info> {
         constraint = funding_account.mint == dispensing_custody.mint,
         has_one = owner
     )]
-    pub funding_account: Box<Account<'info, TokenAccount>>,
+    pub funding_account: Box<InterfaceAccount<'info, TokenAccount>>,
 
     /// CHECK: empty PDA, authority for token accounts
     #[account(
@@ -42,7 +43,7 @@ pub struct AddTokens<'info> {
     )]
     pub auction: Box<Account<'info, Auction>>,
 
-    pub dispensing_custody_mint: Box<Account<'info, Mint>>,
+    pub dispensing_custody_mint: Box<InterfaceAccount<'info, Mint>>,
 
     #[account(
         init_if_needed,
@@ -50,15 +51,16 @@ pub struct AddTokens<'info> {
         constraint = dispensing_custody_mint.key() == dispensing_custody.mint,
         token::mint = dispensing_custody_mint,
         token::authority = transfer_authority,
+        token::token_program = token_program,
         seeds = [b"dispense",
                  dispensing_custody_mint.key().as_ref(),
                  auction.key().as_ref()],
         bump
     )]
-    pub dispensing_custody: Box<Account<'info, TokenAccount>>,
+    pub dispensing_custody: Box<InterfaceAccount<'info, TokenAccount>>,
 
     system_program: Program<'info, System>,
-    token_program: Program<'info, Token>,
+    token_program: Interface<'info, TokenInterface>,
     rent: Sysvar<'info, Rent>,
 }
 
@@ -83,13 +85,23 @@ pub fn add_tokens(ctx: Context<AddTokens>, params: &AddTokensParams) -> Result<(
         LaunchpadError::AuctionWithFixedAmount
     );
 
+    let balance_before = ctx.accounts.dispensing_custody.amount;
     ctx.accounts.launchpad.transfer_tokens(
         ctx.accounts.funding_account.to_account_info(),
         ctx.accounts.dispensing_custody.to_account_info(),
+        ctx.accounts.dispensing_custody_mint.to_account_info(),
         ctx.accounts.owner.to_account_info(),
         ctx.accounts.token_program.to_account_info(),
         params.amount,
+        ctx.accounts.dispensing_custody_mint.decimals,
     )?;
 
+    // available supply is read from dispenser balances, so transfer fees withheld
+    // by Token-2022 mints are accounted for
+    ctx.accounts.dispensing_custody.reload()?;
+    let received_amount =
+        math::checked_sub(ctx.accounts.dispensing_custody.amount, balance_before)?;
+    msg!("Received {} tokens", received_amount);
+
     Ok(())
 }
@@ -42,6 +42,15 @@ pub fn cancel_bid(ctx: Context<CancelBid>, _params: &CancelBidParams) -> Result<
     );
 
     let bid = ctx.accounts.bid.as_mut();
+    require!(
+        !bid.escrowed && bid.pending_amount == 0 && !bid.has_locked_tokens(),
+        LaunchpadError::BidNotSettled
+    );
+    require!(
+        (bid.escrow_amount == 0 && bid.escrow_fee == 0) || ctx.accounts.auction.is_soft_cap_met(),
+        LaunchpadError::BidNotSettled
+    );
+
     if (!bid.seller_initialized && ctx.accounts.initializer.key() == bid.owner)
         || (bid.seller_initialized && ctx.accounts.initializer.key() == ctx.accounts.auction.owner)
     {
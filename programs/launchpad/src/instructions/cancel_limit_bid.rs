@@ -0,0 +1,133 @@
+//! CancelLimitBid instruction handler
+
+use {
+    crate::{
+        error::LaunchpadError,
+        math,
+        state::{
+            auction::Auction, bid::Bid, custody::Custody, launchpad::Launchpad,
+            limit_book::LimitBook,
+        },
+    },
+    anchor_lang::prelude::*,
+    anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface},
+};
+
+#[derive(Accounts)]
+pub struct CancelLimitBid<'info> {
+    #[account()]
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        constraint = refund_account.mint == pricing_custody.mint,
+        has_one = owner
+    )]
+    pub refund_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// CHECK: empty PDA, authority for token accounts
+    #[account(
+        seeds = [b"transfer_authority"],
+        bump = launchpad.transfer_authority_bump
+    )]
+    pub transfer_authority: AccountInfo<'info>,
+
+    #[account(
+        seeds = [b"launchpad"],
+        bump = launchpad.launchpad_bump
+    )]
+    pub launchpad: Box<Account<'info, Launchpad>>,
+
+    #[account(
+        seeds = [b"auction",
+                 auction.common.name.as_bytes()],
+        bump = auction.bump
+    )]
+    pub auction: Box<Account<'info, Auction>>,
+
+    #[account(
+        mut,
+        seeds = [b"limit_book",
+                 auction.key().as_ref()],
+        bump = limit_book.load()?.bump
+    )]
+    pub limit_book: AccountLoader<'info, LimitBook>,
+
+    #[account(
+        mut,
+        has_one = owner,
+        constraint = bid.escrow_custody == pricing_custody.key(),
+        seeds = [b"bid",
+                 owner.key().as_ref(),
+                 auction.key().as_ref()],
+        bump = bid.bump
+    )]
+    pub bid: Box<Account<'info, Bid>>,
+
+    #[account(
+        constraint = pricing_custody.key() == auction.pricing.custody,
+        seeds = [b"custody",
+                 pricing_custody.mint.as_ref()],
+        bump = pricing_custody.bump
+    )]
+    pub pricing_custody: Box<Account<'info, Custody>>,
+
+    #[account(
+        mut,
+        constraint = pricing_token_account.key() == pricing_custody.token_account.key()
+    )]
+    pub pricing_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        constraint = pricing_token_mint.key() == pricing_custody.mint
+    )]
+    pub pricing_token_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct CancelLimitBidParams {}
+
+pub fn cancel_limit_bid(
+    ctx: Context<CancelLimitBid>,
+    _params: &CancelLimitBidParams,
+) -> Result<()> {
+    // load accounts
+    msg!("Load accounts");
+    let bid = ctx.accounts.bid.as_mut();
+
+    // escrow of batch and English auction bids is released upon settlement
+    require!(
+        ctx.accounts.auction.accepts_limit_bids() && bid.escrowed,
+        LaunchpadError::InvalidBidAddress
+    );
+
+    // take the unfilled part off the book, fully filled orders have already
+    // been removed by crank_fills and evicted ones by place_bid
+    ctx.accounts.limit_book.load_mut()?.remove(&bid.key());
+
+    let refund_amount = math::checked_add(bid.escrow_amount, bid.escrow_fee)?;
+
+    // update user's bid
+    msg!("Update user's bid");
+    bid.escrowed = false;
+    bid.escrow_amount = 0;
+    bid.escrow_fee = 0;
+
+    // refund unused escrow
+    if refund_amount > 0 {
+        msg!("Refund {} to the user", refund_amount);
+        ctx.accounts.launchpad.transfer_tokens(
+            ctx.accounts.pricing_token_account.to_account_info(),
+            ctx.accounts.refund_account.to_account_info(),
+            ctx.accounts.pricing_token_mint.to_account_info(),
+            ctx.accounts.transfer_authority.to_account_info(),
+            ctx.accounts.token_program.to_account_info(),
+            refund_amount,
+            ctx.accounts.pricing_custody.decimals,
+        )?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,162 @@
+//! ClaimRefund instruction handler
+
+use {
+    crate::{
+        error::LaunchpadError,
+        math,
+        state::{auction::Auction, bid::Bid, custody::Custody, launchpad::Launchpad},
+    },
+    anchor_lang::prelude::*,
+    anchor_spl::token_interface::{self, Mint, TokenAccount, TokenInterface, TransferChecked},
+};
+
+#[derive(Accounts)]
+pub struct ClaimRefund<'info> {
+    #[account()]
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        constraint = refund_account.mint == payment_custody.mint,
+        has_one = owner
+    )]
+    pub refund_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        constraint = token_account.mint == dispensing_custody.mint,
+        has_one = owner
+    )]
+    pub token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// CHECK: empty PDA, authority for token accounts
+    #[account(
+        seeds = [b"transfer_authority"],
+        bump = launchpad.transfer_authority_bump
+    )]
+    pub transfer_authority: AccountInfo<'info>,
+
+    #[account(
+        seeds = [b"launchpad"],
+        bump = launchpad.launchpad_bump
+    )]
+    pub launchpad: Box<Account<'info, Launchpad>>,
+
+    #[account(
+        mut,
+        seeds = [b"auction",
+                 auction.common.name.as_bytes()],
+        bump = auction.bump
+    )]
+    pub auction: Box<Account<'info, Auction>>,
+
+    #[account(
+        mut,
+        has_one = owner,
+        constraint = bid.escrow_custody == payment_custody.key(),
+        seeds = [b"bid",
+                 owner.key().as_ref(),
+                 auction.key().as_ref()],
+        bump = bid.bump
+    )]
+    pub bid: Box<Account<'info, Bid>>,
+
+    #[account(
+        mut,
+        constraint = dispensing_custody.key() == auction.tokens[0].account
+    )]
+    pub dispensing_custody: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        constraint = dispensing_custody_mint.key() == dispensing_custody.mint
+    )]
+    pub dispensing_custody_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        constraint = payment_custody.key() == auction.pricing.custody,
+        seeds = [b"custody",
+                 payment_custody.mint.as_ref()],
+        bump = payment_custody.bump
+    )]
+    pub payment_custody: Box<Account<'info, Custody>>,
+
+    #[account(
+        mut,
+        constraint = payment_token_account.key() == payment_custody.token_account.key()
+    )]
+    pub payment_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        constraint = payment_token_mint.key() == payment_custody.mint
+    )]
+    pub payment_token_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    token_program: Interface<'info, TokenInterface>,
+    dispensing_token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct ClaimRefundParams {}
+
+pub fn claim_refund(ctx: Context<ClaimRefund>, _params: &ClaimRefundParams) -> Result<()> {
+    // load accounts
+    msg!("Load accounts");
+    let auction = ctx.accounts.auction.as_mut();
+    let bid = ctx.accounts.bid.as_mut();
+
+    let curtime = auction.get_time()?;
+    require!(
+        auction.is_ended(curtime, true),
+        LaunchpadError::AuctionInProgress
+    );
+    require!(
+        auction.is_soft_cap_failed(curtime),
+        LaunchpadError::SoftCapMet
+    );
+
+    let refund_amount = math::checked_add(bid.escrow_amount, bid.escrow_fee)?;
+    require_gt!(refund_amount, 0u64, LaunchpadError::InvalidTokenAmount);
+    let return_amount = math::checked_mul(bid.filled, auction.pricing.unit_size)?;
+
+    // update user's bid
+    msg!("Update user's bid");
+    auction.stats.escrow.amount =
+        math::checked_sub(auction.stats.escrow.amount, bid.escrow_amount)?;
+    auction.stats.escrow.fee = math::checked_sub(auction.stats.escrow.fee, bid.escrow_fee)?;
+    bid.filled = 0;
+    bid.escrow_amount = 0;
+    bid.escrow_fee = 0;
+
+    // return purchased tokens to the auction
+    if return_amount > 0 {
+        msg!("Return {} tokens to the auction", return_amount);
+        let context = CpiContext::new(
+            ctx.accounts.dispensing_token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.token_account.to_account_info(),
+                mint: ctx.accounts.dispensing_custody_mint.to_account_info(),
+                to: ctx.accounts.dispensing_custody.to_account_info(),
+                authority: ctx.accounts.owner.to_account_info(),
+            },
+        );
+        token_interface::transfer_checked(
+            context,
+            return_amount,
+            ctx.accounts.dispensing_custody_mint.decimals,
+        )?;
+    }
+
+    // refund payment and fee
+    msg!("Refund {} to the user", refund_amount);
+    ctx.accounts.launchpad.transfer_tokens(
+        ctx.accounts.payment_token_account.to_account_info(),
+        ctx.accounts.refund_account.to_account_info(),
+        ctx.accounts.payment_token_mint.to_account_info(),
+        ctx.accounts.transfer_authority.to_account_info(),
+        ctx.accounts.token_program.to_account_info(),
+        refund_amount,
+        ctx.accounts.payment_custody.decimals,
+    )?;
+
+    Ok(())
+}
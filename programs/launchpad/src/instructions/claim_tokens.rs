@@ -0,0 +1,577 @@
+//! ClaimTokens instruction handler
+
+use {
+    crate::{
+        error::LaunchpadError,
+        math,
+        state::{
+            self,
+            auction::{Auction, PricingModel},
+            bid::Bid,
+            custody::Custody,
+            launchpad::Launchpad,
+            seller_balance::SellerBalance,
+        },
+    },
+    anchor_lang::prelude::*,
+    anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface},
+    solana_program::sysvar,
+};
+
+#[derive(Accounts)]
+pub struct ClaimTokens<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        constraint = refund_account.mint == payment_custody.mint,
+        has_one = owner
+    )]
+    pub refund_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// CHECK: empty PDA, authority for token accounts
+    #[account(
+        seeds = [b"transfer_authority"],
+        bump = launchpad.transfer_authority_bump
+    )]
+    pub transfer_authority: AccountInfo<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"launchpad"],
+        bump = launchpad.launchpad_bump
+    )]
+    pub launchpad: Box<Account<'info, Launchpad>>,
+
+    #[account(
+        mut,
+        seeds = [b"auction",
+                 auction.common.name.as_bytes()],
+        bump = auction.bump
+    )]
+    pub auction: Box<Account<'info, Auction>>,
+
+    #[account(
+        init_if_needed,
+        payer = owner,
+        space = SellerBalance::LEN,
+        seeds = [b"seller_balance",
+                 auction.owner.as_ref(),
+                 payment_custody.key().as_ref()],
+        bump
+    )]
+    pub seller_balance: Box<Account<'info, SellerBalance>>,
+
+    #[account(
+        mut,
+        has_one = owner,
+        constraint = bid.escrow_custody == payment_custody.key(),
+        seeds = [b"bid",
+                 owner.key().as_ref(),
+                 auction.key().as_ref()],
+        bump = bid.bump
+    )]
+    pub bid: Box<Account<'info, Bid>>,
+
+    #[account(
+        mut,
+        seeds = [b"custody",
+                 payment_custody.mint.as_ref()],
+        bump = payment_custody.bump
+    )]
+    pub payment_custody: Box<Account<'info, Custody>>,
+
+    /// CHECK: oracle account for the payment token
+    #[account(
+        constraint = payment_oracle_account.key() == payment_custody.oracle_account
+    )]
+    pub payment_oracle_account: AccountInfo<'info>,
+
+    #[account(
+        mut,
+        constraint = payment_token_account.key() == payment_custody.token_account.key()
+    )]
+    pub payment_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        constraint = payment_token_mint.key() == payment_custody.mint
+    )]
+    pub payment_token_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// CHECK: account constraints checked in account trait
+    #[account(
+        address = sysvar::slot_hashes::id()
+    )]
+    recent_slothashes: UncheckedAccount<'info>,
+
+    /// CHECK: account constraints checked in account trait
+    #[account(
+        address = sysvar::instructions::id()
+    )]
+    instructions: UncheckedAccount<'info>,
+
+    system_program: Program<'info, System>,
+    token_program: Interface<'info, TokenInterface>,
+    dispensing_token_program: Interface<'info, TokenInterface>,
+    // remaining accounts:
+    //   1 to Auction::MAX_TOKENS user's token receiving accounts (write, unsigned)
+    //   1 to Auction::MAX_TOKENS dispensing custody addresses (write, unsigned)
+    //   1 to Auction::MAX_TOKENS dispensing custody mints (read-only, unsigned)
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct ClaimTokensParams {}
+
+pub fn claim_tokens<'info>(
+    ctx: Context<'_, '_, 'info, 'info, ClaimTokens<'info>>,
+    _params: &ClaimTokensParams,
+) -> Result<()> {
+    // load accounts
+    msg!("Load accounts");
+    let launchpad = ctx.accounts.launchpad.as_mut();
+    let auction = ctx.accounts.auction.as_mut();
+    let bid = ctx.accounts.bid.as_mut();
+    let seller_balance = ctx.accounts.seller_balance.as_mut();
+    let payment_custody = ctx.accounts.payment_custody.as_mut();
+
+    // units bought in the deferred claim mode have been paid for upon the bid,
+    // the payment is released to the seller as the tokens are dispensed here
+    if auction.deferred_claim {
+        let curtime = auction.get_time()?;
+        require!(
+            curtime >= auction.get_claim_time(),
+            LaunchpadError::AuctionInProgress
+        );
+        require_gt!(bid.pending_amount, 0u64, LaunchpadError::InvalidTokenAmount);
+
+        // validate dispensing and receiving accounts
+        msg!("Validate dispensing and receiving accounts");
+        let (receiving_accounts, dispensing_custodies, dispensing_mints) =
+            state::load_dispensing_accounts(
+                ctx.remaining_accounts,
+                auction,
+                &ctx.accounts.owner.key(),
+            )?;
+        let dispenser_amounts: Vec<u64> = dispensing_custodies
+            .iter()
+            .map(|dispenser| dispenser.amount)
+            .collect();
+
+        // basket auctions dispense all tokens, otherwise pick a random token
+        // and route the rest of the claim to other dispensers, the part of
+        // the claim that can't be served is refunded
+        msg!("Select tokens to dispense");
+        let claim_amount = bid.pending_amount;
+        let (token_fills, fill_amount): (Vec<(usize, u64)>, u64) = if auction.basket {
+            let fill_amount =
+                std::cmp::min(claim_amount, auction.get_basket_units(&dispenser_amounts)?);
+            (
+                (0..auction.num_tokens as usize)
+                    .map(|token_num| (token_num, fill_amount))
+                    .collect(),
+                fill_amount,
+            )
+        } else {
+            let rand_seed = if auction.num_tokens == 1 {
+                0
+            } else {
+                // the selection must not be observable by other instructions
+                state::check_single_instruction(&ctx.accounts.instructions.to_account_info())?;
+                state::get_random_seed(&ctx.accounts.recent_slothashes)?
+            };
+            let token_num = match auction.select_token(&dispenser_amounts, 1, rand_seed)? {
+                Some(token) => token,
+                None => dispenser_amounts
+                    .iter()
+                    .enumerate()
+                    .max_by_key(|(_, &amount)| amount)
+                    .map(|(token, _)| token)
+                    .unwrap_or(0),
+            };
+            let token_fills = auction.route_claim(&dispenser_amounts, token_num, claim_amount)?;
+            let fill_amount = token_fills.iter().sum();
+            (
+                token_fills
+                    .into_iter()
+                    .enumerate()
+                    .filter(|(_, units)| *units > 0)
+                    .collect(),
+                fill_amount,
+            )
+        };
+
+        // release escrowed payment and fee for the served units and refund the rest
+        let payment_amount = get_escrow_share(bid.escrow_amount, fill_amount, claim_amount)?;
+        let fee_amount = get_escrow_share(bid.escrow_fee, fill_amount, claim_amount)?;
+        let refund_amount = math::checked_sub(
+            math::checked_add(bid.escrow_amount, bid.escrow_fee)?,
+            math::checked_add(payment_amount, fee_amount)?,
+        )?;
+
+        if fee_amount > 0 {
+            state::collect_escrowed_fee(
+                launchpad,
+                payment_custody,
+                &ctx.accounts.payment_oracle_account,
+                fee_amount,
+                false,
+                curtime,
+            )?;
+        }
+
+        // update user's bid
+        msg!("Update user's bid");
+        let unserved_amount = math::checked_sub(claim_amount, fill_amount)?;
+        auction.stats.pending_amount =
+            math::checked_sub(auction.stats.pending_amount, claim_amount)?;
+        bid.pending_amount = 0;
+        bid.filled = math::checked_sub(bid.filled, unserved_amount)?;
+        bid.escrowed = false;
+        bid.escrow_amount = 0;
+        bid.escrow_fee = 0;
+        if unserved_amount > 0 {
+            msg!("{} units couldn't be served", unserved_amount);
+            let price_adjustment_bps = auction
+                .payment
+                .get_price_adjustment_bps(&payment_custody.key())?;
+            auction.revert_stats(
+                unserved_amount,
+                bid.fill_price,
+                price_adjustment_bps,
+                bid.whitelisted,
+            )?;
+        }
+
+        // update seller's balance
+        msg!("Update seller's balance");
+        init_seller_balance(
+            seller_balance,
+            auction,
+            payment_custody,
+            ctx.bumps.seller_balance,
+        )?;
+        seller_balance.balance = math::checked_add(seller_balance.balance, payment_amount)?;
+
+        // refund payment for unserved units
+        if refund_amount > 0 {
+            msg!("Refund {} to the user", refund_amount);
+            launchpad.transfer_tokens(
+                ctx.accounts.payment_token_account.to_account_info(),
+                ctx.accounts.refund_account.to_account_info(),
+                ctx.accounts.payment_token_mint.to_account_info(),
+                ctx.accounts.transfer_authority.to_account_info(),
+                ctx.accounts.token_program.to_account_info(),
+                refund_amount,
+                payment_custody.decimals,
+            )?;
+        }
+
+        // transfer purchased tokens to the user
+        for (token_num, units) in token_fills {
+            if units == 0 {
+                continue;
+            }
+            bid.token_fills[token_num] = math::checked_add(bid.token_fills[token_num], units)?;
+            auction.stats.token_fills[token_num] =
+                math::checked_add(auction.stats.token_fills[token_num], units)?;
+            let unit_size = if auction.basket {
+                auction.tokens[token_num].basket_amount
+            } else {
+                auction.pricing.unit_size
+            };
+            let transfer_amount = math::checked_mul(units, unit_size)?;
+            msg!("Transfer {} tokens to the user", transfer_amount);
+            launchpad.transfer_tokens(
+                dispensing_custodies[token_num].to_account_info(),
+                receiving_accounts[token_num].to_account_info(),
+                dispensing_mints[token_num].to_account_info(),
+                ctx.accounts.transfer_authority.to_account_info(),
+                ctx.accounts.dispensing_token_program.to_account_info(),
+                transfer_amount,
+                dispensing_mints[token_num].decimals,
+            )?;
+        }
+
+        return Ok(());
+    }
+
+    require!(
+        auction.pricing.pricing_model == PricingModel::BatchAuction
+            || auction.pricing.pricing_model == PricingModel::EnglishAuction,
+        LaunchpadError::UnsupportedPricingModel
+    );
+    require!(bid.escrowed, LaunchpadError::InvalidTokenAmount);
+
+    // outbid english auction bids that couldn't be refunded at the time of
+    // outbidding are returned in full, the leading bid is closed by finalize_auction
+    if auction.pricing.pricing_model == PricingModel::EnglishAuction {
+        require_keys_neq!(
+            bid.key(),
+            auction.stats.leading.bid,
+            LaunchpadError::AuctionNotSettled
+        );
+        let refund_amount = math::checked_add(bid.escrow_amount, bid.escrow_fee)?;
+
+        bid.escrowed = false;
+        bid.escrow_amount = 0;
+        bid.escrow_fee = 0;
+
+        if refund_amount > 0 {
+            msg!("Refund {} to the user", refund_amount);
+            launchpad.transfer_tokens(
+                ctx.accounts.payment_token_account.to_account_info(),
+                ctx.accounts.refund_account.to_account_info(),
+                ctx.accounts.payment_token_mint.to_account_info(),
+                ctx.accounts.transfer_authority.to_account_info(),
+                ctx.accounts.token_program.to_account_info(),
+                refund_amount,
+                payment_custody.decimals,
+            )?;
+        }
+
+        return Ok(());
+    }
+
+    // sealed bids that haven't been revealed are charged with invalid bid fee
+    if bid.sealed {
+        let curtime = auction.get_time()?;
+        require!(
+            curtime >= auction.common.reveal_end_time,
+            LaunchpadError::AuctionInProgress
+        );
+        let fee_amount = launchpad
+            .fees
+            .invalid_bid
+            .get_fee_amount(bid.escrow_amount)?;
+        let refund_amount = math::checked_sub(bid.escrow_amount, fee_amount)?;
+
+        bid.escrowed = false;
+        bid.escrow_amount = 0;
+        bid.sealed = false;
+        bid.commitment = [0; 32];
+
+        if fee_amount > 0 {
+            msg!("Collect unrevealed bid fee {}", fee_amount);
+            state::collect_escrowed_fee(
+                launchpad,
+                payment_custody,
+                &ctx.accounts.payment_oracle_account,
+                fee_amount,
+                true,
+                curtime,
+            )?;
+        }
+
+        if refund_amount > 0 {
+            msg!("Refund {} to the user", refund_amount);
+            launchpad.transfer_tokens(
+                ctx.accounts.payment_token_account.to_account_info(),
+                ctx.accounts.refund_account.to_account_info(),
+                ctx.accounts.payment_token_mint.to_account_info(),
+                ctx.accounts.transfer_authority.to_account_info(),
+                ctx.accounts.token_program.to_account_info(),
+                refund_amount,
+                payment_custody.decimals,
+            )?;
+        }
+
+        return Ok(());
+    }
+
+    require!(auction.is_settled(), LaunchpadError::AuctionNotSettled);
+
+    // validate dispensing and receiving accounts
+    msg!("Validate dispensing and receiving accounts");
+    let (receiving_accounts, dispensing_custodies, dispensing_mints) =
+        state::load_dispensing_accounts(
+            ctx.remaining_accounts,
+            auction,
+            &ctx.accounts.owner.key(),
+        )?;
+
+    // compute fill amount at the clearing price
+    let fill_price = auction.stats.clearing.clearing_price;
+    let mut fill_amount = auction.get_clearing_fill_amount(bid.bid_price, bid.bid_amount)?;
+
+    // pick a random token to dispense according to token ratios, skip dispensers
+    // that can't cover the fill or fall back to the largest one and route the
+    // rest of the fill to other dispensers
+    msg!("Select tokens to dispense");
+    let rand_seed = if auction.num_tokens == 1 {
+        0
+    } else {
+        // the selection must not be observable by other instructions
+        state::check_single_instruction(&ctx.accounts.instructions.to_account_info())?;
+        state::get_random_seed(&ctx.accounts.recent_slothashes)?
+    };
+    let dispenser_amounts: Vec<u64> = dispensing_custodies
+        .iter()
+        .map(|dispenser| dispenser.amount)
+        .collect();
+    let token_num = match auction.select_token(&dispenser_amounts, fill_amount, rand_seed)? {
+        Some(token) => token,
+        None => dispenser_amounts
+            .iter()
+            .enumerate()
+            .max_by_key(|(_, &amount)| amount)
+            .map(|(token, _)| token)
+            .unwrap_or(0),
+    };
+    let token_fills = auction.route_claim(&dispenser_amounts, token_num, fill_amount)?;
+    fill_amount = token_fills.iter().sum();
+
+    // payment and fee were escrowed at the bid price, charge proportionally
+    // to the filled amount at the clearing price and refund the rest
+    let payment_amount = if fill_amount > 0 && bid.escrow_amount > 0 {
+        std::cmp::min(
+            math::checked_as_u64(math::checked_ceil_div(
+                math::checked_mul(
+                    bid.escrow_amount as u128,
+                    math::checked_mul(fill_amount as u128, fill_price as u128)?,
+                )?,
+                math::checked_mul(bid.bid_amount as u128, bid.bid_price as u128)?,
+            )?)?,
+            bid.escrow_amount,
+        )
+    } else {
+        0
+    };
+    let fee_amount = std::cmp::min(
+        launchpad.fees.trade.get_fee_amount(payment_amount)?,
+        bid.escrow_fee,
+    );
+    let refund_amount = math::checked_sub(
+        math::checked_add(bid.escrow_amount, bid.escrow_fee)?,
+        math::checked_add(payment_amount, fee_amount)?,
+    )?;
+
+    if fee_amount > 0 {
+        state::collect_escrowed_fee(
+            launchpad,
+            payment_custody,
+            &ctx.accounts.payment_oracle_account,
+            fee_amount,
+            false,
+            auction.get_time()?,
+        )?;
+    }
+
+    // update user's bid
+    msg!("Update user's bid");
+    bid.escrowed = false;
+    bid.escrow_amount = 0;
+    bid.escrow_fee = 0;
+    bid.filled = math::checked_add(bid.filled, fill_amount)?;
+    bid.fill_time = auction.get_time()?;
+    bid.fill_price = fill_price;
+    bid.fill_amount = fill_amount;
+    for (token_num, &units) in token_fills.iter().enumerate() {
+        bid.token_fills[token_num] = math::checked_add(bid.token_fills[token_num], units)?;
+    }
+
+    // update seller's balance
+    msg!("Update seller's balance");
+    init_seller_balance(
+        seller_balance,
+        auction,
+        payment_custody,
+        ctx.bumps.seller_balance,
+    )?;
+    seller_balance.balance = math::checked_add(seller_balance.balance, payment_amount)?;
+
+    // update auction stats
+    if fill_amount > 0 {
+        msg!("Update auction stats");
+        let curtime = auction.get_time()?;
+        let price_adjustment_bps = auction
+            .payment
+            .get_price_adjustment_bps(&payment_custody.key())?;
+        auction.update_stats(
+            fill_amount,
+            fill_price,
+            price_adjustment_bps,
+            bid.whitelisted,
+            curtime,
+        )?;
+        for (token_num, &units) in token_fills.iter().enumerate() {
+            auction.stats.token_fills[token_num] =
+                math::checked_add(auction.stats.token_fills[token_num], units)?;
+        }
+        auction.stats.clearing.unclaimed_amount = auction
+            .stats
+            .clearing
+            .unclaimed_amount
+            .saturating_sub(fill_amount);
+    }
+
+    // refund unused payment
+    if refund_amount > 0 {
+        msg!("Refund {} to the user", refund_amount);
+        launchpad.transfer_tokens(
+            ctx.accounts.payment_token_account.to_account_info(),
+            ctx.accounts.refund_account.to_account_info(),
+            ctx.accounts.payment_token_mint.to_account_info(),
+            ctx.accounts.transfer_authority.to_account_info(),
+            ctx.accounts.token_program.to_account_info(),
+            refund_amount,
+            payment_custody.decimals,
+        )?;
+    }
+
+    // transfer purchased tokens to the user
+    for (token_num, units) in token_fills.into_iter().enumerate() {
+        if units == 0 {
+            continue;
+        }
+        let transfer_amount = math::checked_mul(units, auction.pricing.unit_size)?;
+        msg!("Transfer {} tokens to the user", transfer_amount);
+        launchpad.transfer_tokens(
+            dispensing_custodies[token_num].to_account_info(),
+            receiving_accounts[token_num].to_account_info(),
+            dispensing_mints[token_num].to_account_info(),
+            ctx.accounts.transfer_authority.to_account_info(),
+            ctx.accounts.dispensing_token_program.to_account_info(),
+            transfer_amount,
+            dispensing_mints[token_num].decimals,
+        )?;
+    }
+
+    Ok(())
+}
+
+fn init_seller_balance(
+    seller_balance: &mut SellerBalance,
+    auction: &Auction,
+    payment_custody: &Account<Custody>,
+    bump: u8,
+) -> Result<()> {
+    if seller_balance.bump == 0 {
+        seller_balance.owner = auction.owner;
+        seller_balance.custody = payment_custody.key();
+        seller_balance.bump = bump;
+    } else if seller_balance.owner != auction.owner
+        || seller_balance.custody != payment_custody.key()
+    {
+        return err!(LaunchpadError::InvalidSellerBalanceAddress);
+    }
+    Ok(())
+}
+
+/// Returns the part of the escrowed amount that pays for fill_amount out of
+/// claim_amount units
+fn get_escrow_share(escrow_amount: u64, fill_amount: u64, claim_amount: u64) -> Result<u64> {
+    if fill_amount == 0 || escrow_amount == 0 {
+        return Ok(0);
+    }
+    if fill_amount >= claim_amount {
+        return Ok(escrow_amount);
+    }
+    Ok(std::cmp::min(
+        math::checked_as_u64(math::checked_ceil_div(
+            math::checked_mul(escrow_amount as u128, fill_amount as u128)?,
+            claim_amount as u128,
+        )?)?,
+        escrow_amount,
+    ))
+}
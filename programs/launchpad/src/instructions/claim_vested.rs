@@ -0,0 +1,114 @@
+//! ClaimVested instruction handler
+
+use {
+    crate::{
+        error::LaunchpadError,
+        math,
+        state::{auction::Auction, bid::Bid},
+    },
+    anchor_lang::prelude::*,
+    anchor_spl::token_interface::{self, Mint, TokenAccount, TokenInterface, TransferChecked},
+};
+
+#[derive(Accounts)]
+pub struct ClaimVested<'info> {
+    #[account()]
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        constraint = receiving_account.mint == dispensing_custody.mint,
+        has_one = owner
+    )]
+    pub receiving_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        constraint = escrow_account.owner == bid.key(),
+        seeds = [b"vesting",
+                 bid.key().as_ref(),
+                 dispensing_custody.mint.as_ref()],
+        bump
+    )]
+    pub escrow_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        seeds = [b"auction",
+                 auction.common.name.as_bytes()],
+        bump = auction.bump
+    )]
+    pub auction: Box<Account<'info, Auction>>,
+
+    #[account(
+        mut,
+        has_one = owner,
+        seeds = [b"bid",
+                 owner.key().as_ref(),
+                 auction.key().as_ref()],
+        bump = bid.bump
+    )]
+    pub bid: Box<Account<'info, Bid>>,
+
+    pub dispensing_custody: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        constraint = dispensing_custody_mint.key() == dispensing_custody.mint
+    )]
+    pub dispensing_custody_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct ClaimVestedParams {}
+
+pub fn claim_vested(ctx: Context<ClaimVested>, _params: &ClaimVestedParams) -> Result<()> {
+    // load accounts
+    msg!("Load accounts");
+    let auction = ctx.accounts.auction.as_ref();
+    let bid = ctx.accounts.bid.as_mut();
+
+    let token_num = auction.tokens[..auction.num_tokens as usize]
+        .iter()
+        .position(|token| token.account == ctx.accounts.dispensing_custody.key())
+        .ok_or(LaunchpadError::InvalidDispenserAddress)?;
+
+    let curtime = auction.get_time()?;
+    let claim_amount = bid.vesting[token_num].get_claimable_amount(
+        &auction.vesting,
+        auction.get_claim_time(),
+        curtime,
+    )?;
+    require_gt!(claim_amount, 0u64, LaunchpadError::InvalidTokenAmount);
+
+    // update user's bid
+    msg!("Update user's bid");
+    bid.vesting[token_num].claimed =
+        math::checked_add(bid.vesting[token_num].claimed, claim_amount)?;
+
+    // release unlocked tokens to the user
+    msg!("Transfer {} tokens to the user", claim_amount);
+    let owner_key = ctx.accounts.owner.key();
+    let auction_key = auction.key();
+    let authority_seeds: &[&[&[u8]]] = &[&[
+        b"bid",
+        owner_key.as_ref(),
+        auction_key.as_ref(),
+        &[bid.bump],
+    ]];
+    let context = CpiContext::new(
+        ctx.accounts.token_program.to_account_info(),
+        TransferChecked {
+            from: ctx.accounts.escrow_account.to_account_info(),
+            mint: ctx.accounts.dispensing_custody_mint.to_account_info(),
+            to: ctx.accounts.receiving_account.to_account_info(),
+            authority: bid.to_account_info(),
+        },
+    )
+    .with_signer(authority_seeds);
+    token_interface::transfer_checked(
+        context,
+        claim_amount,
+        ctx.accounts.dispensing_custody_mint.decimals,
+    )
+}
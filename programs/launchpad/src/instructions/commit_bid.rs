@@ -0,0 +1,156 @@
+//! CommitBid instruction handler
+
+use {
+    crate::{
+        error::LaunchpadError,
+        math,
+        state::{auction::Auction, bid::Bid, custody::Custody, launchpad::Launchpad},
+    },
+    anchor_lang::prelude::*,
+    anchor_spl::token_interface::{self, Mint, TokenAccount, TokenInterface, TransferChecked},
+};
+
+#[derive(Accounts)]
+pub struct CommitBid<'info> {
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        constraint = funding_account.mint == payment_custody.mint,
+        has_one = owner
+    )]
+    pub funding_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        seeds = [b"launchpad"],
+        bump = launchpad.launchpad_bump
+    )]
+    pub launchpad: Box<Account<'info, Launchpad>>,
+
+    #[account(
+        seeds = [b"auction",
+                 auction.common.name.as_bytes()],
+        bump = auction.bump
+    )]
+    pub auction: Box<Account<'info, Auction>>,
+
+    #[account(
+        init_if_needed,
+        payer = owner,
+        space = Bid::LEN,
+        seeds = [b"bid",
+                 owner.key().as_ref(),
+                 auction.key().as_ref()],
+        bump
+    )]
+    pub bid: Box<Account<'info, Bid>>,
+
+    #[account(
+        seeds = [b"custody",
+                 payment_custody.mint.as_ref()],
+        bump = payment_custody.bump
+    )]
+    pub payment_custody: Box<Account<'info, Custody>>,
+
+    #[account(
+        mut,
+        constraint = payment_token_account.key() == payment_custody.token_account.key()
+    )]
+    pub payment_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        constraint = payment_token_mint.key() == payment_custody.mint
+    )]
+    pub payment_token_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    system_program: Program<'info, System>,
+    token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct CommitBidParams {
+    // see Bid::get_commitment()
+    commitment: [u8; 32],
+    // payment tokens to be escrowed, must cover bid's payment and fee
+    deposit: u64,
+}
+
+pub fn commit_bid(ctx: Context<CommitBid>, params: &CommitBidParams) -> Result<()> {
+    require!(
+        ctx.accounts.launchpad.permissions.allow_new_bids,
+        LaunchpadError::BidsNotAllowed
+    );
+
+    // validate inputs
+    require_gt!(params.deposit, 0u64, LaunchpadError::InvalidTokenAmount);
+
+    let auction = &ctx.accounts.auction;
+    let bid = ctx.accounts.bid.as_mut();
+
+    require!(auction.is_sealed(), LaunchpadError::SealedBidsNotAllowed);
+    require!(!bid.escrowed, LaunchpadError::BidAlreadyPlaced);
+    require!(
+        auction
+            .payment
+            .is_accepted(&ctx.accounts.payment_custody.key()),
+        LaunchpadError::InvalidPaymentCustody
+    );
+
+    // check if auction is active
+    let curtime = auction.get_time()?;
+    require!(
+        auction.is_started(curtime, bid.whitelisted),
+        LaunchpadError::AuctionNotStarted
+    );
+    require!(
+        !auction.is_ended(curtime, bid.whitelisted),
+        LaunchpadError::AuctionEnded
+    );
+
+    // escrow the deposit
+    msg!("Escrow deposit {}", params.deposit);
+    let balance_before = ctx.accounts.payment_token_account.amount;
+    let context = CpiContext::new(
+        ctx.accounts.token_program.to_account_info(),
+        TransferChecked {
+            from: ctx.accounts.funding_account.to_account_info(),
+            mint: ctx.accounts.payment_token_mint.to_account_info(),
+            to: ctx.accounts.payment_token_account.to_account_info(),
+            authority: ctx.accounts.owner.to_account_info(),
+        },
+    );
+    token_interface::transfer_checked(
+        context,
+        params.deposit,
+        ctx.accounts.payment_custody.decimals,
+    )?;
+
+    // only the amount received after Token-2022 transfer fees is escrowed
+    ctx.accounts.payment_token_account.reload()?;
+    let received_amount =
+        math::checked_sub(ctx.accounts.payment_token_account.amount, balance_before)?;
+
+    // record the commitment
+    if bid.bump == 0 {
+        bid.owner = ctx.accounts.owner.key();
+        bid.auction = auction.key();
+        bid.whitelisted = false;
+        bid.seller_initialized = false;
+        bid.bump = ctx.bumps.bid;
+    } else if bid.owner != ctx.accounts.owner.key() || bid.auction != auction.key() {
+        return err!(LaunchpadError::InvalidBidAddress);
+    }
+
+    bid.bid_time = curtime;
+    bid.bid_price = 0;
+    bid.bid_amount = 0;
+    bid.escrowed = true;
+    bid.escrow_custody = ctx.accounts.payment_custody.key();
+    bid.escrow_amount = received_amount;
+    bid.escrow_fee = 0;
+    bid.sealed = true;
+    bid.commitment = params.commitment;
+
+    Ok(())
+}
@@ -0,0 +1,388 @@
+//! CrankFills instruction handler
+
+use {
+    crate::{
+        error::LaunchpadError,
+        math,
+        state::{
+            self,
+            auction::Auction,
+            bid::Bid,
+            custody::Custody,
+            launchpad::Launchpad,
+            limit_book::{LimitBook, LimitOrder},
+            price_history::PriceHistory,
+            seller_balance::SellerBalance,
+        },
+    },
+    anchor_lang::prelude::*,
+    anchor_spl::{
+        associated_token,
+        token_interface::{TokenAccount, TokenInterface},
+    },
+    solana_program::sysvar,
+};
+
+#[derive(Accounts)]
+pub struct CrankFills<'info> {
+    #[account()]
+    pub cranker: Signer<'info>,
+
+    /// CHECK: empty PDA, authority for token accounts
+    #[account(
+        seeds = [b"transfer_authority"],
+        bump = launchpad.transfer_authority_bump
+    )]
+    pub transfer_authority: AccountInfo<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"launchpad"],
+        bump = launchpad.launchpad_bump
+    )]
+    pub launchpad: Box<Account<'info, Launchpad>>,
+
+    #[account(
+        mut,
+        seeds = [b"auction",
+                 auction.common.name.as_bytes()],
+        bump = auction.bump
+    )]
+    pub auction: Box<Account<'info, Auction>>,
+
+    #[account(
+        mut,
+        seeds = [b"limit_book",
+                 auction.key().as_ref()],
+        bump = limit_book.load()?.bump
+    )]
+    pub limit_book: AccountLoader<'info, LimitBook>,
+
+    #[account(
+        mut,
+        seeds = [b"price_history",
+                 auction.key().as_ref()],
+        bump = price_history.load()?.bump
+    )]
+    pub price_history: AccountLoader<'info, PriceHistory>,
+
+    #[account(
+        mut,
+        seeds = [b"seller_balance",
+                 auction.owner.as_ref(),
+                 pricing_custody.key().as_ref()],
+        bump = seller_balance.bump
+    )]
+    pub seller_balance: Box<Account<'info, SellerBalance>>,
+
+    #[account(
+        mut,
+        constraint = pricing_custody.key() == auction.pricing.custody,
+        seeds = [b"custody",
+                 pricing_custody.mint.as_ref()],
+        bump = pricing_custody.bump
+    )]
+    pub pricing_custody: Box<Account<'info, Custody>>,
+
+    /// CHECK: oracle account for the pricing token
+    #[account(
+        constraint = pricing_oracle_account.key() == pricing_custody.oracle_account
+    )]
+    pub pricing_oracle_account: AccountInfo<'info>,
+
+    /// CHECK: account constraints checked in account trait
+    #[account(
+        address = sysvar::slot_hashes::id()
+    )]
+    recent_slothashes: UncheckedAccount<'info>,
+
+    /// CHECK: account constraints checked in account trait
+    #[account(
+        address = sysvar::instructions::id()
+    )]
+    instructions: UncheckedAccount<'info>,
+
+    token_program: Interface<'info, TokenInterface>,
+    // remaining accounts:
+    //   1 to Auction::MAX_TOKENS dispensing custody addresses (write, unsigned)
+    //   1 to Auction::MAX_TOKENS dispensing custody mints (read-only, unsigned)
+    //   for every limit order to fill, starting from the top of the book:
+    //     bid address (write, unsigned)
+    //     1 to Auction::MAX_TOKENS bid owner's associated token accounts (write, unsigned)
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct CrankFillsParams {}
+
+pub fn crank_fills<'info>(
+    ctx: Context<'_, '_, 'info, 'info, CrankFills<'info>>,
+    _params: &CrankFillsParams,
+) -> Result<u64> {
+    require!(
+        ctx.accounts.launchpad.permissions.allow_new_bids,
+        LaunchpadError::BidsNotAllowed
+    );
+
+    // tokens are picked from the slot hash, so the crank must be the only
+    // instruction in the transaction if there is a choice
+    if ctx.accounts.auction.num_tokens > 1 {
+        state::check_single_instruction(&ctx.accounts.instructions.to_account_info())?;
+    }
+
+    // load accounts
+    msg!("Load accounts");
+    let launchpad = ctx.accounts.launchpad.as_mut();
+    let auction = ctx.accounts.auction.as_mut();
+    let seller_balance = ctx.accounts.seller_balance.as_mut();
+    let pricing_custody = ctx.accounts.pricing_custody.as_mut();
+    let mut limit_book = ctx.accounts.limit_book.load_mut()?;
+    let mut price_history = ctx.accounts.price_history.load_mut()?;
+
+    // validate dispensing accounts
+    msg!("Validate dispensing accounts");
+    let num_tokens = auction.num_tokens as usize;
+    if ctx.remaining_accounts.len() < num_tokens * 2 {
+        return Err(ProgramError::NotEnoughAccountKeys.into());
+    }
+    let (dispensing_accounts, order_accounts) = ctx.remaining_accounts.split_at(num_tokens * 2);
+    if order_accounts.len() % (num_tokens + 1) != 0 {
+        return Err(ProgramError::NotEnoughAccountKeys.into());
+    }
+    let dispensing_custodies =
+        state::load_interface_accounts::<TokenAccount>(&dispensing_accounts[..num_tokens])?;
+    let dispensing_mints =
+        state::load_dispensing_mints(&dispensing_accounts[num_tokens..], &dispensing_custodies)?;
+    for (token, dispenser) in dispensing_custodies.iter().enumerate() {
+        require_keys_eq!(
+            dispenser.key(),
+            auction.tokens[token].account,
+            LaunchpadError::InvalidDispenserAddress
+        );
+    }
+    let mut dispenser_amounts: Vec<u64> = dispensing_custodies
+        .iter()
+        .map(|dispenser| dispenser.amount)
+        .collect();
+
+    // fill resting orders in price-time priority until the auction price is
+    // above the limit of the top order or supply runs out
+    let curtime = auction.get_time()?;
+    let mut num_filled = 0u64;
+    for accounts in order_accounts.chunks_exact(num_tokens + 1) {
+        if limit_book.num_orders == 0 {
+            break;
+        }
+        let order = limit_book.orders[0];
+        let mut bid = Account::<Bid>::try_from(&accounts[0])?;
+        require_keys_eq!(bid.key(), order.bid, LaunchpadError::InvalidBidAddress);
+
+        if auction.is_ended(curtime, bid.whitelisted) {
+            break;
+        }
+
+        // orders that reached the bidder's fill limit or can't receive tokens are
+        // taken off the book so they don't block it, the unused escrow is returned
+        // with cancel_limit_bid
+        let fill_amount_limit = if bid.whitelisted {
+            std::cmp::max(
+                auction.common.fill_limit_wl_address,
+                auction.common.fill_limit_reg_address,
+            )
+        } else {
+            auction.common.fill_limit_reg_address
+        };
+        let max_amount = std::cmp::min(order.amount, fill_amount_limit.saturating_sub(bid.filled));
+        let receiving_accounts =
+            match load_receiving_accounts(&accounts[1..], &dispensing_custodies, &bid.owner)? {
+                Some(receiving_accounts) if max_amount > 0 => receiving_accounts,
+                _ => {
+                    msg!("Remove order {}", order.bid);
+                    limit_book.remove(&order.bid);
+                    continue;
+                }
+            };
+
+        // pick a random token to dispense according to token ratios
+        let rand_seed = if auction.num_tokens == 1 {
+            0
+        } else {
+            state::get_random_seed(&ctx.accounts.recent_slothashes)?
+        };
+        let token_num = match auction.select_token(&dispenser_amounts, 1, rand_seed)? {
+            Some(token_num) => token_num,
+            None => break,
+        };
+
+        // orders that can't be filled are taken off the book instead of
+        // blocking the orders behind them
+        let (avail_amount, fill_price, payment_amount, fee_amount) = match get_order_fill(
+            launchpad,
+            auction,
+            pricing_custody,
+            &order,
+            std::cmp::min(
+                max_amount,
+                math::checked_div(dispenser_amounts[token_num], auction.pricing.unit_size)?,
+            ),
+            curtime,
+        ) {
+            Ok(fill) => fill,
+            Err(err) => {
+                msg!("Remove order {}: {:?}", order.bid, err);
+                limit_book.remove(&order.bid);
+                continue;
+            }
+        };
+        if avail_amount == 0 {
+            break;
+        }
+
+        // release escrowed payment and fee at the fill price
+        let (payment_amount, fee_amount) = bid.release_escrow(payment_amount, fee_amount);
+        msg!(
+            "Fill order {} with {} units at {}, payment {} and fee {}",
+            order.bid,
+            avail_amount,
+            fill_price,
+            payment_amount,
+            fee_amount
+        );
+        seller_balance.balance = math::checked_add(seller_balance.balance, payment_amount)?;
+        if fee_amount > 0 {
+            state::collect_escrowed_fee(
+                launchpad,
+                pricing_custody,
+                &ctx.accounts.pricing_oracle_account,
+                fee_amount,
+                false,
+                curtime,
+            )?;
+        }
+
+        // update user's bid
+        bid.filled = math::checked_add(bid.filled, avail_amount)?;
+        bid.fill_time = curtime;
+        bid.fill_price = fill_price;
+        bid.fill_amount = avail_amount;
+        bid.token_fills[token_num] = math::checked_add(bid.token_fills[token_num], avail_amount)?;
+
+        // update auction stats
+        let price_adjustment_bps = auction
+            .payment
+            .get_price_adjustment_bps(&pricing_custody.key())?;
+        auction.update_stats(
+            avail_amount,
+            fill_price,
+            price_adjustment_bps,
+            bid.whitelisted,
+            curtime,
+        )?;
+        auction.stats.token_fills[token_num] =
+            math::checked_add(auction.stats.token_fills[token_num], avail_amount)?;
+        price_history.record_fill(fill_price, avail_amount, curtime)?;
+
+        // transfer purchased tokens to the user
+        let transfer_amount = math::checked_mul(avail_amount, auction.pricing.unit_size)?;
+        msg!("Transfer {} tokens to the user", transfer_amount);
+        launchpad.transfer_tokens(
+            dispensing_custodies[token_num].to_account_info(),
+            receiving_accounts[token_num].to_account_info(),
+            dispensing_mints[token_num].to_account_info(),
+            ctx.accounts.transfer_authority.to_account_info(),
+            ctx.accounts.token_program.to_account_info(),
+            transfer_amount,
+            dispensing_mints[token_num].decimals,
+        )?;
+        dispenser_amounts[token_num] =
+            math::checked_sub(dispenser_amounts[token_num], transfer_amount)?;
+        num_filled = math::checked_add(num_filled, avail_amount)?;
+
+        // partially filled orders keep their place at the top of the book
+        let remaining_amount = math::checked_sub(order.amount, avail_amount)?;
+        if remaining_amount == 0 {
+            limit_book.remove(&order.bid);
+        } else {
+            limit_book.orders[0].amount = remaining_amount;
+        }
+        bid.exit(&crate::ID)?;
+
+        if remaining_amount > 0 {
+            break;
+        }
+    }
+
+    Ok(num_filled)
+}
+
+/// Computes the fill of the order at the auction price, capped by max_amount.
+/// Returns the number of units, fill price, payment and fee to release.
+fn get_order_fill(
+    launchpad: &Launchpad,
+    auction: &Auction,
+    pricing_custody: &Account<Custody>,
+    order: &LimitOrder,
+    max_amount: u64,
+    curtime: i64,
+) -> Result<(u64, u64, u64, u64)> {
+    let avail_amount = auction.get_hard_cap_amount(
+        std::cmp::min(
+            auction.get_auction_amount(order.price, curtime)?,
+            max_amount,
+        ),
+        curtime,
+        auction
+            .payment
+            .get_price_adjustment_bps(&pricing_custody.key())?,
+    )?;
+    if avail_amount == 0 {
+        return Ok((0, 0, 0, 0));
+    }
+    let fill_price = auction.get_auction_price(avail_amount, curtime)?;
+    require_gte!(order.price, fill_price, LaunchpadError::PriceCalcError);
+
+    let payment_amount = auction.payment.adjust_payment_amount(
+        &pricing_custody.key(),
+        state::convert_payment_amount(
+            fill_price,
+            avail_amount,
+            pricing_custody,
+            pricing_custody,
+            &None,
+        )?,
+    )?;
+    let fee_amount = launchpad.fees.trade.get_fee_amount(payment_amount)?;
+
+    Ok((avail_amount, fill_price, payment_amount, fee_amount))
+}
+
+/// Loads the bid owner's associated token accounts. Returns None if any of them
+/// can't receive tokens, e.g. it has been closed or frozen.
+fn load_receiving_accounts<'a>(
+    accounts: &'a [AccountInfo<'a>],
+    dispensing_custodies: &[InterfaceAccount<'a, TokenAccount>],
+    owner: &Pubkey,
+) -> Result<Option<Vec<InterfaceAccount<'a, TokenAccount>>>> {
+    let mut receiving_accounts = Vec::with_capacity(accounts.len());
+    for (account, dispenser) in accounts.iter().zip(dispensing_custodies) {
+        require_keys_eq!(
+            account.key(),
+            associated_token::get_associated_token_address_with_program_id(
+                owner,
+                &dispenser.mint,
+                dispenser.to_account_info().owner
+            ),
+            LaunchpadError::InvalidReceivingAddress
+        );
+        if account.owner != dispenser.to_account_info().owner {
+            return Ok(None);
+        }
+        match InterfaceAccount::<TokenAccount>::try_from(account) {
+            Ok(receiving_account) if !receiving_account.is_frozen() => {
+                receiving_accounts.push(receiving_account)
+            }
+            _ => return Ok(None),
+        }
+    }
+
+    Ok(Some(receiving_accounts))
+}
@@ -7,11 +7,13 @@ use {
             self,
             auction::Auction,
             launchpad::Launchpad,
+            limit_book::LimitBook,
             multisig::{AdminInstruction, Multisig},
+            price_history::PriceHistory,
         },
     },
     anchor_lang::prelude::*,
-    anchor_spl::token::{Token, TokenAccount},
+    anchor_spl::token_interface::{TokenAccount, TokenInterface},
 };
 
 #[derive(Accounts)]
@@ -49,7 +51,25 @@ pub struct DeleteAuction<'info> {
     )]
     pub auction: Box<Account<'info, Auction>>,
 
-    token_program: Program<'info, Token>,
+    #[account(
+        mut,
+        seeds = [b"price_history",
+                 auction.key().as_ref()],
+        bump = price_history.load()?.bump,
+        close = transfer_authority
+    )]
+    pub price_history: AccountLoader<'info, PriceHistory>,
+
+    #[account(
+        mut,
+        seeds = [b"limit_book",
+                 auction.key().as_ref()],
+        bump = limit_book.load()?.bump,
+        close = transfer_authority
+    )]
+    pub limit_book: AccountLoader<'info, LimitBook>,
+
+    token_program: Interface<'info, TokenInterface>,
     // remaining accounts:
     //   1 to Auction::MAX_TOKENS dispensing custody addresses (write, unsigned)
     //      with seeds = [b"dispense", mint.key().as_ref(), auction.key().as_ref()],
@@ -59,7 +79,7 @@ pub struct DeleteAuction<'info> {
 pub struct DeleteAuctionParams {}
 
 pub fn delete_auction<'info>(
-    ctx: Context<'_, '_, '_, 'info, DeleteAuction<'info>>,
+    ctx: Context<'_, '_, 'info, 'info, DeleteAuction<'info>>,
     params: &DeleteAuctionParams,
 ) -> Result<u8> {
     if !cfg!(feature = "test") {
@@ -90,9 +110,8 @@ pub fn delete_auction<'info>(
         if ctx.remaining_accounts.len() < auction.num_tokens.into() {
             return Err(ProgramError::NotEnoughAccountKeys.into());
         }
-        let dispensers = state::load_accounts::<TokenAccount>(
+        let dispensers = state::load_interface_accounts::<TokenAccount>(
             &ctx.remaining_accounts[..auction.num_tokens.into()],
-            &Token::id(),
         )?;
         for (i, dispenser) in dispensers.iter().enumerate() {
             require_keys_eq!(
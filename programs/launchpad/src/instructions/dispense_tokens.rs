@@ -0,0 +1,147 @@
+//! DispenseTokens instruction handler
+
+use {
+    crate::{
+        error::LaunchpadError,
+        math,
+        state::{self, auction::Auction, bid::Bid, launchpad::Launchpad, randomness},
+    },
+    anchor_lang::prelude::*,
+    anchor_spl::token_interface::TokenInterface,
+};
+
+#[derive(Accounts)]
+pub struct DispenseTokens<'info> {
+    #[account()]
+    pub user: Signer<'info>,
+
+    /// CHECK: empty PDA, authority for token accounts
+    #[account(
+        seeds = [b"transfer_authority"],
+        bump = launchpad.transfer_authority_bump
+    )]
+    pub transfer_authority: AccountInfo<'info>,
+
+    #[account(
+        seeds = [b"launchpad"],
+        bump = launchpad.launchpad_bump
+    )]
+    pub launchpad: Box<Account<'info, Launchpad>>,
+
+    #[account(
+        mut,
+        seeds = [b"auction",
+                 auction.common.name.as_bytes()],
+        bump = auction.bump
+    )]
+    pub auction: Box<Account<'info, Auction>>,
+
+    #[account(
+        mut,
+        seeds = [b"bid",
+                 bid.owner.as_ref(),
+                 auction.key().as_ref()],
+        bump = bid.bump
+    )]
+    pub bid: Box<Account<'info, Bid>>,
+
+    /// CHECK: slot hashes sysvar or VRF account, see RandomnessParams::get_randomness_account()
+    #[account(
+        constraint = randomness_account.key() == auction.randomness.get_randomness_account()
+    )]
+    pub randomness_account: AccountInfo<'info>,
+
+    token_program: Interface<'info, TokenInterface>,
+    // remaining accounts:
+    //   1 to Auction::MAX_TOKENS bid owner's token receiving accounts (write, unsigned)
+    //   1 to Auction::MAX_TOKENS dispensing custody addresses (write, unsigned)
+    //   1 to Auction::MAX_TOKENS dispensing custody mints (read-only, unsigned)
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct DispenseTokensParams {}
+
+pub fn dispense_tokens<'info>(
+    ctx: Context<'_, '_, 'info, 'info, DispenseTokens<'info>>,
+    _params: &DispenseTokensParams,
+) -> Result<()> {
+    // load accounts
+    msg!("Load accounts");
+    let auction = ctx.accounts.auction.as_mut();
+    let bid = ctx.accounts.bid.as_mut();
+
+    require!(
+        auction.randomness.is_deferred(),
+        LaunchpadError::UnsupportedRandomnessSource
+    );
+    require_gt!(bid.pending_amount, 0u64, LaunchpadError::InvalidTokenAmount);
+
+    // validate dispensing and receiving accounts
+    msg!("Validate dispensing and receiving accounts");
+    let (receiving_accounts, dispensing_custodies, dispensing_mints) =
+        state::load_dispensing_accounts(ctx.remaining_accounts, auction, &bid.owner)?;
+    let dispenser_amounts: Vec<u64> = dispensing_custodies
+        .iter()
+        .map(|dispenser| dispenser.amount)
+        .collect();
+
+    // pick a random token to dispense according to token ratios
+    msg!("Select token to dispense");
+    let randomness = auction.randomness.get_randomness(
+        &ctx.accounts.randomness_account,
+        bid.pending_slot,
+        Clock::get()?.slot,
+    )?;
+    let selected_token = match randomness {
+        Some(randomness) => auction.select_token(
+            &dispenser_amounts,
+            bid.pending_amount,
+            randomness::get_bid_seed(&randomness, &bid.key()),
+        )?,
+        None => None,
+    };
+
+    // the revealed slot hash has expired or the picked dispensers ran out, fall back to
+    // the most common token that covers the bid, so delaying never improves the odds
+    let token_num = match selected_token {
+        Some(token) => token,
+        None => {
+            let mut token_num: Option<usize> = None;
+            for (token, &amount) in dispenser_amounts.iter().enumerate() {
+                if math::checked_div(amount, auction.pricing.unit_size)? < bid.pending_amount {
+                    continue;
+                }
+                match token_num {
+                    Some(best) if auction.tokens[best].ratio >= auction.tokens[token].ratio => {}
+                    _ => token_num = Some(token),
+                }
+            }
+            token_num.ok_or(LaunchpadError::InsufficientAmount)?
+        }
+    };
+
+    // update user's bid
+    msg!("Update user's bid");
+    let transfer_amount = math::checked_mul(bid.pending_amount, auction.pricing.unit_size)?;
+    auction.stats.pending_amount =
+        math::checked_sub(auction.stats.pending_amount, bid.pending_amount)?;
+    bid.token_fills[token_num] = math::checked_add(bid.token_fills[token_num], bid.pending_amount)?;
+    auction.stats.token_fills[token_num] =
+        math::checked_add(auction.stats.token_fills[token_num], bid.pending_amount)?;
+    bid.pending_amount = 0;
+    bid.pending_slot = 0;
+
+    // transfer purchased tokens to the user
+    msg!("Transfer {} tokens to the user", transfer_amount);
+    ctx.accounts.launchpad.transfer_tokens(
+        dispensing_custodies[token_num].to_account_info(),
+        receiving_accounts[token_num].to_account_info(),
+        dispensing_mints[token_num].to_account_info(),
+        ctx.accounts.transfer_authority.to_account_info(),
+        ctx.accounts.token_program.to_account_info(),
+        transfer_amount,
+        dispensing_mints[token_num].decimals,
+    )?;
+
+    Ok(())
+}
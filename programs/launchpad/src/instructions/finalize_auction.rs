@@ -0,0 +1,201 @@
+//! FinalizeAuction instruction handler
+
+use {
+    crate::{
+        error::LaunchpadError,
+        math,
+        state::{
+            self,
+            auction::{Auction, PricingModel},
+            bid::Bid,
+            custody::Custody,
+            launchpad::Launchpad,
+            seller_balance::SellerBalance,
+        },
+    },
+    anchor_lang::prelude::*,
+    anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface},
+};
+
+#[derive(Accounts)]
+pub struct FinalizeAuction<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    /// CHECK: empty PDA, authority for token accounts
+    #[account(
+        seeds = [b"transfer_authority"],
+        bump = launchpad.transfer_authority_bump
+    )]
+    pub transfer_authority: AccountInfo<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"launchpad"],
+        bump = launchpad.launchpad_bump
+    )]
+    pub launchpad: Box<Account<'info, Launchpad>>,
+
+    #[account(
+        mut,
+        seeds = [b"auction",
+                 auction.common.name.as_bytes()],
+        bump = auction.bump
+    )]
+    pub auction: Box<Account<'info, Auction>>,
+
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = SellerBalance::LEN,
+        seeds = [b"seller_balance",
+                 auction.owner.as_ref(),
+                 pricing_custody.key().as_ref()],
+        bump
+    )]
+    pub seller_balance: Box<Account<'info, SellerBalance>>,
+
+    #[account(
+        mut,
+        constraint = bid.key() == auction.stats.leading.bid @ LaunchpadError::InvalidBidAddress,
+        constraint = bid.escrow_custody == pricing_custody.key()
+    )]
+    pub bid: Box<Account<'info, Bid>>,
+
+    #[account(
+        mut,
+        constraint = receiving_account.owner == bid.owner @ LaunchpadError::InvalidReceivingAddress,
+        constraint = receiving_account.mint == dispensing_custody.mint @ LaunchpadError::InvalidReceivingAddress
+    )]
+    pub receiving_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        constraint = dispensing_custody.key() == auction.tokens[0].account @ LaunchpadError::InvalidDispenserAddress
+    )]
+    pub dispensing_custody: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        constraint = dispensing_custody_mint.key() == dispensing_custody.mint
+    )]
+    pub dispensing_custody_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mut,
+        constraint = pricing_custody.key() == auction.pricing.custody,
+        seeds = [b"custody",
+                 pricing_custody.mint.as_ref()],
+        bump = pricing_custody.bump
+    )]
+    pub pricing_custody: Box<Account<'info, Custody>>,
+
+    /// CHECK: oracle account for the pricing token
+    #[account(
+        constraint = pricing_oracle_account.key() == pricing_custody.oracle_account
+    )]
+    pub pricing_oracle_account: AccountInfo<'info>,
+
+    system_program: Program<'info, System>,
+    token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct FinalizeAuctionParams {}
+
+pub fn finalize_auction(
+    ctx: Context<FinalizeAuction>,
+    _params: &FinalizeAuctionParams,
+) -> Result<()> {
+    let launchpad = ctx.accounts.launchpad.as_mut();
+    let auction = ctx.accounts.auction.as_mut();
+    let bid = ctx.accounts.bid.as_mut();
+    let seller_balance = ctx.accounts.seller_balance.as_mut();
+    let pricing_custody = ctx.accounts.pricing_custody.as_mut();
+
+    require!(
+        auction.pricing.pricing_model == PricingModel::EnglishAuction,
+        LaunchpadError::UnsupportedPricingModel
+    );
+    require!(!auction.is_settled(), LaunchpadError::AuctionAlreadySettled);
+    let curtime = auction.get_time()?;
+    require!(
+        auction.is_ended(curtime, true),
+        LaunchpadError::AuctionInProgress
+    );
+    require!(auction.has_leading_bid(), LaunchpadError::AuctionEmpty);
+    require!(bid.escrowed, LaunchpadError::InvalidTokenAmount);
+
+    let fill_price = auction.stats.leading.price;
+    let payment_amount = bid.escrow_amount;
+    let fee_amount = bid.escrow_fee;
+
+    if fee_amount > 0 {
+        msg!("Collect fee {}", fee_amount);
+        state::collect_escrowed_fee(
+            launchpad,
+            pricing_custody,
+            &ctx.accounts.pricing_oracle_account,
+            fee_amount,
+            false,
+            curtime,
+        )?;
+    }
+
+    // update winner's bid
+    msg!("Update winner's bid");
+    bid.escrowed = false;
+    bid.escrow_amount = 0;
+    bid.escrow_fee = 0;
+    bid.filled = math::checked_add(bid.filled, 1)?;
+    bid.fill_time = curtime;
+    bid.fill_price = fill_price;
+    bid.fill_amount = 1;
+    bid.token_fills[0] = math::checked_add(bid.token_fills[0], 1)?;
+
+    // update seller's balance
+    msg!("Update seller's balance");
+    if seller_balance.bump == 0 {
+        seller_balance.owner = auction.owner;
+        seller_balance.custody = pricing_custody.key();
+        seller_balance.bump = ctx.bumps.seller_balance;
+    } else if seller_balance.owner != auction.owner
+        || seller_balance.custody != pricing_custody.key()
+    {
+        return err!(LaunchpadError::InvalidSellerBalanceAddress);
+    }
+    seller_balance.balance = math::checked_add(seller_balance.balance, payment_amount)?;
+
+    // update auction stats
+    msg!("Update auction stats");
+    let price_adjustment_bps = auction
+        .payment
+        .get_price_adjustment_bps(&pricing_custody.key())?;
+    auction.update_stats(
+        1,
+        fill_price,
+        price_adjustment_bps,
+        bid.whitelisted,
+        curtime,
+    )?;
+    auction.stats.clearing.settle_time = curtime;
+    auction.stats.clearing.clearing_price = fill_price;
+    auction.stats.clearing.supply = 1;
+    auction.stats.token_fills[0] = math::checked_add(auction.stats.token_fills[0], 1)?;
+
+    // transfer the lot to the winner
+    msg!(
+        "Transfer {} tokens to the winner",
+        auction.pricing.unit_size
+    );
+    launchpad.transfer_tokens(
+        ctx.accounts.dispensing_custody.to_account_info(),
+        ctx.accounts.receiving_account.to_account_info(),
+        ctx.accounts.dispensing_custody_mint.to_account_info(),
+        ctx.accounts.transfer_authority.to_account_info(),
+        ctx.accounts.token_program.to_account_info(),
+        auction.pricing.unit_size,
+        ctx.accounts.dispensing_custody_mint.decimals,
+    )?;
+
+    Ok(())
+}
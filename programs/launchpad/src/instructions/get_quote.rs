@@ -0,0 +1,301 @@
+//! GetQuote instruction handler
+
+use {
+    crate::{
+        error::LaunchpadError,
+        math,
+        state::{
+            self,
+            auction::{Auction, PricingModel},
+            bid::{BadBidType, Bid},
+            custody::Custody,
+            launchpad::Launchpad,
+        },
+    },
+    anchor_lang::prelude::*,
+    anchor_spl::token_interface::TokenAccount,
+};
+
+#[derive(Accounts)]
+pub struct GetQuote<'info> {
+    #[account()]
+    pub user: Signer<'info>,
+
+    #[account(
+        seeds = [b"launchpad"],
+        bump = launchpad.launchpad_bump
+    )]
+    pub launchpad: Box<Account<'info, Launchpad>>,
+
+    #[account(
+        seeds = [b"auction",
+                 auction.common.name.as_bytes()],
+        bump = auction.bump
+    )]
+    pub auction: Box<Account<'info, Auction>>,
+
+    /// CHECK: user's bid account, may not be initialized yet
+    #[account(
+        seeds = [b"bid",
+                 user.key().as_ref(),
+                 auction.key().as_ref()],
+        bump
+    )]
+    pub bid: AccountInfo<'info>,
+
+    #[account(
+        constraint = pricing_custody.key() == auction.pricing.custody,
+        seeds = [b"custody",
+                 pricing_custody.mint.as_ref()],
+        bump = pricing_custody.bump
+    )]
+    pub pricing_custody: Box<Account<'info, Custody>>,
+
+    /// CHECK: oracle account for the pricing token
+    #[account(
+        constraint = pricing_oracle_account.key() == pricing_custody.oracle_account
+    )]
+    pub pricing_oracle_account: AccountInfo<'info>,
+
+    #[account(
+        seeds = [b"custody",
+                 payment_custody.mint.as_ref()],
+        bump = payment_custody.bump
+    )]
+    pub payment_custody: Box<Account<'info, Custody>>,
+
+    /// CHECK: oracle account for the payment token
+    #[account(
+        constraint = payment_oracle_account.key() == payment_custody.oracle_account
+    )]
+    pub payment_oracle_account: AccountInfo<'info>,
+    // remaining accounts:
+    //   1 to Auction::MAX_TOKENS dispensing custody addresses (read-only, unsigned)
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct GetQuoteParams {
+    // number of units to buy, zero means as many as the budget allows
+    amount: u64,
+    // maximum payment plus fee in payment tokens, zero means no limit
+    budget: u64,
+}
+
+#[derive(Copy, Clone, PartialEq, AnchorSerialize, AnchorDeserialize, Default, Debug)]
+pub struct Quote {
+    pub fill_amount: u64,
+    // price per unit in pricing tokens, to be used as the bid price
+    pub unit_price: u64,
+    // payment and fee in payment tokens
+    pub payment_amount: u64,
+    pub fee_amount: u64,
+    // mint of the dispensed token, default if selected randomly upon the bid
+    pub token_mint: Pubkey,
+    pub bad_bid_type: BadBidType,
+}
+
+pub fn get_quote<'info>(
+    ctx: Context<'_, '_, 'info, 'info, GetQuote<'info>>,
+    params: &GetQuoteParams,
+) -> Result<Quote> {
+    require!(
+        params.amount > 0 || params.budget > 0,
+        LaunchpadError::InvalidTokenAmount
+    );
+
+    let launchpad = &ctx.accounts.launchpad;
+    let auction = &ctx.accounts.auction;
+    let pricing_custody = &ctx.accounts.pricing_custody;
+    let payment_custody = &ctx.accounts.payment_custody;
+    require!(
+        auction.payment.is_accepted(&payment_custody.key()),
+        LaunchpadError::InvalidPaymentCustody
+    );
+
+    if auction.pricing.pricing_model == PricingModel::EnglishAuction
+        || (auction.pricing.pricing_model != PricingModel::BatchAuction
+            && !auction.is_soft_cap_met())
+    {
+        require_keys_eq!(
+            payment_custody.key(),
+            auction.pricing.custody,
+            LaunchpadError::InvalidPaymentCustody
+        );
+    }
+
+    // load user's bid if any
+    let (whitelisted, filled) = if state::is_empty_account(&ctx.accounts.bid)? {
+        (false, 0)
+    } else {
+        require_keys_eq!(
+            *ctx.accounts.bid.owner,
+            crate::ID,
+            ErrorCode::AccountOwnedByWrongProgram
+        );
+        let bid = Bid::try_deserialize(&mut &ctx.accounts.bid.try_borrow_data()?[..])?;
+        (bid.whitelisted, bid.filled)
+    };
+
+    let curtime = auction.get_time()?;
+    require!(
+        !auction.is_ended(curtime, whitelisted),
+        LaunchpadError::AuctionEnded
+    );
+
+    // same checks as in place_bid
+    let fill_amount_limit = if whitelisted {
+        std::cmp::max(
+            auction.common.fill_limit_wl_address,
+            auction.common.fill_limit_reg_address,
+        )
+    } else {
+        auction.common.fill_limit_reg_address
+    };
+    let bad_bid_type = if !auction.is_started(curtime, whitelisted) {
+        BadBidType::TooEarly
+    } else if fill_amount_limit < filled {
+        BadBidType::FillLimit
+    } else {
+        BadBidType::None
+    };
+
+    // compute max amount available to the user
+    let order_amount_limit = if whitelisted {
+        std::cmp::max(
+            auction.common.order_limit_wl_address,
+            auction.common.order_limit_reg_address,
+        )
+    } else {
+        auction.common.order_limit_reg_address
+    };
+    let mut max_amount = std::cmp::min(
+        auction.get_auction_amount(auction.pricing.max_price, curtime)?,
+        order_amount_limit,
+    );
+    if params.amount > 0 {
+        max_amount = std::cmp::min(max_amount, params.amount);
+    }
+
+    // batch auction bids are escrowed, so dispensers don't limit the amount
+    let mut token_mint = Pubkey::default();
+    if auction.pricing.pricing_model != PricingModel::BatchAuction {
+        if ctx.remaining_accounts.len() > auction.num_tokens.into() {
+            return err!(LaunchpadError::TooManyAccountKeys);
+        }
+        if ctx.remaining_accounts.len() < auction.num_tokens.into() {
+            return Err(ProgramError::NotEnoughAccountKeys.into());
+        }
+        let dispensers = state::load_interface_accounts::<TokenAccount>(ctx.remaining_accounts)?;
+        let mut max_amount_to_dispense = 0;
+        for (i, dispenser) in dispensers.iter().enumerate() {
+            require_keys_eq!(
+                dispenser.key(),
+                auction.tokens[i].account,
+                LaunchpadError::InvalidDispenserAddress
+            );
+            let dispenser_units = math::checked_div(dispenser.amount, auction.pricing.unit_size)?;
+            max_amount_to_dispense = if auction.split_fills {
+                math::checked_add(max_amount_to_dispense, dispenser_units)?
+            } else {
+                std::cmp::max(max_amount_to_dispense, dispenser_units)
+            };
+        }
+        // deferred claims reserve supply of all dispensers, every unit of the
+        // basket auction takes all tokens
+        if auction.deferred_claim || auction.basket {
+            let dispenser_amounts: Vec<u64> = dispensers
+                .iter()
+                .map(|dispenser| dispenser.amount)
+                .collect();
+            max_amount_to_dispense = if auction.deferred_claim {
+                auction.get_deferred_supply(&dispenser_amounts)?
+            } else {
+                auction
+                    .get_basket_units(&dispenser_amounts)?
+                    .saturating_sub(auction.stats.pending_amount)
+            };
+        } else {
+            // units of pending bids are still in dispensers
+            max_amount_to_dispense =
+                max_amount_to_dispense.saturating_sub(auction.stats.pending_amount);
+        }
+        max_amount = std::cmp::min(max_amount, max_amount_to_dispense);
+        if auction.num_tokens == 1 {
+            token_mint = dispensers[0].mint;
+        }
+    }
+    max_amount = auction.get_hard_cap_amount(
+        max_amount,
+        curtime,
+        auction
+            .payment
+            .get_price_adjustment_bps(&payment_custody.key())?,
+    )?;
+
+    let (token_pair_price, _) = state::get_token_pair_price(
+        pricing_custody,
+        &ctx.accounts.pricing_oracle_account,
+        payment_custody,
+        &ctx.accounts.payment_oracle_account,
+        false,
+        curtime,
+    )?;
+    let get_quote_for_amount = |amount: u64| -> Result<Quote> {
+        let unit_price = auction.get_auction_price(amount, curtime)?;
+        let payment_amount = auction.payment.adjust_payment_amount(
+            &payment_custody.key(),
+            state::convert_payment_amount(
+                unit_price,
+                amount,
+                pricing_custody,
+                payment_custody,
+                &token_pair_price,
+            )?,
+        )?;
+        Ok(Quote {
+            fill_amount: amount,
+            unit_price,
+            payment_amount,
+            fee_amount: launchpad.fees.trade.get_fee_amount(payment_amount)?,
+            token_mint,
+            bad_bid_type,
+        })
+    };
+    let fits_budget = |quote: &Quote| -> Result<bool> {
+        Ok(params.budget == 0
+            || math::checked_add(quote.payment_amount, quote.fee_amount)? <= params.budget)
+    };
+
+    if max_amount == 0 {
+        return Ok(Quote {
+            token_mint,
+            bad_bid_type,
+            ..Quote::default()
+        });
+    }
+    let quote = get_quote_for_amount(max_amount)?;
+    if fits_budget(&quote)? {
+        return Ok(quote);
+    }
+
+    // find the largest amount that fits the budget
+    let mut low = 0u64;
+    let mut high = max_amount - 1;
+    while low < high {
+        let mid = low + (high - low) / 2 + 1;
+        if fits_budget(&get_quote_for_amount(mid)?)? {
+            low = mid;
+        } else {
+            high = mid - 1;
+        }
+    }
+    if low == 0 {
+        return Ok(Quote {
+            token_mint,
+            bad_bid_type,
+            ..Quote::default()
+        });
+    }
+
+    get_quote_for_amount(low)
+}
@@ -10,10 +10,14 @@ use {
             },
             custody::Custody,
             launchpad::Launchpad,
+            limit_book::LimitBook,
+            price_history::PriceHistory,
+            randomness::RandomnessParams,
+            vesting::VestingParams,
         },
     },
     anchor_lang::prelude::*,
-    anchor_spl::token::Token,
+    anchor_spl::token_interface::TokenInterface,
 };
 
 #[derive(Accounts)]
@@ -47,6 +51,26 @@ pub struct InitAuction<'info> {
     )]
     pub auction: Box<Account<'info, Auction>>,
 
+    #[account(
+        init,
+        payer = owner,
+        space = PriceHistory::LEN,
+        seeds = [b"price_history",
+                 auction.key().as_ref()],
+        bump
+    )]
+    pub price_history: AccountLoader<'info, PriceHistory>,
+
+    #[account(
+        init,
+        payer = owner,
+        space = LimitBook::LEN,
+        seeds = [b"limit_book",
+                 auction.key().as_ref()],
+        bump
+    )]
+    pub limit_book: AccountLoader<'info, LimitBook>,
+
     #[account(
         seeds = [b"custody",
                  pricing_custody.mint.as_ref()],
@@ -55,7 +79,7 @@ pub struct InitAuction<'info> {
     pub pricing_custody: Box<Account<'info, Custody>>,
 
     system_program: Program<'info, System>,
-    token_program: Program<'info, Token>,
+    token_program: Interface<'info, TokenInterface>,
     rent: Sysvar<'info, Rent>,
     // remaining accounts:
     //   1 to Auction::MAX_TOKENS dispensing custody addresses (write, unsigned)
@@ -68,14 +92,23 @@ pub struct InitAuctionParams {
     pub enabled: bool,
     pub updatable: bool,
     pub fixed_amount: bool,
+    pub basket: bool,
+    pub deferred_claim: bool,
+    pub split_fills: bool,
     pub common: CommonParams,
     pub payment: PaymentParams,
     pub pricing: PricingParams,
     pub token_ratios: Vec<u64>,
+    // token amounts in every unit of the basket auction, ignored otherwise
+    pub basket_amounts: Vec<u64>,
+    pub randomness: RandomnessParams,
+    pub vesting: VestingParams,
+    // length of the price history candles in seconds
+    pub candle_duration: i64,
 }
 
 pub fn init_auction<'info>(
-    ctx: Context<'_, '_, '_, 'info, InitAuction<'info>>,
+    ctx: Context<'_, '_, 'info, 'info, InitAuction<'info>>,
     params: &InitAuctionParams,
 ) -> Result<()> {
     require!(
@@ -129,6 +162,9 @@ pub fn init_auction<'info>(
     auction.enabled = params.enabled;
     auction.updatable = params.updatable;
     auction.fixed_amount = params.fixed_amount;
+    auction.basket = params.basket;
+    auction.deferred_claim = params.deferred_claim;
+    auction.split_fills = params.split_fills;
     auction.common = params.common.clone();
     auction.payment = params.payment;
     auction.pricing = params.pricing;
@@ -137,13 +173,34 @@ pub fn init_auction<'info>(
     auction.stats.reg_bidders.min_fill_price = u64::MAX;
     auction.tokens = [AuctionToken::default(); Auction::MAX_TOKENS];
     auction.num_tokens = dispensers.len() as u8;
+    auction.randomness = params.randomness;
+    auction.vesting = params.vesting;
 
     for (n, dispenser) in dispensers.iter().enumerate() {
         auction.tokens[n].ratio = params.token_ratios[n];
         auction.tokens[n].account = dispenser.key();
+        if params.basket {
+            auction.tokens[n].basket_amount = params.basket_amounts.get(n).copied().unwrap_or(0);
+        }
     }
 
-    auction.bump = *ctx.bumps.get("auction").ok_or(ProgramError::InvalidSeeds)?;
+    auction.bump = ctx.bumps.auction;
+
+    // init price history
+    require_gt!(
+        params.candle_duration,
+        0i64,
+        LaunchpadError::InvalidAuctionConfig
+    );
+    let mut price_history = ctx.accounts.price_history.load_init()?;
+    price_history.auction = auction.key();
+    price_history.candle_duration = params.candle_duration;
+    price_history.bump = ctx.bumps.price_history;
+
+    // init limit book
+    let mut limit_book = ctx.accounts.limit_book.load_init()?;
+    limit_book.auction = auction.key();
+    limit_book.bump = ctx.bumps.limit_book;
 
     auction.creation_time = if cfg!(feature = "test") {
         0
@@ -12,7 +12,7 @@ use {
     anchor_lang::prelude::*,
     anchor_spl::{
         associated_token::AssociatedToken,
-        token::{Mint, Token, TokenAccount},
+        token_interface::{Mint, TokenAccount, TokenInterface},
     },
 };
 
@@ -50,7 +50,7 @@ pub struct InitCustody<'info> {
     )]
     pub custody: Box<Account<'info, Custody>>,
 
-    pub custody_token_mint: Box<Account<'info, Mint>>,
+    pub custody_token_mint: Box<InterfaceAccount<'info, Mint>>,
 
     // token custodies are shared between multiple auctions
     #[account(
@@ -58,26 +58,27 @@ pub struct InitCustody<'info> {
         payer = admin,
         constraint = custody_token_mint.key() == custody_token_account.mint,
         associated_token::mint = custody_token_mint,
-        associated_token::authority = transfer_authority
+        associated_token::authority = transfer_authority,
+        associated_token::token_program = token_program
     )]
-    pub custody_token_account: Box<Account<'info, TokenAccount>>,
+    pub custody_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
 
     system_program: Program<'info, System>,
-    token_program: Program<'info, Token>,
+    token_program: Interface<'info, TokenInterface>,
     associated_token_program: Program<'info, AssociatedToken>,
     rent: Sysvar<'info, Rent>,
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize)]
 pub struct InitCustodyParams {
-    pub max_oracle_price_error: f64,
+    pub max_oracle_price_error: u64,
     pub max_oracle_price_age_sec: u32,
     pub oracle_type: OracleType,
     pub oracle_account: Pubkey,
 }
 
 pub fn init_custody<'info>(
-    ctx: Context<'_, '_, '_, 'info, InitCustody<'info>>,
+    ctx: Context<'_, '_, 'info, 'info, InitCustody<'info>>,
     params: &InitCustodyParams,
 ) -> Result<u8> {
     // validate signatures
@@ -111,7 +112,7 @@ pub fn init_custody<'info>(
     custody.max_oracle_price_age_sec = params.max_oracle_price_age_sec;
     custody.oracle_type = params.oracle_type;
     custody.oracle_account = params.oracle_account;
-    custody.bump = *ctx.bumps.get("custody").ok_or(ProgramError::InvalidSeeds)?;
+    custody.bump = ctx.bumps.custody;
 
     if !custody.validate() {
         err!(LaunchpadError::InvalidCustodyConfig)
@@ -6,16 +6,21 @@ use {
         math,
         state::{
             self,
-            auction::Auction,
+            auction::{Auction, EscrowStats, LeadingBid, PricingModel},
             bid::{BadBidType, Bid, BidType},
             custody::Custody,
             launchpad::Launchpad,
+            limit_book::{LimitBook, LimitOrder},
             oracle::OraclePrice,
+            price_history::PriceHistory,
             seller_balance::SellerBalance,
         },
     },
     anchor_lang::prelude::*,
-    anchor_spl::token::{Token, TokenAccount, Transfer},
+    anchor_spl::{
+        token,
+        token_interface::{self, Mint, SyncNative, TokenAccount, TokenInterface, TransferChecked},
+    },
     solana_program::sysvar,
 };
 
@@ -24,12 +29,10 @@ pub struct PlaceBid<'info> {
     #[account(mut)]
     pub owner: Signer<'info>,
 
-    #[account(
-        mut,
-        constraint = funding_account.mint == payment_custody.mint,
-        has_one = owner
-    )]
-    pub funding_account: Box<Account<'info, TokenAccount>>,
+    /// CHECK: user's token account for the payment, or the owner's wallet
+    /// for native SOL payments, validated in the instruction handler
+    #[account(mut)]
+    pub funding_account: AccountInfo<'info>,
 
     /// CHECK: empty PDA, authority for token accounts
     #[account(
@@ -53,6 +56,22 @@ pub struct PlaceBid<'info> {
     )]
     pub auction: Box<Account<'info, Auction>>,
 
+    #[account(
+        mut,
+        seeds = [b"price_history",
+                 auction.key().as_ref()],
+        bump = price_history.load()?.bump
+    )]
+    pub price_history: AccountLoader<'info, PriceHistory>,
+
+    #[account(
+        mut,
+        seeds = [b"limit_book",
+                 auction.key().as_ref()],
+        bump = limit_book.load()?.bump
+    )]
+    pub limit_book: AccountLoader<'info, LimitBook>,
+
     #[account(
         init_if_needed,
         payer = owner,
@@ -107,7 +126,12 @@ pub struct PlaceBid<'info> {
         mut,
         constraint = payment_token_account.key() == payment_custody.token_account.key()
     )]
-    pub payment_token_account: Box<Account<'info, TokenAccount>>,
+    pub payment_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        constraint = payment_token_mint.key() == payment_custody.mint
+    )]
+    pub payment_token_mint: Box<InterfaceAccount<'info, Mint>>,
 
     /// CHECK: account constraints checked in account trait
     #[account(
@@ -122,10 +146,22 @@ pub struct PlaceBid<'info> {
     instructions: UncheckedAccount<'info>,
 
     system_program: Program<'info, System>,
-    token_program: Program<'info, Token>,
-    // remaining accounts:
-    //   1 to Auction::MAX_TOKENS user's token receiving accounts (write, unsigned)
+    token_program: Interface<'info, TokenInterface>,
+    dispensing_token_program: Interface<'info, TokenInterface>,
+    // remaining accounts (not required for batch auctions and limit bids):
+    //   1 to Auction::MAX_TOKENS user's token receiving accounts, or vesting escrow
+    //     accounts at the ["vesting", bid, mint] addresses if the auction has
+    //     vesting (write, unsigned)
     //   1 to Auction::MAX_TOKENS dispensing custody addresses (write, unsigned)
+    //   1 to Auction::MAX_TOKENS dispensing custody mints (read-only, unsigned)
+    // remaining accounts for deferred claims:
+    //   1 to Auction::MAX_TOKENS dispensing custody addresses (read-only, unsigned)
+    // remaining accounts for English auctions:
+    //   dispensing custody address (read-only, unsigned)
+    //   leading bid address, if any (write, unsigned)
+    //   leading bid refund account, if any (write, unsigned)
+    //   temporary wrapped SOL account, if the leading bid is refunded in native
+    //     SOL, see state::transfer_native_sol() (write, unsigned)
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize)]
@@ -136,7 +172,7 @@ pub struct PlaceBidParams {
 }
 
 pub fn place_bid<'info>(
-    ctx: Context<'_, '_, '_, 'info, PlaceBid<'info>>,
+    ctx: Context<'_, '_, 'info, 'info, PlaceBid<'info>>,
     params: &PlaceBidParams,
 ) -> Result<()> {
     require!(
@@ -145,17 +181,7 @@ pub fn place_bid<'info>(
     );
 
     // check if this instruction is the only instruction in the transaction
-    require!(
-        sysvar::instructions::load_current_index_checked(
-            &ctx.accounts.instructions.to_account_info()
-        )? == 0
-            && sysvar::instructions::load_instruction_at_checked(
-                1,
-                &ctx.accounts.instructions.to_account_info()
-            )
-            .is_err(),
-        LaunchpadError::MustBeSingleInstruction
-    );
+    state::check_single_instruction(&ctx.accounts.instructions.to_account_info())?;
 
     // load accounts
     msg!("Load accounts");
@@ -164,25 +190,51 @@ pub fn place_bid<'info>(
     let bid = ctx.accounts.bid.as_mut();
     let seller_balance = ctx.accounts.seller_balance.as_mut();
     let payment_custody = ctx.accounts.payment_custody.as_mut();
+    let batch_auction = auction.pricing.pricing_model == PricingModel::BatchAuction;
+    let english_auction = auction.pricing.pricing_model == PricingModel::EnglishAuction;
+    let limit_bid = params.bid_type == BidType::Limit;
+    require!(
+        auction.payment.is_accepted(&payment_custody.key()),
+        LaunchpadError::InvalidPaymentCustody
+    );
 
-    if ctx.remaining_accounts.is_empty() || ctx.remaining_accounts.len() % 2 != 0 {
-        return Err(ProgramError::NotEnoughAccountKeys.into());
-    }
-    let accounts_half_len = ctx.remaining_accounts.len() / 2;
-    if accounts_half_len > auction.num_tokens.into() {
-        return err!(LaunchpadError::TooManyAccountKeys);
-    }
-    if accounts_half_len < auction.num_tokens.into() {
-        return Err(ProgramError::NotEnoughAccountKeys.into());
-    }
-    let receiving_accounts = state::load_accounts::<TokenAccount>(
-        &ctx.remaining_accounts[..accounts_half_len],
-        &Token::id(),
-    )?;
-    let dispensing_custodies = state::load_accounts::<TokenAccount>(
-        &ctx.remaining_accounts[accounts_half_len..],
-        &Token::id(),
-    )?;
+    // native SOL payments are debited from the owner's wallet and wrapped
+    // into the SOL custody
+    let native_sol = ctx.accounts.funding_account.key() == ctx.accounts.owner.key();
+    let funding_balance = if native_sol {
+        require_keys_eq!(
+            payment_custody.mint,
+            token::spl_token::native_mint::id(),
+            LaunchpadError::InvalidPaymentCustody
+        );
+        ctx.accounts.owner.lamports()
+    } else {
+        require_keys_eq!(
+            *ctx.accounts.funding_account.owner,
+            ctx.accounts.token_program.key(),
+            ErrorCode::AccountOwnedByWrongProgram
+        );
+        let funding_account = TokenAccount::try_deserialize(
+            &mut &ctx.accounts.funding_account.try_borrow_data()?[..],
+        )?;
+        require_keys_eq!(
+            funding_account.mint,
+            payment_custody.mint,
+            LaunchpadError::InvalidPaymentCustody
+        );
+        require_keys_eq!(
+            funding_account.owner,
+            ctx.accounts.owner.key(),
+            ErrorCode::ConstraintHasOne
+        );
+        funding_account.amount
+    };
+    let soft_cap_escrow = !batch_auction && !english_auction && !auction.is_soft_cap_met();
+    let deferred_dispense = !batch_auction
+        && !english_auction
+        && !auction.basket
+        && auction.num_tokens > 1
+        && auction.randomness.is_deferred();
 
     // validate inputs
     msg!("Validate inputs");
@@ -219,58 +271,237 @@ pub fn place_bid<'info>(
         LaunchpadError::AuctionEnded
     );
 
-    // validate dispensing and receiving accounts
-    // all accounts needs to be validated, not the only selected to dispense,
-    // so the user can't game the process
-    msg!("Validate dispensing and receiving accounts");
-    for token in 0..auction.num_tokens as usize {
-        if receiving_accounts[token].owner != ctx.accounts.owner.key() {
-            msg!("Invalid owner of the receiving token account");
-            return Err(ProgramError::IllegalOwner.into());
+    // batch auction bids are escrowed and filled at the clearing price upon settlement,
+    // hence no tokens are dispensed at this point
+    let mut dispensing_accounts = Vec::new();
+    let (fill_price, fill_amount) = if batch_auction {
+        require!(!auction.is_sealed(), LaunchpadError::SealedBidsOnly);
+        require!(!bid.escrowed, LaunchpadError::BidAlreadyPlaced);
+        let price_level = auction.get_price_level(params.price)?;
+        (auction.get_level_price(price_level)?, params.amount)
+    } else if english_auction {
+        // English auction bids are escrowed in the pricing token until outbid or finalized
+        require_keys_eq!(
+            payment_custody.key(),
+            auction.pricing.custody,
+            LaunchpadError::InvalidPaymentCustody
+        );
+        require!(!bid.escrowed, LaunchpadError::BidAlreadyPlaced);
+        require_eq!(params.amount, 1u64, LaunchpadError::InvalidTokenAmount);
+        require_gte!(
+            params.price,
+            auction.get_auction_price(1, curtime)?,
+            LaunchpadError::BidPriceTooSmall
+        );
+        require_gte!(
+            auction.pricing.max_price,
+            params.price,
+            LaunchpadError::InvalidBidPrice
+        );
+
+        // check that the lot is available, native SOL refunds of the leading
+        // bid are unwrapped through a temporary account
+        let expected_accounts = if !auction.has_leading_bid() {
+            1
+        } else if auction.stats.leading.refund_account == auction.stats.leading.owner {
+            4
+        } else {
+            3
+        };
+        if ctx.remaining_accounts.len() > expected_accounts {
+            return err!(LaunchpadError::TooManyAccountKeys);
         }
+        if ctx.remaining_accounts.len() < expected_accounts {
+            return Err(ProgramError::NotEnoughAccountKeys.into());
+        }
+        let dispensing_custody =
+            InterfaceAccount::<TokenAccount>::try_from(&ctx.remaining_accounts[0])?;
         require_keys_eq!(
-            dispensing_custodies[token].key(),
-            auction.tokens[token].account,
+            dispensing_custody.key(),
+            auction.tokens[0].account,
             LaunchpadError::InvalidDispenserAddress
         );
+        require_gte!(
+            dispensing_custody.amount,
+            auction.pricing.unit_size,
+            LaunchpadError::InsufficientAmount
+        );
+
+        (params.price, params.amount)
+    } else if limit_bid {
+        // limit bids are escrowed in the pricing token at the limit price until
+        // filled by crank_fills or canceled
+        require!(
+            auction.accepts_limit_bids(),
+            LaunchpadError::LimitBidsNotAllowed
+        );
         require_keys_eq!(
-            dispensing_custodies[token].mint,
-            receiving_accounts[token].mint,
-            LaunchpadError::InvalidReceivingAddress
-        )
-    }
+            payment_custody.key(),
+            auction.pricing.custody,
+            LaunchpadError::InvalidPaymentCustody
+        );
+        require!(!bid.escrowed, LaunchpadError::BidAlreadyPlaced);
+        require_gte!(
+            auction.pricing.max_price,
+            params.price,
+            LaunchpadError::InvalidBidPrice
+        );
+        require_gte!(
+            params.amount,
+            auction.common.min_limit_order_amount,
+            LaunchpadError::InvalidTokenAmount
+        );
 
-    // pick a random token to dispense
-    msg!("Select token to dispense");
-    let token_num = if auction.num_tokens == 1 {
-        0
+        (params.price, params.amount)
     } else {
-        let slothashes_data = ctx.accounts.recent_slothashes.data.borrow();
-        if slothashes_data.len() < 20 {
-            return Err(ProgramError::InvalidAccountData.into());
+        // payments are escrowed in the pricing token until the soft cap is met
+        if soft_cap_escrow {
+            require_keys_eq!(
+                payment_custody.key(),
+                auction.pricing.custody,
+                LaunchpadError::InvalidPaymentCustody
+            );
         }
-        let rand_seed = usize::from_le_bytes(slothashes_data[12..20].try_into().unwrap());
-        rand_seed % dispensing_custodies.len()
-    };
-    let max_amount_to_dispense = math::checked_div(
-        dispensing_custodies[token_num].amount,
-        auction.pricing.unit_size,
-    )?;
 
-    // get available amount at the given price
-    msg!("Compute available amount");
-    let avail_amount = std::cmp::min(
-        auction.get_auction_amount(params.price, curtime)?,
-        max_amount_to_dispense,
-    );
+        // payments for deferred claims are escrowed in the bid until claimed
+        if auction.deferred_claim && bid.pending_amount > 0 {
+            require_keys_eq!(
+                payment_custody.key(),
+                bid.escrow_custody,
+                LaunchpadError::InvalidPaymentCustody
+            );
+        }
 
-    if avail_amount == 0 || (params.bid_type == BidType::Fok && avail_amount < params.amount) {
-        return err!(LaunchpadError::InsufficientAmount);
-    }
-    let fill_amount = std::cmp::min(avail_amount, params.amount);
+        // validate dispensing and receiving accounts, vested tokens are
+        // locked in the vesting escrow accounts of the bid, deferred claims
+        // only read dispensers to reserve supply until claim_tokens
+        let (receiving_accounts, dispensing_custodies, dispensing_mints) = if auction.deferred_claim
+        {
+            msg!("Validate dispensing accounts");
+            (
+                Vec::new(),
+                state::load_dispensers(ctx.remaining_accounts, auction)?,
+                Vec::new(),
+            )
+        } else {
+            msg!("Validate dispensing and receiving accounts");
+            let receiver = if auction.vesting.is_enabled() {
+                state::init_vesting_accounts(
+                    ctx.remaining_accounts,
+                    bid.to_account_info(),
+                    ctx.accounts.owner.to_account_info(),
+                    ctx.accounts.system_program.to_account_info(),
+                    ctx.accounts.dispensing_token_program.to_account_info(),
+                )?;
+                bid.key()
+            } else {
+                ctx.accounts.owner.key()
+            };
+            state::load_dispensing_accounts(ctx.remaining_accounts, auction, &receiver)?
+        };
+
+        // pick a random token to dispense according to token ratios, with deferred
+        // randomness the token is picked later by dispense_tokens, basket auctions
+        // dispense all tokens
+        let dispenser_amounts: Vec<u64> = dispensing_custodies
+            .iter()
+            .map(|dispenser| dispenser.amount)
+            .collect();
+        let (token_nums, max_amount_to_dispense) = if auction.deferred_claim {
+            (Vec::new(), auction.get_deferred_supply(&dispenser_amounts)?)
+        } else if auction.basket {
+            (
+                (0..auction.num_tokens as usize).collect(),
+                auction.get_basket_units(&dispenser_amounts)?,
+            )
+        } else if deferred_dispense {
+            require_eq!(bid.pending_amount, 0u64, LaunchpadError::BidAlreadyPlaced);
+            // the largest dispenser must be able to cover all pending bids
+            let max_dispenser_amount = dispenser_amounts.iter().max().copied().unwrap_or(0);
+            (
+                Vec::new(),
+                math::checked_div(max_dispenser_amount, auction.pricing.unit_size)?
+                    .saturating_sub(auction.stats.pending_amount),
+            )
+        } else {
+            msg!("Select token to dispense");
+            let rand_seed = if auction.num_tokens == 1 {
+                0
+            } else {
+                state::get_random_seed(&ctx.accounts.recent_slothashes)?
+            };
+            let token_num = auction
+                .select_token(&dispenser_amounts, 1, rand_seed)?
+                .ok_or(LaunchpadError::InsufficientAmount)?;
+            let max_amount_to_dispense = if auction.split_fills {
+                let mut total_amount = 0u64;
+                for &amount in dispenser_amounts.iter() {
+                    total_amount = math::checked_add(
+                        total_amount,
+                        math::checked_div(amount, auction.pricing.unit_size)?,
+                    )?;
+                }
+                total_amount
+            } else {
+                math::checked_div(dispenser_amounts[token_num], auction.pricing.unit_size)?
+            };
+            (vec![token_num], max_amount_to_dispense)
+        };
 
-    let fill_price = auction.get_auction_price(fill_amount, curtime)?;
-    require_gte!(params.price, fill_price, LaunchpadError::PriceCalcError);
+        // get available amount at the given price
+        msg!("Compute available amount");
+        let avail_amount = auction.get_hard_cap_amount(
+            std::cmp::min(
+                auction.get_auction_amount(params.price, curtime)?,
+                max_amount_to_dispense,
+            ),
+            curtime,
+            auction
+                .payment
+                .get_price_adjustment_bps(&payment_custody.key())?,
+        )?;
+
+        if avail_amount == 0 || (params.bid_type == BidType::Fok && avail_amount < params.amount) {
+            return err!(LaunchpadError::InsufficientAmount);
+        }
+        let fill_amount = std::cmp::min(avail_amount, params.amount);
+
+        let fill_price = auction.get_auction_price(fill_amount, curtime)?;
+        require_gte!(params.price, fill_price, LaunchpadError::PriceCalcError);
+
+        // split fills take the remainder from other dispensers
+        let token_fills: Vec<(usize, u64)> = if auction.split_fills {
+            auction
+                .route_fill(&dispenser_amounts, token_nums[0], fill_amount)?
+                .into_iter()
+                .enumerate()
+                .filter(|(_, units)| *units > 0)
+                .collect()
+        } else {
+            token_nums
+                .into_iter()
+                .map(|token_num| (token_num, fill_amount))
+                .collect()
+        };
+        for (token_num, units) in token_fills {
+            let unit_size = if auction.basket {
+                auction.tokens[token_num].basket_amount
+            } else {
+                auction.pricing.unit_size
+            };
+            dispensing_accounts.push((
+                token_num,
+                receiving_accounts[token_num].to_account_info(),
+                dispensing_custodies[token_num].to_account_info(),
+                dispensing_mints[token_num].to_account_info(),
+                dispensing_mints[token_num].decimals,
+                units,
+                unit_size,
+            ));
+        }
+
+        (fill_price, fill_amount)
+    };
 
     // check for malicious bid
     let fill_amount_limit = if bid.whitelisted {
@@ -296,12 +527,15 @@ pub fn place_bid<'info>(
             return collect_bad_bid_fee(
                 launchpad,
                 payment_custody,
-                ctx.accounts.token_program.to_account_info(),
+                native_sol,
                 ctx.accounts.funding_account.to_account_info(),
                 ctx.accounts.payment_token_account.to_account_info(),
+                ctx.accounts.payment_token_mint.to_account_info(),
                 ctx.accounts.pricing_oracle_account.to_account_info(),
                 ctx.accounts.owner.to_account_info(),
-                std::cmp::min(fill_amount, ctx.accounts.funding_account.amount),
+                ctx.accounts.token_program.to_account_info(),
+                ctx.accounts.system_program.to_account_info(),
+                std::cmp::min(fill_amount, funding_balance),
                 curtime,
             );
         }
@@ -309,63 +543,57 @@ pub fn place_bid<'info>(
 
     // compute payment amount
     let mut payment_amount = 0;
+    let mut fee_amount = 0;
     if fill_price > 0 {
         msg!("Compute payment amount");
-        let payment_token_price = if !launchpad.fees.trade.is_zero()
-            || payment_custody.key() != ctx.accounts.pricing_custody.key()
-        {
-            OraclePrice::new_from_oracle(
-                payment_custody.oracle_type,
-                &ctx.accounts.payment_oracle_account.to_account_info(),
-                payment_custody.max_oracle_price_error,
-                payment_custody.max_oracle_price_age_sec,
-                curtime,
-            )?
-        } else {
-            OraclePrice::new(0, 0)
-        };
-
-        if payment_custody.key() == ctx.accounts.pricing_custody.key() {
-            payment_amount = math::checked_mul(fill_price, fill_amount)?;
-        } else {
-            let pricing_custody = &ctx.accounts.pricing_custody;
-            let auction_token_price = OraclePrice::new_from_oracle(
-                pricing_custody.oracle_type,
-                &ctx.accounts.pricing_oracle_account.to_account_info(),
-                pricing_custody.max_oracle_price_error,
-                pricing_custody.max_oracle_price_age_sec,
-                curtime,
-            )?;
-
-            let token_pair_price = auction_token_price.checked_div(&payment_token_price)?;
-            let price_per_token = math::checked_decimal_ceil_mul(
-                fill_price,
-                -(pricing_custody.decimals as i32),
-                token_pair_price.price,
-                token_pair_price.exponent,
-                -(payment_custody.decimals as i32),
-            )?;
-
-            payment_amount = math::checked_mul(price_per_token, fill_amount)?;
-        }
+        let payment_token_price;
+        (payment_amount, payment_token_price) = state::get_payment_amount(
+            fill_price,
+            fill_amount,
+            &ctx.accounts.pricing_custody,
+            &ctx.accounts.pricing_oracle_account,
+            payment_custody,
+            &ctx.accounts.payment_oracle_account,
+            !launchpad.fees.trade.is_zero(),
+            curtime,
+        )?;
+        payment_amount = auction
+            .payment
+            .adjust_payment_amount(&payment_custody.key(), payment_amount)?;
 
         // compute fee
-        let fee_amount = launchpad.fees.trade.get_fee_amount(payment_amount)?;
+        fee_amount = launchpad.fees.trade.get_fee_amount(payment_amount)?;
 
         // collect payment and fee
         msg!("Collect payment {} and fee {}", payment_amount, fee_amount);
         let total_amount = math::checked_add(payment_amount, fee_amount)?;
-        let context = CpiContext::new(
+        let received_amount = transfer_payment(
+            native_sol,
+            ctx.accounts.funding_account.to_account_info(),
+            ctx.accounts.payment_token_account.to_account_info(),
+            ctx.accounts.payment_token_mint.to_account_info(),
+            ctx.accounts.owner.to_account_info(),
             ctx.accounts.token_program.to_account_info(),
-            Transfer {
-                from: ctx.accounts.funding_account.to_account_info(),
-                to: ctx.accounts.payment_token_account.to_account_info(),
-                authority: ctx.accounts.owner.to_account_info(),
-            },
-        );
-        anchor_spl::token::transfer(context, total_amount)?;
+            ctx.accounts.system_program.to_account_info(),
+            total_amount,
+            payment_custody.decimals,
+        )?;
+
+        // transfer fees withheld by Token-2022 payment mints are deducted from
+        // the payment, so only the amount actually received is credited
+        if received_amount < total_amount {
+            payment_amount = math::checked_sub(received_amount, fee_amount)?;
+            msg!("Received payment {} after transfer fees", payment_amount);
+        }
 
-        if fee_amount > 0 {
+        // fees for escrowed bids are collected upon settlement or once the soft cap is met
+        if fee_amount > 0
+            && !batch_auction
+            && !english_auction
+            && !limit_bid
+            && !soft_cap_escrow
+            && !auction.deferred_claim
+        {
             payment_custody.collected_fees =
                 math::checked_add(payment_custody.collected_fees, fee_amount)?;
 
@@ -388,7 +616,7 @@ pub fn place_bid<'info>(
         bid.auction = auction.key();
         bid.whitelisted = false;
         bid.seller_initialized = false;
-        bid.bump = *ctx.bumps.get("bid").ok_or(ProgramError::InvalidSeeds)?;
+        bid.bump = ctx.bumps.bid;
     } else if bid.owner != ctx.accounts.owner.key() || bid.auction != auction.key() {
         return err!(LaunchpadError::InvalidBidAddress);
     }
@@ -397,78 +625,290 @@ pub fn place_bid<'info>(
     bid.bid_price = params.price;
     bid.bid_amount = params.amount;
     bid.bid_type = params.bid_type;
-    bid.filled = math::checked_add(bid.filled, fill_amount)?;
-    bid.fill_time = bid.bid_time;
-    bid.fill_price = fill_price;
-    bid.fill_amount = fill_amount;
+
+    if batch_auction {
+        msg!("Escrow payment {} and fee {}", payment_amount, fee_amount);
+        bid.bid_price = fill_price;
+        bid.escrowed = true;
+        bid.escrow_custody = payment_custody.key();
+        bid.escrow_amount = payment_amount;
+        bid.escrow_fee = fee_amount;
+
+        let price_level = auction.get_price_level(fill_price)?;
+        auction.add_demand(price_level, fill_amount)?;
+
+        return Ok(());
+    }
+
+    if english_auction {
+        msg!("Escrow payment {} and fee {}", payment_amount, fee_amount);
+        bid.escrowed = true;
+        bid.escrow_custody = payment_custody.key();
+        bid.escrow_amount = payment_amount;
+        bid.escrow_fee = fee_amount;
+
+        if auction.has_leading_bid() {
+            refund_outbid(
+                launchpad,
+                auction,
+                &ctx.remaining_accounts[1..],
+                payment_custody,
+                ctx.accounts.payment_token_account.to_account_info(),
+                ctx.accounts.payment_token_mint.to_account_info(),
+                ctx.accounts.transfer_authority.to_account_info(),
+                ctx.accounts.owner.to_account_info(),
+                ctx.accounts.token_program.to_account_info(),
+                ctx.accounts.system_program.to_account_info(),
+            )?;
+        }
+
+        auction.stats.leading = LeadingBid {
+            bid: bid.key(),
+            owner: bid.owner,
+            refund_account: ctx.accounts.funding_account.key(),
+            price: fill_price,
+            bid_time: bid.bid_time,
+        };
+
+        return Ok(());
+    }
 
     // update seller's balance
     msg!("Update seller's balance");
     if seller_balance.bump == 0 {
         seller_balance.owner = auction.owner;
-        seller_balance.custody = ctx.accounts.payment_custody.key();
-        seller_balance.bump = *ctx
-            .bumps
-            .get("seller_balance")
-            .ok_or(ProgramError::InvalidSeeds)?;
+        seller_balance.custody = payment_custody.key();
+        seller_balance.bump = ctx.bumps.seller_balance;
     } else if seller_balance.owner != auction.owner
-        || seller_balance.custody == ctx.accounts.payment_custody.key()
+        || seller_balance.custody != payment_custody.key()
     {
         return err!(LaunchpadError::InvalidSellerBalanceAddress);
     }
-    seller_balance.balance = math::checked_add(seller_balance.balance, payment_amount)?;
 
-    // update auction stats
-    msg!("Update auction stats");
-    let curtime = auction.get_time()?;
-    if auction.stats.first_trade_time == 0 {
-        auction.stats.first_trade_time = curtime;
+    if limit_bid {
+        msg!("Escrow payment {} and fee {}", payment_amount, fee_amount);
+        bid.escrowed = true;
+        bid.escrow_custody = payment_custody.key();
+        bid.escrow_amount = payment_amount;
+        bid.escrow_fee = fee_amount;
+
+        // the evicted order keeps its escrow until canceled with cancel_limit_bid
+        if let Some(evicted) = ctx.accounts.limit_book.load_mut()?.insert(LimitOrder {
+            bid: bid.key(),
+            price: params.price,
+            bid_time: bid.bid_time,
+            amount: params.amount,
+        })? {
+            msg!("Evict order {}", evicted.bid);
+        }
+
+        return Ok(());
     }
-    auction.stats.last_trade_time = curtime;
-    auction.stats.last_amount = fill_amount;
-    auction.stats.last_price = fill_price;
 
-    let bidder_stats = if bid.whitelisted {
-        &mut auction.stats.wl_bidders
+    bid.filled = math::checked_add(bid.filled, fill_amount)?;
+    bid.fill_time = bid.bid_time;
+    bid.fill_price = fill_price;
+    bid.fill_amount = fill_amount;
+
+    if auction.deferred_claim {
+        msg!("Escrow payment {} and fee {}", payment_amount, fee_amount);
+        bid.escrowed = true;
+        bid.escrow_custody = payment_custody.key();
+        bid.escrow_amount = math::checked_add(bid.escrow_amount, payment_amount)?;
+        bid.escrow_fee = math::checked_add(bid.escrow_fee, fee_amount)?;
+    } else if soft_cap_escrow {
+        msg!("Escrow payment {} and fee {}", payment_amount, fee_amount);
+        bid.escrow_custody = payment_custody.key();
+        bid.escrow_amount = math::checked_add(bid.escrow_amount, payment_amount)?;
+        bid.escrow_fee = math::checked_add(bid.escrow_fee, fee_amount)?;
+        auction.stats.escrow.amount =
+            math::checked_add(auction.stats.escrow.amount, payment_amount)?;
+        auction.stats.escrow.fee = math::checked_add(auction.stats.escrow.fee, fee_amount)?;
     } else {
-        &mut auction.stats.reg_bidders
-    };
-    bidder_stats.fills_volume = math::checked_add(bidder_stats.fills_volume, fill_amount)?;
-    bidder_stats.weighted_fills_sum = math::checked_add(
-        bidder_stats.weighted_fills_sum,
-        math::checked_mul(fill_amount as u128, fill_price as u128)?,
+        seller_balance.balance = math::checked_add(seller_balance.balance, payment_amount)?;
+    }
+
+    // update auction stats
+    msg!("Update auction stats");
+    let curtime = auction.get_time()?;
+    let price_adjustment_bps = auction
+        .payment
+        .get_price_adjustment_bps(&payment_custody.key())?;
+    auction.update_stats(
+        fill_amount,
+        fill_price,
+        price_adjustment_bps,
+        bid.whitelisted,
+        curtime,
     )?;
-    if fill_price < bidder_stats.min_fill_price {
-        bidder_stats.min_fill_price = fill_price;
+    ctx.accounts
+        .price_history
+        .load_mut()?
+        .record_fill(fill_price, fill_amount, curtime)?;
+
+    // release escrowed payments to the seller once the soft cap is met
+    if soft_cap_escrow && auction.is_soft_cap_met() {
+        msg!(
+            "Release escrowed payments {} and fees {}",
+            auction.stats.escrow.amount,
+            auction.stats.escrow.fee
+        );
+        seller_balance.balance =
+            math::checked_add(seller_balance.balance, auction.stats.escrow.amount)?;
+        if auction.stats.escrow.fee > 0 {
+            state::collect_escrowed_fee(
+                launchpad,
+                payment_custody,
+                &ctx.accounts.payment_oracle_account,
+                auction.stats.escrow.fee,
+                false,
+                curtime,
+            )?;
+        }
+        auction.stats.escrow = EscrowStats::default();
     }
-    if fill_price > bidder_stats.max_fill_price {
-        bidder_stats.max_fill_price = fill_price;
+
+    if deferred_dispense || auction.deferred_claim {
+        msg!("Commit {} units to be dispensed", fill_amount);
+        bid.pending_amount = math::checked_add(bid.pending_amount, fill_amount)?;
+        if deferred_dispense {
+            bid.pending_slot = Clock::get()?.slot;
+        }
+        auction.stats.pending_amount =
+            math::checked_add(auction.stats.pending_amount, fill_amount)?;
+        return Ok(());
     }
-    bidder_stats.num_trades = bidder_stats.num_trades.wrapping_add(1);
 
     // transfer purchased tokens to the user
-    let transfer_amount = math::checked_mul(fill_amount, auction.pricing.unit_size)?;
-    msg!("Transfer {} tokens to the user", transfer_amount);
-    ctx.accounts.launchpad.transfer_tokens(
-        dispensing_custodies[token_num].to_account_info(),
-        receiving_accounts[token_num].to_account_info(),
-        ctx.accounts.transfer_authority.to_account_info(),
-        ctx.accounts.token_program.to_account_info(),
-        transfer_amount,
-    )?;
+    if dispensing_accounts.is_empty() {
+        return Err(ProgramError::NotEnoughAccountKeys.into());
+    }
+    for (
+        token_num,
+        receiving_account,
+        dispensing_custody,
+        dispensing_mint,
+        decimals,
+        units,
+        unit_size,
+    ) in dispensing_accounts
+    {
+        let transfer_amount = math::checked_mul(units, unit_size)?;
+        bid.token_fills[token_num] = math::checked_add(bid.token_fills[token_num], units)?;
+        auction.stats.token_fills[token_num] =
+            math::checked_add(auction.stats.token_fills[token_num], units)?;
+        msg!("Transfer {} tokens to the user", transfer_amount);
+        let balance_before = token_interface::accessor::amount(&receiving_account)?;
+        ctx.accounts.launchpad.transfer_tokens(
+            dispensing_custody,
+            receiving_account.clone(),
+            dispensing_mint,
+            ctx.accounts.transfer_authority.to_account_info(),
+            ctx.accounts.dispensing_token_program.to_account_info(),
+            transfer_amount,
+            decimals,
+        )?;
+        // vested tokens are locked at the amount received after transfer fees
+        if auction.vesting.is_enabled() {
+            let received_amount = math::checked_sub(
+                token_interface::accessor::amount(&receiving_account)?,
+                balance_before,
+            )?;
+            bid.vesting[token_num].amount =
+                math::checked_add(bid.vesting[token_num].amount, received_amount)?;
+        }
+    }
 
     Ok(())
 }
 
+/// Returns escrowed funds to the outbid leader of the English auction. Native SOL
+/// bids are refunded to the wallet. If the refund account can't receive tokens,
+/// funds stay escrowed in the bid and can be reclaimed with claim_tokens, so the
+/// leader can't block new bids.
+#[allow(clippy::too_many_arguments)]
+fn refund_outbid<'info>(
+    launchpad: &Launchpad,
+    auction: &Auction,
+    accounts: &'info [AccountInfo<'info>],
+    payment_custody: &Custody,
+    payment_token_account: AccountInfo<'info>,
+    payment_token_mint: AccountInfo<'info>,
+    transfer_authority: AccountInfo<'info>,
+    payer: AccountInfo<'info>,
+    token_program: AccountInfo<'info>,
+    system_program: AccountInfo<'info>,
+) -> Result<()> {
+    let mut outbid = Account::<Bid>::try_from(&accounts[0])?;
+    require_keys_eq!(
+        outbid.key(),
+        auction.stats.leading.bid,
+        LaunchpadError::InvalidBidAddress
+    );
+    require_keys_eq!(
+        accounts[1].key(),
+        auction.stats.leading.refund_account,
+        LaunchpadError::InvalidReceivingAddress
+    );
+
+    let refund_amount = math::checked_add(outbid.escrow_amount, outbid.escrow_fee)?;
+    if auction.stats.leading.refund_account == auction.stats.leading.owner {
+        msg!("Refund {} SOL to the outbid user", refund_amount);
+        state::transfer_native_sol(
+            launchpad,
+            payment_token_account,
+            accounts[2].clone(),
+            payment_token_mint,
+            transfer_authority,
+            payer,
+            accounts[1].clone(),
+            token_program,
+            system_program,
+            refund_amount,
+            payment_custody.decimals,
+        )?;
+    } else {
+        let can_refund = match InterfaceAccount::<TokenAccount>::try_from(&accounts[1]) {
+            Ok(refund_account) => {
+                refund_account.mint == payment_custody.mint && !refund_account.is_frozen()
+            }
+            Err(_) => false,
+        };
+        if !can_refund {
+            msg!("Refund account is not available, funds stay in escrow");
+            return Ok(());
+        }
+
+        msg!("Refund {} to the outbid user", refund_amount);
+        launchpad.transfer_tokens(
+            payment_token_account,
+            accounts[1].clone(),
+            payment_token_mint,
+            transfer_authority,
+            token_program,
+            refund_amount,
+            payment_custody.decimals,
+        )?;
+    }
+
+    outbid.escrowed = false;
+    outbid.escrow_amount = 0;
+    outbid.escrow_fee = 0;
+    outbid.exit(&crate::ID)
+}
+
 #[allow(clippy::too_many_arguments)]
 fn collect_bad_bid_fee<'info>(
     launchpad: &mut Account<'info, Launchpad>,
     custody: &mut Account<'info, Custody>,
-    token_program: AccountInfo<'info>,
+    native_sol: bool,
     funding_account: AccountInfo<'info>,
     destination_account: AccountInfo<'info>,
+    mint: AccountInfo<'info>,
     oracle_account: AccountInfo<'info>,
     authority: AccountInfo<'info>,
+    token_program: AccountInfo<'info>,
+    system_program: AccountInfo<'info>,
     bid_amount: u64,
     curtime: i64,
 ) -> Result<()> {
@@ -478,15 +918,18 @@ fn collect_bad_bid_fee<'info>(
     }
 
     msg!("Collect bad bid fee {}", fee_amount);
-    let context = CpiContext::new(
+    let decimals = custody.decimals;
+    let fee_amount = transfer_payment(
+        native_sol,
+        funding_account,
+        destination_account,
+        mint,
+        authority,
         token_program,
-        Transfer {
-            from: funding_account,
-            to: destination_account,
-            authority,
-        },
-    );
-    anchor_spl::token::transfer(context, fee_amount)?;
+        system_program,
+        fee_amount,
+        decimals,
+    )?;
 
     custody.collected_fees = math::checked_add(custody.collected_fees, fee_amount)?;
 
@@ -509,3 +952,57 @@ fn collect_bad_bid_fee<'info>(
 
     Ok(())
 }
+
+/// Transfers payment from the user's token account. Native SOL payments are
+/// transferred from the user's wallet to the wrapped SOL custody instead.
+/// Returns the amount received by the custody, which is less than the amount
+/// sent if the payment mint charges transfer fees.
+#[allow(clippy::too_many_arguments)]
+fn transfer_payment<'info>(
+    native_sol: bool,
+    funding_account: AccountInfo<'info>,
+    destination_account: AccountInfo<'info>,
+    mint: AccountInfo<'info>,
+    authority: AccountInfo<'info>,
+    token_program: AccountInfo<'info>,
+    system_program: AccountInfo<'info>,
+    amount: u64,
+    decimals: u8,
+) -> Result<u64> {
+    let balance_before = token_interface::accessor::amount(&destination_account)?;
+
+    if native_sol {
+        let context = CpiContext::new(
+            system_program,
+            anchor_lang::system_program::Transfer {
+                from: funding_account,
+                to: destination_account.clone(),
+            },
+        );
+        anchor_lang::system_program::transfer(context, amount)?;
+
+        let context = CpiContext::new(
+            token_program,
+            SyncNative {
+                account: destination_account.clone(),
+            },
+        );
+        token_interface::sync_native(context)?;
+    } else {
+        let context = CpiContext::new(
+            token_program,
+            TransferChecked {
+                from: funding_account,
+                mint,
+                to: destination_account.clone(),
+                authority,
+            },
+        );
+        token_interface::transfer_checked(context, amount, decimals)?;
+    }
+
+    math::checked_sub(
+        token_interface::accessor::amount(&destination_account)?,
+        balance_before,
+    )
+}
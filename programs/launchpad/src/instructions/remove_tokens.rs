@@ -3,10 +3,14 @@
 use {
     crate::{
         error::LaunchpadError,
-        state::{auction::Auction, launchpad::Launchpad},
+        math,
+        state::{
+            auction::{Auction, PricingModel},
+            launchpad::Launchpad,
+        },
     },
     anchor_lang::prelude::*,
-    anchor_spl::token::{Token, TokenAccount},
+    anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface},
 };
 
 #[derive(Accounts)]
@@ -19,7 +23,7 @@ pub struct RemoveTokens<'info> {
         constraint = receiving_account.mint == dispensing_custody.mint,
         has_one = owner
     )]
-    pub receiving_account: Box<Account<'info, TokenAccount>>,
+    pub receiving_account: Box<InterfaceAccount<'info, TokenAccount>>,
 
     /// CHECK: empty PDA, authority for token accounts
     #[account(
@@ -49,9 +53,14 @@ pub struct RemoveTokens<'info> {
                  auction.key().as_ref()],
         bump
     )]
-    pub dispensing_custody: Box<Account<'info, TokenAccount>>,
+    pub dispensing_custody: Box<InterfaceAccount<'info, TokenAccount>>,
 
-    token_program: Program<'info, Token>,
+    #[account(
+        constraint = dispensing_custody_mint.key() == dispensing_custody.mint
+    )]
+    pub dispensing_custody_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    token_program: Interface<'info, TokenInterface>,
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize)]
@@ -75,12 +84,63 @@ pub fn remove_tokens(ctx: Context<RemoveTokens>, params: &RemoveTokensParams) ->
         LaunchpadError::AuctionWithFixedAmount
     );
 
+    // tokens allocated to the batch auction bids can't be pulled out until claimed
+    let auction = &ctx.accounts.auction;
+    if auction.pricing.pricing_model == PricingModel::BatchAuction
+        && auction.is_ended(curtime, true)
+    {
+        require!(auction.is_settled(), LaunchpadError::AuctionNotSettled);
+        let reserved_amount = math::checked_mul(
+            auction.stats.clearing.unclaimed_amount,
+            auction.pricing.unit_size,
+        )?;
+        require_gte!(
+            math::checked_sub(ctx.accounts.dispensing_custody.amount, params.amount)?,
+            reserved_amount,
+            LaunchpadError::BidNotSettled
+        );
+    }
+
+    // the lot of the english auction stays in custody while there is a leading bid
+    if auction.pricing.pricing_model == PricingModel::EnglishAuction
+        && auction.has_leading_bid()
+        && !auction.is_settled()
+    {
+        require_gte!(
+            math::checked_sub(ctx.accounts.dispensing_custody.amount, params.amount)?,
+            auction.pricing.unit_size,
+            LaunchpadError::BidNotSettled
+        );
+    }
+
+    // tokens of pending bids are dispensed once the randomness is available
+    // or claimed, every dispenser keeps enough to cover all of them
+    if auction.stats.pending_amount > 0 {
+        let unit_size = if auction.basket {
+            auction.tokens[..auction.num_tokens as usize]
+                .iter()
+                .find(|token| token.account == ctx.accounts.dispensing_custody.key())
+                .map(|token| token.basket_amount)
+                .ok_or(LaunchpadError::InvalidDispenserAddress)?
+        } else {
+            auction.pricing.unit_size
+        };
+        let reserved_amount = math::checked_mul(auction.stats.pending_amount, unit_size)?;
+        require_gte!(
+            math::checked_sub(ctx.accounts.dispensing_custody.amount, params.amount)?,
+            reserved_amount,
+            LaunchpadError::BidNotSettled
+        );
+    }
+
     ctx.accounts.launchpad.transfer_tokens(
         ctx.accounts.dispensing_custody.to_account_info(),
         ctx.accounts.receiving_account.to_account_info(),
+        ctx.accounts.dispensing_custody_mint.to_account_info(),
         ctx.accounts.transfer_authority.to_account_info(),
         ctx.accounts.token_program.to_account_info(),
         params.amount,
+        ctx.accounts.dispensing_custody_mint.decimals,
     )?;
 
     Ok(())
@@ -0,0 +1,196 @@
+//! RevealBid instruction handler
+
+use {
+    crate::{
+        error::LaunchpadError,
+        math,
+        state::{self, auction::Auction, bid::Bid, custody::Custody, launchpad::Launchpad},
+    },
+    anchor_lang::prelude::*,
+    anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface},
+};
+
+#[derive(Accounts)]
+pub struct RevealBid<'info> {
+    #[account()]
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        constraint = refund_account.mint == payment_custody.mint,
+        has_one = owner
+    )]
+    pub refund_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// CHECK: empty PDA, authority for token accounts
+    #[account(
+        seeds = [b"transfer_authority"],
+        bump = launchpad.transfer_authority_bump
+    )]
+    pub transfer_authority: AccountInfo<'info>,
+
+    #[account(
+        seeds = [b"launchpad"],
+        bump = launchpad.launchpad_bump
+    )]
+    pub launchpad: Box<Account<'info, Launchpad>>,
+
+    #[account(
+        mut,
+        seeds = [b"auction",
+                 auction.common.name.as_bytes()],
+        bump = auction.bump
+    )]
+    pub auction: Box<Account<'info, Auction>>,
+
+    #[account(
+        mut,
+        has_one = owner,
+        constraint = bid.escrow_custody == payment_custody.key(),
+        seeds = [b"bid",
+                 owner.key().as_ref(),
+                 auction.key().as_ref()],
+        bump = bid.bump
+    )]
+    pub bid: Box<Account<'info, Bid>>,
+
+    #[account(
+        constraint = pricing_custody.key() == auction.pricing.custody,
+        seeds = [b"custody",
+                 pricing_custody.mint.as_ref()],
+        bump = pricing_custody.bump
+    )]
+    pub pricing_custody: Box<Account<'info, Custody>>,
+
+    /// CHECK: oracle account for the pricing token
+    #[account(
+        constraint = pricing_oracle_account.key() == pricing_custody.oracle_account
+    )]
+    pub pricing_oracle_account: AccountInfo<'info>,
+
+    #[account(
+        seeds = [b"custody",
+                 payment_custody.mint.as_ref()],
+        bump = payment_custody.bump
+    )]
+    pub payment_custody: Box<Account<'info, Custody>>,
+
+    /// CHECK: oracle account for the payment token
+    #[account(
+        constraint = payment_oracle_account.key() == payment_custody.oracle_account
+    )]
+    pub payment_oracle_account: AccountInfo<'info>,
+
+    #[account(
+        mut,
+        constraint = payment_token_account.key() == payment_custody.token_account.key()
+    )]
+    pub payment_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        constraint = payment_token_mint.key() == payment_custody.mint
+    )]
+    pub payment_token_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct RevealBidParams {
+    price: u64,
+    amount: u64,
+    salt: [u8; 32],
+}
+
+pub fn reveal_bid(ctx: Context<RevealBid>, params: &RevealBidParams) -> Result<()> {
+    let launchpad = &ctx.accounts.launchpad;
+    let auction = ctx.accounts.auction.as_mut();
+    let bid = ctx.accounts.bid.as_mut();
+
+    let curtime = auction.get_time()?;
+    require!(
+        auction.is_reveal_period(curtime),
+        LaunchpadError::RevealNotAllowed
+    );
+    require!(bid.sealed, LaunchpadError::RevealNotAllowed);
+
+    // check the commitment
+    require!(
+        Bid::get_commitment(
+            &ctx.accounts.owner.key(),
+            params.price,
+            params.amount,
+            &params.salt
+        ) == bid.commitment,
+        LaunchpadError::InvalidBidCommitment
+    );
+
+    // validate inputs
+    require_gt!(params.amount, 0u64, LaunchpadError::InvalidTokenAmount);
+    let order_amount_limit = if bid.whitelisted {
+        std::cmp::max(
+            auction.common.order_limit_wl_address,
+            auction.common.order_limit_reg_address,
+        )
+    } else {
+        auction.common.order_limit_reg_address
+    };
+    require_gte!(
+        order_amount_limit,
+        params.amount,
+        LaunchpadError::BidAmountTooLarge
+    );
+    let price_level = auction.get_price_level(params.price)?;
+    let bid_price = auction.get_level_price(price_level)?;
+
+    // check that deposit covers the payment
+    let (payment_amount, _) = state::get_payment_amount(
+        bid_price,
+        params.amount,
+        &ctx.accounts.pricing_custody,
+        &ctx.accounts.pricing_oracle_account,
+        &ctx.accounts.payment_custody,
+        &ctx.accounts.payment_oracle_account,
+        false,
+        curtime,
+    )?;
+    let payment_amount = auction
+        .payment
+        .adjust_payment_amount(&ctx.accounts.payment_custody.key(), payment_amount)?;
+    let fee_amount = launchpad.fees.trade.get_fee_amount(payment_amount)?;
+    let total_amount = math::checked_add(payment_amount, fee_amount)?;
+    require_gte!(
+        bid.escrow_amount,
+        total_amount,
+        LaunchpadError::InsufficientAmount
+    );
+    let refund_amount = math::checked_sub(bid.escrow_amount, total_amount)?;
+
+    // open the bid
+    msg!("Reveal bid: price {}, amount {}", bid_price, params.amount);
+    bid.bid_time = curtime;
+    bid.bid_price = bid_price;
+    bid.bid_amount = params.amount;
+    bid.escrow_amount = payment_amount;
+    bid.escrow_fee = fee_amount;
+    bid.sealed = false;
+    bid.commitment = [0; 32];
+
+    auction.add_demand(price_level, params.amount)?;
+
+    // refund excess deposit
+    if refund_amount > 0 {
+        msg!("Refund {} to the user", refund_amount);
+        launchpad.transfer_tokens(
+            ctx.accounts.payment_token_account.to_account_info(),
+            ctx.accounts.refund_account.to_account_info(),
+            ctx.accounts.payment_token_mint.to_account_info(),
+            ctx.accounts.transfer_authority.to_account_info(),
+            ctx.accounts.token_program.to_account_info(),
+            refund_amount,
+            ctx.accounts.payment_custody.decimals,
+        )?;
+    }
+
+    Ok(())
+}
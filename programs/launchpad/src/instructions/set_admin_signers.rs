@@ -25,7 +25,7 @@ pub struct SetAdminSignersParams {
 }
 
 pub fn set_admin_signers<'info>(
-    ctx: Context<'_, '_, '_, 'info, SetAdminSigners<'info>>,
+    ctx: Context<'_, '_, 'info, 'info, SetAdminSigners<'info>>,
     params: &SetAdminSignersParams,
 ) -> Result<u8> {
     // validate signatures
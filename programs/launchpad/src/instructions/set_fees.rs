@@ -40,7 +40,7 @@ pub struct SetFeesParams {
 }
 
 pub fn set_fees<'info>(
-    ctx: Context<'_, '_, '_, 'info, SetFees<'info>>,
+    ctx: Context<'_, '_, 'info, 'info, SetFees<'info>>,
     params: &SetFeesParams,
 ) -> Result<u8> {
     // validate signatures
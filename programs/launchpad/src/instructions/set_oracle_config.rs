@@ -35,14 +35,14 @@ pub struct SetOracleConfig<'info> {
 
 #[derive(AnchorSerialize, AnchorDeserialize)]
 pub struct SetOracleConfigParams {
-    pub max_oracle_price_error: f64,
+    pub max_oracle_price_error: u64,
     pub max_oracle_price_age_sec: u32,
     pub oracle_type: OracleType,
     pub oracle_account: Pubkey,
 }
 
 pub fn set_oracle_config<'info>(
-    ctx: Context<'_, '_, '_, 'info, SetOracleConfig<'info>>,
+    ctx: Context<'_, '_, 'info, 'info, SetOracleConfig<'info>>,
     params: &SetOracleConfigParams,
 ) -> Result<u8> {
     // validate signatures
@@ -42,7 +42,7 @@ pub struct SetPermissionsParams {
 }
 
 pub fn set_permissions<'info>(
-    ctx: Context<'_, '_, '_, 'info, SetPermissions<'info>>,
+    ctx: Context<'_, '_, 'info, 'info, SetPermissions<'info>>,
     params: &SetPermissionsParams,
 ) -> Result<u8> {
     // validate signatures
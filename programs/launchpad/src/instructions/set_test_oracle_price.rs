@@ -60,7 +60,7 @@ pub struct SetTestOraclePriceParams {
 }
 
 pub fn set_test_oracle_price<'info>(
-    ctx: Context<'_, '_, '_, 'info, SetTestOraclePrice<'info>>,
+    ctx: Context<'_, '_, 'info, 'info, SetTestOraclePrice<'info>>,
     params: &SetTestOraclePriceParams,
 ) -> Result<u8> {
     // validate signatures
@@ -38,7 +38,7 @@ pub struct SetTestTimeParams {
 }
 
 pub fn set_test_time<'info>(
-    ctx: Context<'_, '_, '_, 'info, SetTestTime<'info>>,
+    ctx: Context<'_, '_, 'info, 'info, SetTestTime<'info>>,
     params: &SetTestTimeParams,
 ) -> Result<u8> {
     if !cfg!(feature = "test") {
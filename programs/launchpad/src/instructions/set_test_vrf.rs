@@ -0,0 +1,76 @@
+//! SetTestVrf instruction handler
+
+use {
+    crate::state::{
+        auction::Auction,
+        multisig::{AdminInstruction, Multisig},
+        randomness::TestVrf,
+    },
+    anchor_lang::prelude::*,
+};
+
+#[derive(Accounts)]
+pub struct SetTestVrf<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"multisig"],
+        bump = multisig.load()?.bump
+    )]
+    pub multisig: AccountLoader<'info, Multisig>,
+
+    #[account(
+        seeds = [b"auction",
+                 auction.common.name.as_bytes()],
+        bump = auction.bump
+    )]
+    pub auction: Box<Account<'info, Auction>>,
+
+    #[account(
+        init_if_needed,
+        payer = admin,
+        space = TestVrf::LEN,
+        seeds = [b"test_vrf",
+                 auction.key().as_ref()],
+        bump
+    )]
+    pub vrf_account: Box<Account<'info, TestVrf>>,
+
+    system_program: Program<'info, System>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct SetTestVrfParams {
+    pub randomness: [u8; 32],
+    pub slot: u64,
+}
+
+pub fn set_test_vrf<'info>(
+    ctx: Context<'_, '_, 'info, 'info, SetTestVrf<'info>>,
+    params: &SetTestVrfParams,
+) -> Result<u8> {
+    // validate signatures
+    let mut multisig = ctx.accounts.multisig.load_mut()?;
+
+    let signatures_left = multisig.sign_multisig(
+        &ctx.accounts.admin,
+        &Multisig::get_account_infos(&ctx)[1..],
+        &Multisig::get_instruction_data(AdminInstruction::SetTestVrf, params)?,
+    )?;
+    if signatures_left > 0 {
+        msg!(
+            "Instruction has been signed but more signatures are required: {}",
+            signatures_left
+        );
+        return Ok(signatures_left);
+    }
+
+    // update randomness
+    let vrf_account = ctx.accounts.vrf_account.as_mut();
+    vrf_account.randomness = params.randomness;
+    vrf_account.slot = params.slot;
+
+    Ok(0)
+}
@@ -0,0 +1,84 @@
+//! SettleAuction instruction handler
+
+use {
+    crate::{
+        error::LaunchpadError,
+        math,
+        state::{
+            self,
+            auction::{Auction, PricingModel},
+        },
+    },
+    anchor_lang::prelude::*,
+    anchor_spl::token_interface::TokenAccount,
+};
+
+#[derive(Accounts)]
+pub struct SettleAuction<'info> {
+    #[account()]
+    pub user: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"auction",
+                 auction.common.name.as_bytes()],
+        bump = auction.bump
+    )]
+    pub auction: Box<Account<'info, Auction>>,
+    // remaining accounts:
+    //   1 to Auction::MAX_TOKENS dispensing custody addresses (read-only, unsigned)
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct SettleAuctionParams {}
+
+pub fn settle_auction<'info>(
+    ctx: Context<'_, '_, 'info, 'info, SettleAuction<'info>>,
+    _params: &SettleAuctionParams,
+) -> Result<()> {
+    let auction = ctx.accounts.auction.as_mut();
+
+    require!(
+        auction.pricing.pricing_model == PricingModel::BatchAuction,
+        LaunchpadError::UnsupportedPricingModel
+    );
+    require!(!auction.is_settled(), LaunchpadError::AuctionAlreadySettled);
+    let curtime = auction.get_time()?;
+    require!(
+        auction.is_settle_period(curtime),
+        LaunchpadError::AuctionInProgress
+    );
+
+    // compute the number of units available for sale
+    if ctx.remaining_accounts.len() > auction.num_tokens.into() {
+        return err!(LaunchpadError::TooManyAccountKeys);
+    }
+    if ctx.remaining_accounts.len() < auction.num_tokens.into() {
+        return Err(ProgramError::NotEnoughAccountKeys.into());
+    }
+    let dispensers = state::load_interface_accounts::<TokenAccount>(ctx.remaining_accounts)?;
+    let mut supply = 0u64;
+    for (i, dispenser) in dispensers.iter().enumerate() {
+        require_keys_eq!(
+            dispenser.key(),
+            auction.tokens[i].account,
+            LaunchpadError::InvalidDispenserAddress
+        );
+        supply = math::checked_add(
+            supply,
+            math::checked_div(dispenser.amount, auction.pricing.unit_size)?,
+        )?;
+    }
+
+    // compute the clearing price
+    auction.settle(supply, curtime)?;
+
+    msg!(
+        "Clearing price {}, supply {}, filled {}",
+        auction.stats.clearing.clearing_price,
+        supply,
+        auction.stats.clearing.unclaimed_amount
+    );
+
+    Ok(())
+}
@@ -11,7 +11,7 @@ use {
     anchor_lang::prelude::*,
     anchor_spl::token::Token,
     solana_address_lookup_table_program as saltp,
-    solana_program::{program, program_error::ProgramError, sysvar},
+    solana_program::{program, sysvar},
 };
 
 #[derive(Accounts)]
@@ -98,10 +98,7 @@ pub fn test_init(ctx: Context<TestInit>, params: &TestInitParams) -> Result<()>
     multisig.set_signers(ctx.remaining_accounts, params.min_signatures)?;
 
     // record multisig PDA bump
-    multisig.bump = *ctx
-        .bumps
-        .get("multisig")
-        .ok_or(ProgramError::InvalidSeeds)?;
+    multisig.bump = ctx.bumps.multisig;
 
     // record launchpad
     let launchpad = ctx.accounts.launchpad.as_mut();
@@ -119,14 +116,8 @@ pub fn test_init(ctx: Context<TestInit>, params: &TestInitParams) -> Result<()>
     launchpad.collected_fees.auction_update_sol = 0;
     launchpad.collected_fees.invalid_bid_usdc = 0;
     launchpad.collected_fees.trade_usdc = 0;
-    launchpad.transfer_authority_bump = *ctx
-        .bumps
-        .get("transfer_authority")
-        .ok_or(ProgramError::InvalidSeeds)?;
-    launchpad.launchpad_bump = *ctx
-        .bumps
-        .get("launchpad")
-        .ok_or(ProgramError::InvalidSeeds)?;
+    launchpad.transfer_authority_bump = ctx.bumps.transfer_authority;
+    launchpad.launchpad_bump = ctx.bumps.launchpad;
 
     if !launchpad.validate() {
         return err!(LaunchpadError::InvalidLaunchpadConfig);
@@ -30,7 +30,7 @@ pub struct WhitelistAddParams {
 }
 
 pub fn whitelist_add<'info>(
-    ctx: Context<'_, '_, '_, 'info, WhitelistAdd<'info>>,
+    ctx: Context<'_, '_, 'info, 'info, WhitelistAdd<'info>>,
     params: &WhitelistAddParams,
 ) -> Result<()> {
     if ctx.remaining_accounts.is_empty() || ctx.remaining_accounts.len() != params.addresses.len() {
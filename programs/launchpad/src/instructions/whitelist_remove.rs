@@ -29,7 +29,7 @@ pub struct WhitelistRemove<'info> {
 pub struct WhitelistRemoveParams {}
 
 pub fn whitelist_remove<'info>(
-    ctx: Context<'_, '_, '_, 'info, WhitelistRemove<'info>>,
+    ctx: Context<'_, '_, 'info, 'info, WhitelistRemove<'info>>,
     _params: &WhitelistRemoveParams,
 ) -> Result<()> {
     if ctx.remaining_accounts.is_empty() {
@@ -40,6 +40,7 @@ pub fn whitelist_remove<'info>(
         .accounts
         .auction
         .is_ended(ctx.accounts.auction.get_time()?, true);
+    let soft_cap_met = ctx.accounts.auction.is_soft_cap_met();
     let mut bid_accounts = state::load_accounts::<Bid>(ctx.remaining_accounts, &crate::ID)?;
     for bid in bid_accounts.iter_mut() {
         // validate bid address
@@ -59,8 +60,15 @@ pub fn whitelist_remove<'info>(
             LaunchpadError::InvalidBidAddress
         );
 
-        // remove from white-list or close the account
-        if auction_ended && bid.seller_initialized {
+        // remove from white-list or close the account, bids with pending
+        // soft cap refunds or locked tokens are kept open
+        if auction_ended
+            && bid.seller_initialized
+            && !bid.escrowed
+            && bid.pending_amount == 0
+            && !bid.has_locked_tokens()
+            && (soft_cap_met || (bid.escrow_amount == 0 && bid.escrow_fee == 0))
+        {
             bid.close(ctx.accounts.owner.to_account_info())?;
         } else {
             bid.whitelisted = false;
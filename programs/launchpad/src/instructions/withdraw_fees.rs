@@ -12,7 +12,7 @@ use {
         },
     },
     anchor_lang::prelude::*,
-    anchor_spl::token::{Token, TokenAccount},
+    anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface},
     solana_program::sysvar,
 };
 
@@ -50,17 +50,22 @@ pub struct WithdrawFees<'info> {
     )]
     pub custody: Box<Account<'info, Custody>>,
 
+    #[account(
+        constraint = custody_token_mint.key() == custody.mint
+    )]
+    pub custody_token_mint: Box<InterfaceAccount<'info, Mint>>,
+
     #[account(
         mut,
         constraint = custody_token_account.key() == custody.token_account.key()
     )]
-    pub custody_token_account: Box<Account<'info, TokenAccount>>,
+    pub custody_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
 
     #[account(
         mut,
         constraint = receiving_token_account.mint == custody_token_account.mint
     )]
-    pub receiving_token_account: Box<Account<'info, TokenAccount>>,
+    pub receiving_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
 
     /// CHECK: SOL fees receiving account
     #[account(
@@ -69,7 +74,7 @@ pub struct WithdrawFees<'info> {
     )]
     pub receiving_sol_account: AccountInfo<'info>,
 
-    token_program: Program<'info, Token>,
+    token_program: Interface<'info, TokenInterface>,
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize)]
@@ -79,7 +84,7 @@ pub struct WithdrawFeesParams {
 }
 
 pub fn withdraw_fees<'info>(
-    ctx: Context<'_, '_, '_, 'info, WithdrawFees<'info>>,
+    ctx: Context<'_, '_, 'info, 'info, WithdrawFees<'info>>,
     params: &WithdrawFeesParams,
 ) -> Result<u8> {
     // validate inputs
@@ -120,9 +125,11 @@ pub fn withdraw_fees<'info>(
         ctx.accounts.launchpad.transfer_tokens(
             ctx.accounts.custody_token_account.to_account_info(),
             ctx.accounts.receiving_token_account.to_account_info(),
+            ctx.accounts.custody_token_mint.to_account_info(),
             ctx.accounts.transfer_authority.to_account_info(),
             ctx.accounts.token_program.to_account_info(),
             params.token_amount,
+            custody.decimals,
         )?;
     }
 
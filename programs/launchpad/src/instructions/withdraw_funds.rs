@@ -5,16 +5,20 @@ use {
         error::LaunchpadError,
         math,
         state::{
-            auction::Auction, custody::Custody, launchpad::Launchpad, seller_balance::SellerBalance,
+            self, auction::Auction, custody::Custody, launchpad::Launchpad,
+            seller_balance::SellerBalance,
         },
     },
-    anchor_lang::prelude::*,
-    anchor_spl::token::{Token, TokenAccount},
+    anchor_lang::{prelude::*, Owners},
+    anchor_spl::{
+        token,
+        token_interface::{Mint, TokenAccount, TokenInterface},
+    },
 };
 
 #[derive(Accounts)]
 pub struct WithdrawFunds<'info> {
-    #[account()]
+    #[account(mut)]
     pub owner: Signer<'info>,
 
     /// CHECK: empty PDA, authority for token accounts
@@ -46,11 +50,16 @@ pub struct WithdrawFunds<'info> {
     )]
     pub custody: Box<Account<'info, Custody>>,
 
+    #[account(
+        constraint = custody_token_mint.key() == custody.mint
+    )]
+    pub custody_token_mint: Box<InterfaceAccount<'info, Mint>>,
+
     #[account(
         mut,
         constraint = custody_token_account.key() == custody.token_account.key()
     )]
-    pub custody_token_account: Box<Account<'info, TokenAccount>>,
+    pub custody_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
 
     #[account(
         mut,
@@ -63,19 +72,20 @@ pub struct WithdrawFunds<'info> {
     )]
     pub seller_balance: Box<Account<'info, SellerBalance>>,
 
-    #[account(
-        mut,
-        constraint = receiving_account.mint == custody_token_account.mint,
-        has_one = owner
-    )]
-    pub receiving_account: Box<Account<'info, TokenAccount>>,
+    /// CHECK: owner's token account, or the temporary wrapped SOL account
+    /// for native SOL withdrawals, see state::transfer_native_sol()
+    #[account(mut)]
+    pub receiving_account: AccountInfo<'info>,
 
-    token_program: Program<'info, Token>,
+    system_program: Program<'info, System>,
+    token_program: Interface<'info, TokenInterface>,
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize)]
 pub struct WithdrawFundsParams {
     pub amount: u64,
+    // unwrap SOL to the owner's wallet
+    pub native_sol: bool,
 }
 
 pub fn withdraw_funds(ctx: Context<WithdrawFunds>, params: &WithdrawFundsParams) -> Result<()> {
@@ -86,6 +96,13 @@ pub fn withdraw_funds(ctx: Context<WithdrawFunds>, params: &WithdrawFundsParams)
 
     // validate inputs
     require_gt!(params.amount, 0u64, LaunchpadError::InvalidTokenAmount);
+    if params.native_sol {
+        require_keys_eq!(
+            ctx.accounts.custody.mint,
+            token::spl_token::native_mint::id(),
+            LaunchpadError::InvalidPaymentCustody
+        );
+    }
 
     // transfer fees from the custody to the receiver
     msg!(
@@ -100,13 +117,43 @@ pub fn withdraw_funds(ctx: Context<WithdrawFunds>, params: &WithdrawFundsParams)
     }
     seller_balance.balance = math::checked_sub(seller_balance.balance, params.amount)?;
 
+    // pay out in native SOL
+    if params.native_sol {
+        msg!("Unwrap SOL to the owner's wallet");
+        return state::transfer_native_sol(
+            &ctx.accounts.launchpad,
+            ctx.accounts.custody_token_account.to_account_info(),
+            ctx.accounts.receiving_account.to_account_info(),
+            ctx.accounts.custody_token_mint.to_account_info(),
+            ctx.accounts.transfer_authority.to_account_info(),
+            ctx.accounts.owner.to_account_info(),
+            ctx.accounts.owner.to_account_info(),
+            ctx.accounts.token_program.to_account_info(),
+            ctx.accounts.system_program.to_account_info(),
+            params.amount,
+            ctx.accounts.custody.decimals,
+        );
+    }
+
+    require!(
+        TokenAccount::owners().contains(ctx.accounts.receiving_account.owner),
+        LaunchpadError::InvalidReceivingAddress
+    );
+    let receiving_account =
+        TokenAccount::try_deserialize(&mut &ctx.accounts.receiving_account.try_borrow_data()?[..])?;
+    require!(
+        receiving_account.mint == ctx.accounts.custody_token_account.mint
+            && receiving_account.owner == ctx.accounts.owner.key(),
+        LaunchpadError::InvalidReceivingAddress
+    );
+
     ctx.accounts.launchpad.transfer_tokens(
         ctx.accounts.custody_token_account.to_account_info(),
         ctx.accounts.receiving_account.to_account_info(),
+        ctx.accounts.custody_token_mint.to_account_info(),
         ctx.accounts.transfer_authority.to_account_info(),
         ctx.accounts.token_program.to_account_info(),
         params.amount,
-    )?;
-
-    Ok(())
+        ctx.accounts.custody.decimals,
+    )
 }
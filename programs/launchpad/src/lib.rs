@@ -2,10 +2,10 @@
 
 #![allow(clippy::result_large_err)]
 
-mod error;
+pub mod error;
 mod instructions;
-mod math;
-mod state;
+pub mod math;
+pub mod state;
 
 use {anchor_lang::prelude::*, instructions::*};
 
@@ -27,7 +27,7 @@ pub mod launchpad {
     // admin instructions
 
     pub fn delete_auction<'info>(
-        ctx: Context<'_, '_, '_, 'info, DeleteAuction<'info>>,
+        ctx: Context<'_, '_, 'info, 'info, DeleteAuction<'info>>,
         params: DeleteAuctionParams,
     ) -> Result<u8> {
         instructions::delete_auction(ctx, &params)
@@ -38,42 +38,42 @@ pub mod launchpad {
     }
 
     pub fn init_custody<'info>(
-        ctx: Context<'_, '_, '_, 'info, InitCustody<'info>>,
+        ctx: Context<'_, '_, 'info, 'info, InitCustody<'info>>,
         params: InitCustodyParams,
     ) -> Result<u8> {
         instructions::init_custody(ctx, &params)
     }
 
     pub fn set_admin_signers<'info>(
-        ctx: Context<'_, '_, '_, 'info, SetAdminSigners<'info>>,
+        ctx: Context<'_, '_, 'info, 'info, SetAdminSigners<'info>>,
         params: SetAdminSignersParams,
     ) -> Result<u8> {
         instructions::set_admin_signers(ctx, &params)
     }
 
     pub fn set_fees<'info>(
-        ctx: Context<'_, '_, '_, 'info, SetFees<'info>>,
+        ctx: Context<'_, '_, 'info, 'info, SetFees<'info>>,
         params: SetFeesParams,
     ) -> Result<u8> {
         instructions::set_fees(ctx, &params)
     }
 
     pub fn set_oracle_config<'info>(
-        ctx: Context<'_, '_, '_, 'info, SetOracleConfig<'info>>,
+        ctx: Context<'_, '_, 'info, 'info, SetOracleConfig<'info>>,
         params: SetOracleConfigParams,
     ) -> Result<u8> {
         instructions::set_oracle_config(ctx, &params)
     }
 
     pub fn set_permissions<'info>(
-        ctx: Context<'_, '_, '_, 'info, SetPermissions<'info>>,
+        ctx: Context<'_, '_, 'info, 'info, SetPermissions<'info>>,
         params: SetPermissionsParams,
     ) -> Result<u8> {
         instructions::set_permissions(ctx, &params)
     }
 
     pub fn withdraw_fees<'info>(
-        ctx: Context<'_, '_, '_, 'info, WithdrawFees<'info>>,
+        ctx: Context<'_, '_, 'info, 'info, WithdrawFees<'info>>,
         params: WithdrawFeesParams,
     ) -> Result<u8> {
         instructions::withdraw_fees(ctx, &params)
@@ -82,19 +82,26 @@ pub mod launchpad {
     // test instructions
 
     pub fn set_test_oracle_price<'info>(
-        ctx: Context<'_, '_, '_, 'info, SetTestOraclePrice<'info>>,
+        ctx: Context<'_, '_, 'info, 'info, SetTestOraclePrice<'info>>,
         params: SetTestOraclePriceParams,
     ) -> Result<u8> {
         instructions::set_test_oracle_price(ctx, &params)
     }
 
     pub fn set_test_time<'info>(
-        ctx: Context<'_, '_, '_, 'info, SetTestTime<'info>>,
+        ctx: Context<'_, '_, 'info, 'info, SetTestTime<'info>>,
         params: SetTestTimeParams,
     ) -> Result<u8> {
         instructions::set_test_time(ctx, &params)
     }
 
+    pub fn set_test_vrf<'info>(
+        ctx: Context<'_, '_, 'info, 'info, SetTestVrf<'info>>,
+        params: SetTestVrfParams,
+    ) -> Result<u8> {
+        instructions::set_test_vrf(ctx, &params)
+    }
+
     pub fn test_init(ctx: Context<TestInit>, params: TestInitParams) -> Result<()> {
         instructions::test_init(ctx, &params)
     }
@@ -117,7 +124,7 @@ pub mod launchpad {
     }
 
     pub fn init_auction<'info>(
-        ctx: Context<'_, '_, '_, 'info, InitAuction<'info>>,
+        ctx: Context<'_, '_, 'info, 'info, InitAuction<'info>>,
         params: InitAuctionParams,
     ) -> Result<()> {
         instructions::init_auction(ctx, &params)
@@ -132,14 +139,14 @@ pub mod launchpad {
     }
 
     pub fn whitelist_add<'info>(
-        ctx: Context<'_, '_, '_, 'info, WhitelistAdd<'info>>,
+        ctx: Context<'_, '_, 'info, 'info, WhitelistAdd<'info>>,
         params: WhitelistAddParams,
     ) -> Result<()> {
         instructions::whitelist_add(ctx, &params)
     }
 
     pub fn whitelist_remove<'info>(
-        ctx: Context<'_, '_, '_, 'info, WhitelistRemove<'info>>,
+        ctx: Context<'_, '_, 'info, 'info, WhitelistRemove<'info>>,
         params: WhitelistRemoveParams,
     ) -> Result<()> {
         instructions::whitelist_remove(ctx, &params)
@@ -155,6 +162,53 @@ pub mod launchpad {
         instructions::cancel_bid(ctx, &params)
     }
 
+    pub fn cancel_limit_bid(
+        ctx: Context<CancelLimitBid>,
+        params: CancelLimitBidParams,
+    ) -> Result<()> {
+        instructions::cancel_limit_bid(ctx, &params)
+    }
+
+    pub fn claim_refund(ctx: Context<ClaimRefund>, params: ClaimRefundParams) -> Result<()> {
+        instructions::claim_refund(ctx, &params)
+    }
+
+    pub fn claim_tokens<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ClaimTokens<'info>>,
+        params: ClaimTokensParams,
+    ) -> Result<()> {
+        instructions::claim_tokens(ctx, &params)
+    }
+
+    pub fn claim_vested(ctx: Context<ClaimVested>, params: ClaimVestedParams) -> Result<()> {
+        instructions::claim_vested(ctx, &params)
+    }
+
+    pub fn commit_bid(ctx: Context<CommitBid>, params: CommitBidParams) -> Result<()> {
+        instructions::commit_bid(ctx, &params)
+    }
+
+    pub fn crank_fills<'info>(
+        ctx: Context<'_, '_, 'info, 'info, CrankFills<'info>>,
+        params: CrankFillsParams,
+    ) -> Result<u64> {
+        instructions::crank_fills(ctx, &params)
+    }
+
+    pub fn dispense_tokens<'info>(
+        ctx: Context<'_, '_, 'info, 'info, DispenseTokens<'info>>,
+        params: DispenseTokensParams,
+    ) -> Result<()> {
+        instructions::dispense_tokens(ctx, &params)
+    }
+
+    pub fn finalize_auction(
+        ctx: Context<FinalizeAuction>,
+        params: FinalizeAuctionParams,
+    ) -> Result<()> {
+        instructions::finalize_auction(ctx, &params)
+    }
+
     pub fn get_auction_amount(
         ctx: Context<GetAuctionAmount>,
         params: GetAuctionAmountParams,
@@ -169,10 +223,28 @@ pub mod launchpad {
         instructions::get_auction_price(ctx, &params)
     }
 
+    pub fn get_quote<'info>(
+        ctx: Context<'_, '_, 'info, 'info, GetQuote<'info>>,
+        params: GetQuoteParams,
+    ) -> Result<Quote> {
+        instructions::get_quote(ctx, &params)
+    }
+
     pub fn place_bid<'info>(
-        ctx: Context<'_, '_, '_, 'info, PlaceBid<'info>>,
+        ctx: Context<'_, '_, 'info, 'info, PlaceBid<'info>>,
         params: PlaceBidParams,
     ) -> Result<()> {
         instructions::place_bid(ctx, &params)
     }
+
+    pub fn reveal_bid(ctx: Context<RevealBid>, params: RevealBidParams) -> Result<()> {
+        instructions::reveal_bid(ctx, &params)
+    }
+
+    pub fn settle_auction<'info>(
+        ctx: Context<'_, '_, 'info, 'info, SettleAuction<'info>>,
+        params: SettleAuctionParams,
+    ) -> Result<()> {
+        instructions::settle_auction(ctx, &params)
+    }
 }
@@ -343,3 +343,373 @@ pub fn to_token_amount(ui_amount: f64, decimals: u8) -> Result<u64> {
         checked_powi(10.0, decimals as i32)?,
     )?)
 }
+
+// Fixed-point numbers are u128 values scaled by FIXED_POINT_ONE, i.e. they carry
+// 9 decimal digits. Every routine below comes in a floor and a ceil flavor, so
+// a computed value is always a lower or an upper bound of the exact result and
+// callers can pick the direction that favors the seller. Arithmetic routines
+// are exact up to the final rounding, exp/ln-based routines have relative error
+// below 1e-8.
+pub const FIXED_POINT_DECIMALS: u8 = 9;
+pub const FIXED_POINT_ONE: u128 = 1_000_000_000;
+// largest argument accepted by checked_fixed_exp(), e^64 still fits into u128
+pub const FIXED_POINT_MAX_EXP: u128 = 64 * FIXED_POINT_ONE;
+// ln(2) with 18 decimal digits for range reduction
+const LN2_SCALED: u128 = 693_147_180_559_945_309;
+
+pub fn checked_fixed_mul(arg1: u128, arg2: u128) -> Result<u128> {
+    checked_div(checked_mul(arg1, arg2)?, FIXED_POINT_ONE)
+}
+
+pub fn checked_fixed_ceil_mul(arg1: u128, arg2: u128) -> Result<u128> {
+    checked_ceil_div(checked_mul(arg1, arg2)?, FIXED_POINT_ONE)
+}
+
+pub fn checked_fixed_div(arg1: u128, arg2: u128) -> Result<u128> {
+    checked_div(checked_mul(arg1, FIXED_POINT_ONE)?, arg2)
+}
+
+pub fn checked_fixed_ceil_div(arg1: u128, arg2: u128) -> Result<u128> {
+    checked_ceil_div(checked_mul(arg1, FIXED_POINT_ONE)?, arg2)
+}
+
+/// Returns arg^exp rounded down, saturates at u128::MAX instead of overflowing
+pub fn checked_fixed_powi(arg: u128, exp: u64) -> Result<u128> {
+    fixed_powi(arg, exp, false)
+}
+
+/// Returns arg^exp rounded up, saturates at u128::MAX instead of overflowing
+pub fn checked_fixed_ceil_powi(arg: u128, exp: u64) -> Result<u128> {
+    fixed_powi(arg, exp, true)
+}
+
+/// Returns e^arg rounded down
+pub fn checked_fixed_exp(arg: u128) -> Result<u128> {
+    fixed_exp(arg, false)
+}
+
+/// Returns e^arg rounded up
+pub fn checked_fixed_ceil_exp(arg: u128) -> Result<u128> {
+    fixed_exp(arg, true)
+}
+
+/// Returns e^-arg rounded down
+pub fn checked_fixed_exp_neg(arg: u128) -> Result<u128> {
+    if arg > FIXED_POINT_MAX_EXP {
+        return Ok(0);
+    }
+    checked_fixed_div(FIXED_POINT_ONE, fixed_exp(arg, true)?)
+}
+
+/// Returns e^-arg rounded up
+pub fn checked_fixed_ceil_exp_neg(arg: u128) -> Result<u128> {
+    if arg > FIXED_POINT_MAX_EXP {
+        return Ok(1);
+    }
+    checked_fixed_ceil_div(FIXED_POINT_ONE, fixed_exp(arg, false)?)
+}
+
+/// Returns ln(arg) rounded down, arg must be >= 1
+pub fn checked_fixed_ln(arg: u128) -> Result<u128> {
+    fixed_ln(arg, false)
+}
+
+/// Returns ln(arg) rounded up, arg must be >= 1
+pub fn checked_fixed_ceil_ln(arg: u128) -> Result<u128> {
+    fixed_ln(arg, true)
+}
+
+/// Returns (e^arg - 1) / arg rounded down
+pub fn checked_fixed_exprel(arg: u128) -> Result<u128> {
+    fixed_exprel(arg, false)
+}
+
+/// Returns (e^arg - 1) / arg rounded up
+pub fn checked_fixed_ceil_exprel(arg: u128) -> Result<u128> {
+    fixed_exprel(arg, true)
+}
+
+/// Returns ln(1 + arg) / arg rounded down
+pub fn checked_fixed_lnrel(arg: u128) -> Result<u128> {
+    fixed_lnrel(arg, false)
+}
+
+/// Returns ln(1 + arg) / arg rounded up
+pub fn checked_fixed_ceil_lnrel(arg: u128) -> Result<u128> {
+    fixed_lnrel(arg, true)
+}
+
+fn fixed_mul_rounded(arg1: u128, arg2: u128, round_up: bool) -> Result<u128> {
+    if round_up {
+        checked_fixed_ceil_mul(arg1, arg2)
+    } else {
+        checked_fixed_mul(arg1, arg2)
+    }
+}
+
+fn fixed_div_rounded(arg1: u128, arg2: u128, round_up: bool) -> Result<u128> {
+    if round_up {
+        checked_fixed_ceil_div(arg1, arg2)
+    } else {
+        checked_fixed_div(arg1, arg2)
+    }
+}
+
+// Sums the series starting with the given term, where each next term is derived
+// from the previous one with next_term(). When rounding up, terms never drop below
+// one unit, and once they do the remaining tail is bounded by one more unit.
+fn fixed_series_sum(
+    first_term: u128,
+    round_up: bool,
+    mut next_term: impl FnMut(u128, u128) -> Result<u128>,
+) -> Result<u128> {
+    let mut sum = first_term;
+    let mut term = first_term;
+    let mut n = 1u128;
+    while term > 0 {
+        term = next_term(term, n)?;
+        sum = checked_add(sum, term)?;
+        if round_up && term <= 1 {
+            return checked_add(sum, 1);
+        }
+        n = checked_add(n, 1)?;
+    }
+    Ok(sum)
+}
+
+fn fixed_powi(arg: u128, exp: u64, round_up: bool) -> Result<u128> {
+    // exponentiation by squaring, powers of arg > 1 only grow, so
+    // once a product overflows the result is saturated
+    let mut result = FIXED_POINT_ONE;
+    let mut base = arg;
+    let mut exp = exp;
+    while exp > 0 {
+        if exp & 1 == 1 {
+            if result.checked_mul(base).is_none() {
+                return Ok(u128::MAX);
+            }
+            result = fixed_mul_rounded(result, base, round_up)?;
+        }
+        exp >>= 1;
+        if exp > 0 {
+            if base.checked_mul(base).is_none() {
+                return Ok(u128::MAX);
+            }
+            base = fixed_mul_rounded(base, base, round_up)?;
+        }
+    }
+    Ok(result)
+}
+
+fn fixed_exp(arg: u128, round_up: bool) -> Result<u128> {
+    if arg > FIXED_POINT_MAX_EXP {
+        msg!("Error: Overflow in exp({})", arg);
+        return err!(LaunchpadError::MathOverflow);
+    }
+
+    // e^arg = 2^k * e^r, where r = arg - k * ln(2) is in [0, ln(2))
+    let scaled_arg = checked_mul(arg, FIXED_POINT_ONE)?;
+    let k = checked_div(scaled_arg, LN2_SCALED)?;
+    let scaled_r = checked_sub(scaled_arg, checked_mul(k, LN2_SCALED)?)?;
+    let r = if round_up {
+        checked_ceil_div(scaled_r, FIXED_POINT_ONE)?
+    } else {
+        checked_div(scaled_r, FIXED_POINT_ONE)?
+    };
+
+    // e^r = 1 + r + r^2/2! + r^3/3! + ...
+    let exp_r = fixed_series_sum(FIXED_POINT_ONE, round_up, |term, n| {
+        fixed_div_rounded(
+            fixed_mul_rounded(term, r, round_up)?,
+            checked_mul(n, FIXED_POINT_ONE)?,
+            round_up,
+        )
+    })?;
+
+    checked_mul(exp_r, checked_pow(2u128, k as usize)?)
+}
+
+fn fixed_ln(arg: u128, round_up: bool) -> Result<u128> {
+    if arg < FIXED_POINT_ONE {
+        msg!("Error: Overflow in ln({})", arg);
+        return err!(LaunchpadError::MathOverflow);
+    }
+
+    // ln(arg) = k * ln(2) + ln(m), where m = arg / 2^k is in [1, 2)
+    let mut k = 0u128;
+    while (arg >> (k + 1)) >= FIXED_POINT_ONE {
+        k += 1;
+    }
+    let m = if round_up {
+        checked_ceil_div(arg, 1u128 << k)?
+    } else {
+        arg >> k
+    };
+
+    // ln(m) = 2 * (s + s^3/3 + s^5/5 + ...), where s = (m - 1) / (m + 1)
+    let s = fixed_div_rounded(
+        checked_sub(m, FIXED_POINT_ONE)?,
+        checked_add(m, FIXED_POINT_ONE)?,
+        round_up,
+    )?;
+    let s2 = fixed_mul_rounded(s, s, round_up)?;
+    let mut power = s;
+    let ln_m = fixed_series_sum(s, round_up, |_, n| {
+        power = fixed_mul_rounded(power, s2, round_up)?;
+        let divisor = checked_add(checked_mul(n, 2)?, 1)?;
+        if round_up {
+            checked_ceil_div(power, divisor)
+        } else {
+            checked_div(power, divisor)
+        }
+    })?;
+
+    let scaled_k_ln2 = checked_mul(k, LN2_SCALED)?;
+    let k_ln2 = if round_up {
+        checked_ceil_div(scaled_k_ln2, FIXED_POINT_ONE)?
+    } else {
+        checked_div(scaled_k_ln2, FIXED_POINT_ONE)?
+    };
+    checked_add(k_ln2, checked_mul(ln_m, 2)?)
+}
+
+fn fixed_exprel(arg: u128, round_up: bool) -> Result<u128> {
+    if arg >= FIXED_POINT_ONE {
+        // no cancellation for large arguments
+        return fixed_div_rounded(
+            checked_sub(fixed_exp(arg, round_up)?, FIXED_POINT_ONE)?,
+            arg,
+            round_up,
+        );
+    }
+
+    // (e^arg - 1) / arg = 1 + arg/2! + arg^2/3! + ...
+    fixed_series_sum(FIXED_POINT_ONE, round_up, |term, n| {
+        fixed_div_rounded(
+            fixed_mul_rounded(term, arg, round_up)?,
+            checked_mul(checked_add(n, 1)?, FIXED_POINT_ONE)?,
+            round_up,
+        )
+    })
+}
+
+fn fixed_lnrel(arg: u128, round_up: bool) -> Result<u128> {
+    if arg >= FIXED_POINT_ONE {
+        // no cancellation for large arguments
+        return fixed_div_rounded(
+            fixed_ln(checked_add(FIXED_POINT_ONE, arg)?, round_up)?,
+            arg,
+            round_up,
+        );
+    }
+
+    // ln(1 + arg) / arg = 2 / (2 + arg) * (1 + s^2/3 + s^4/5 + ...),
+    // where s = arg / (2 + arg)
+    let two = checked_mul(FIXED_POINT_ONE, 2)?;
+    let two_plus_arg = checked_add(two, arg)?;
+    let s = fixed_div_rounded(arg, two_plus_arg, round_up)?;
+    let s2 = fixed_mul_rounded(s, s, round_up)?;
+    let mut power = FIXED_POINT_ONE;
+    let series = fixed_series_sum(FIXED_POINT_ONE, round_up, |_, n| {
+        power = fixed_mul_rounded(power, s2, round_up)?;
+        let divisor = checked_add(checked_mul(n, 2)?, 1)?;
+        if round_up {
+            checked_ceil_div(power, divisor)
+        } else {
+            checked_div(power, divisor)
+        }
+    })?;
+
+    fixed_mul_rounded(
+        fixed_div_rounded(two, two_plus_arg, round_up)?,
+        series,
+        round_up,
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // checks that floor and ceil values bracket the exact result
+    fn assert_bounds(exact: f64, floor: u128, ceil: u128) {
+        let exact = exact * FIXED_POINT_ONE as f64;
+        let tolerance = f64::max(exact * 1e-8, 32.0);
+        assert!(
+            floor as f64 <= exact * (1.0 + 1e-15),
+            "{} > {}",
+            floor,
+            exact
+        );
+        assert!(ceil as f64 >= exact * (1.0 - 1e-15), "{} < {}", ceil, exact);
+        assert!(exact - floor as f64 <= tolerance, "{} << {}", floor, exact);
+        assert!(ceil as f64 - exact <= tolerance, "{} >> {}", ceil, exact);
+    }
+
+    #[test]
+    fn fixed_exp_ln() {
+        for arg in [
+            1u128,
+            123_456,
+            500_000_000,
+            693_147_181,
+            1_000_000_000,
+            20_123_456_789,
+        ] {
+            let x = arg as f64 / FIXED_POINT_ONE as f64;
+            assert_bounds(
+                f64::exp(x),
+                checked_fixed_exp(arg).unwrap(),
+                checked_fixed_ceil_exp(arg).unwrap(),
+            );
+            assert_bounds(
+                f64::exp(-x),
+                checked_fixed_exp_neg(arg).unwrap(),
+                checked_fixed_ceil_exp_neg(arg).unwrap(),
+            );
+            assert_bounds(
+                f64::exp_m1(x) / x,
+                checked_fixed_exprel(arg).unwrap(),
+                checked_fixed_ceil_exprel(arg).unwrap(),
+            );
+            assert_bounds(
+                f64::ln_1p(x) / x,
+                checked_fixed_lnrel(arg).unwrap(),
+                checked_fixed_ceil_lnrel(arg).unwrap(),
+            );
+            assert_bounds(
+                f64::ln_1p(x),
+                checked_fixed_ln(FIXED_POINT_ONE + arg).unwrap(),
+                checked_fixed_ceil_ln(FIXED_POINT_ONE + arg).unwrap(),
+            );
+        }
+
+        assert_eq!(
+            1_024 * FIXED_POINT_ONE,
+            checked_fixed_powi(2 * FIXED_POINT_ONE, 10).unwrap()
+        );
+        assert_eq!(
+            976_562,
+            checked_fixed_powi(FIXED_POINT_ONE / 2, 10).unwrap()
+        );
+        assert_eq!(
+            976_563,
+            checked_fixed_ceil_powi(FIXED_POINT_ONE / 2, 10).unwrap()
+        );
+        assert_eq!(
+            u128::MAX,
+            checked_fixed_powi(2 * FIXED_POINT_ONE, 200).unwrap()
+        );
+        assert_eq!(FIXED_POINT_ONE, checked_fixed_exp(0).unwrap());
+        assert_eq!(FIXED_POINT_ONE, checked_fixed_exprel(0).unwrap());
+        assert_eq!(FIXED_POINT_ONE, checked_fixed_lnrel(0).unwrap());
+        assert_eq!(0, checked_fixed_ln(FIXED_POINT_ONE).unwrap());
+        assert_eq!(0, checked_fixed_exp_neg(FIXED_POINT_MAX_EXP + 1).unwrap());
+        assert_eq!(
+            1,
+            checked_fixed_ceil_exp_neg(FIXED_POINT_MAX_EXP + 1).unwrap()
+        );
+        assert!(checked_fixed_exp(FIXED_POINT_MAX_EXP + 1).is_err());
+        assert!(checked_fixed_ln(FIXED_POINT_ONE - 1).is_err());
+    }
+}
@@ -4,14 +4,24 @@ pub mod auction;
 pub mod bid;
 pub mod custody;
 pub mod launchpad;
+pub mod limit_book;
 pub mod multisig;
 pub mod oracle;
+pub mod price_history;
+pub mod randomness;
 pub mod seller_balance;
+pub mod vesting;
 
 use {
-    crate::{error::LaunchpadError, math, state::bid::Bid},
+    crate::{
+        error::LaunchpadError,
+        math,
+        state::{
+            auction::Auction, bid::Bid, custody::Custody, launchpad::Launchpad, oracle::OraclePrice,
+        },
+    },
     anchor_lang::{prelude::*, Discriminator},
-    anchor_spl::token::{Mint, TokenAccount},
+    anchor_spl::token_interface::{self, Mint, TokenAccount},
 };
 
 pub fn is_empty_account(account_info: &AccountInfo) -> Result<bool> {
@@ -86,23 +96,28 @@ pub fn initialize_token_account<'info>(
     authority: AccountInfo<'info>,
     seeds: &[&[&[u8]]],
 ) -> Result<()> {
+    // Token-2022 accounts are larger if the mint requires account extensions
+    let cpi_accounts = token_interface::GetAccountDataSize { mint: mint.clone() };
+    let cpi_context = anchor_lang::context::CpiContext::new(token_program.clone(), cpi_accounts);
+    let len = token_interface::get_account_data_size(cpi_context, &[])?;
+
     initialize_account(
         payer,
         token_account.clone(),
         system_program.clone(),
-        &anchor_spl::token::ID,
+        token_program.key,
         seeds,
-        TokenAccount::LEN,
+        len as usize,
     )?;
 
-    let cpi_accounts = anchor_spl::token::InitializeAccount {
+    let cpi_accounts = token_interface::InitializeAccount {
         account: token_account,
         mint,
         authority,
         rent,
     };
     let cpi_context = anchor_lang::context::CpiContext::new(token_program, cpi_accounts);
-    anchor_spl::token::initialize_account(cpi_context.with_signer(seeds))
+    token_interface::initialize_account(cpi_context.with_signer(seeds))
 }
 
 pub fn close_token_account<'info>(
@@ -112,17 +127,96 @@ pub fn close_token_account<'info>(
     authority: AccountInfo<'info>,
     seeds: &[&[&[u8]]],
 ) -> Result<()> {
-    let cpi_accounts = anchor_spl::token::CloseAccount {
+    let cpi_accounts = token_interface::CloseAccount {
         account: token_account,
         destination: receiver,
         authority,
     };
     let cpi_context = anchor_lang::context::CpiContext::new(token_program, cpi_accounts);
-    anchor_spl::token::close_account(cpi_context.with_signer(seeds))
+    token_interface::close_account(cpi_context.with_signer(seeds))
+}
+
+/// Pays out wrapped SOL from the custody to the recipient's wallet. Tokens are
+/// moved to a temporary token account at the ["unwrap", payer] address which
+/// is closed to the payer right away, then the payer forwards the unwrapped
+/// lamports, so the payer only fronts the rent of the temporary account.
+#[allow(clippy::too_many_arguments)]
+pub fn transfer_native_sol<'info>(
+    launchpad: &Launchpad,
+    from: AccountInfo<'info>,
+    temp_account: AccountInfo<'info>,
+    mint: AccountInfo<'info>,
+    transfer_authority: AccountInfo<'info>,
+    payer: AccountInfo<'info>,
+    recipient: AccountInfo<'info>,
+    token_program: AccountInfo<'info>,
+    system_program: AccountInfo<'info>,
+    amount: u64,
+    decimals: u8,
+) -> Result<()> {
+    let (temp_account_key, temp_account_bump) =
+        Pubkey::find_program_address(&[b"unwrap", payer.key.as_ref()], &crate::ID);
+    require_keys_eq!(
+        temp_account.key(),
+        temp_account_key,
+        LaunchpadError::InvalidReceivingAddress
+    );
+    let temp_account_seeds: &[&[&[u8]]] = &[&[b"unwrap", payer.key.as_ref(), &[temp_account_bump]]];
+    let authority_seeds: &[&[&[u8]]] =
+        &[&[b"transfer_authority", &[launchpad.transfer_authority_bump]]];
+
+    // create the temporary account owned by the transfer authority
+    let cpi_accounts = token_interface::GetAccountDataSize { mint: mint.clone() };
+    let cpi_context = anchor_lang::context::CpiContext::new(token_program.clone(), cpi_accounts);
+    let len = token_interface::get_account_data_size(cpi_context, &[])?;
+    initialize_account(
+        payer.clone(),
+        temp_account.clone(),
+        system_program.clone(),
+        token_program.key,
+        temp_account_seeds,
+        len as usize,
+    )?;
+    let cpi_accounts = token_interface::InitializeAccount3 {
+        account: temp_account.clone(),
+        mint: mint.clone(),
+        authority: transfer_authority.clone(),
+    };
+    let cpi_context = anchor_lang::context::CpiContext::new(token_program.clone(), cpi_accounts);
+    token_interface::initialize_account3(cpi_context)?;
+
+    // unwrap by closing the temporary account
+    launchpad.transfer_tokens(
+        from,
+        temp_account.clone(),
+        mint,
+        transfer_authority.clone(),
+        token_program.clone(),
+        amount,
+        decimals,
+    )?;
+    close_token_account(
+        payer.clone(),
+        temp_account,
+        token_program,
+        transfer_authority,
+        authority_seeds,
+    )?;
+
+    if payer.key != recipient.key {
+        let cpi_accounts = anchor_lang::system_program::Transfer {
+            from: payer,
+            to: recipient,
+        };
+        let cpi_context = anchor_lang::context::CpiContext::new(system_program, cpi_accounts);
+        anchor_lang::system_program::transfer(cpi_context, amount)?;
+    }
+
+    Ok(())
 }
 
 pub fn load_accounts<'a, T: AccountSerialize + AccountDeserialize + Owner + Clone>(
-    accounts: &[AccountInfo<'a>],
+    accounts: &'a [AccountInfo<'a>],
     expected_owner: &Pubkey,
 ) -> Result<Vec<Account<'a, T>>> {
     let mut res: Vec<Account<T>> = Vec::with_capacity(accounts.len());
@@ -141,6 +235,354 @@ pub fn load_accounts<'a, T: AccountSerialize + AccountDeserialize + Owner + Clon
     Ok(res)
 }
 
+/// Same as load_accounts() for accounts of the token interface, which can be
+/// owned by either the Token or the Token-2022 program
+pub fn load_interface_accounts<
+    'a,
+    T: AccountSerialize + AccountDeserialize + anchor_lang::Owners + Clone,
+>(
+    accounts: &'a [AccountInfo<'a>],
+) -> Result<Vec<InterfaceAccount<'a, T>>> {
+    let mut res: Vec<InterfaceAccount<T>> = Vec::with_capacity(accounts.len());
+
+    for account in accounts {
+        if !T::owners().contains(account.owner) {
+            return Err(ProgramError::IllegalOwner.into());
+        }
+        res.push(InterfaceAccount::<T>::try_from(account)?);
+    }
+
+    if res.is_empty() {
+        return Err(ProgramError::NotEnoughAccountKeys.into());
+    }
+
+    Ok(res)
+}
+
+/// Loads auction's dispensing accounts in the order of Auction::tokens
+pub fn load_dispensers<'a>(
+    accounts: &'a [AccountInfo<'a>],
+    auction: &Auction,
+) -> Result<Vec<InterfaceAccount<'a, TokenAccount>>> {
+    if accounts.len() > auction.num_tokens.into() {
+        return err!(LaunchpadError::TooManyAccountKeys);
+    }
+    if accounts.len() < auction.num_tokens.into() {
+        return Err(ProgramError::NotEnoughAccountKeys.into());
+    }
+    let dispensers = load_interface_accounts::<TokenAccount>(accounts)?;
+    for (token, dispenser) in dispensers.iter().enumerate() {
+        require_keys_eq!(
+            dispenser.key(),
+            auction.tokens[token].account,
+            LaunchpadError::InvalidDispenserAddress
+        );
+    }
+
+    Ok(dispensers)
+}
+
+/// Loads and validates user's receiving accounts, auction's dispensing accounts
+/// and their mints. All accounts need to be validated, not only the one selected
+/// to dispense, so the user can't game the process.
+#[allow(clippy::type_complexity)]
+pub fn load_dispensing_accounts<'a>(
+    accounts: &'a [AccountInfo<'a>],
+    auction: &Auction,
+    receiver: &Pubkey,
+) -> Result<(
+    Vec<InterfaceAccount<'a, TokenAccount>>,
+    Vec<InterfaceAccount<'a, TokenAccount>>,
+    Vec<InterfaceAccount<'a, Mint>>,
+)> {
+    if accounts.is_empty() || accounts.len() % 3 != 0 {
+        return Err(ProgramError::NotEnoughAccountKeys.into());
+    }
+    let accounts_third_len = accounts.len() / 3;
+    if accounts_third_len > auction.num_tokens.into() {
+        return err!(LaunchpadError::TooManyAccountKeys);
+    }
+    if accounts_third_len < auction.num_tokens.into() {
+        return Err(ProgramError::NotEnoughAccountKeys.into());
+    }
+    let receiving_accounts =
+        load_interface_accounts::<TokenAccount>(&accounts[..accounts_third_len])?;
+    let dispensing_custodies = load_interface_accounts::<TokenAccount>(
+        &accounts[accounts_third_len..accounts_third_len * 2],
+    )?;
+    let dispensing_mints = load_interface_accounts::<Mint>(&accounts[accounts_third_len * 2..])?;
+
+    for token in 0..auction.num_tokens as usize {
+        if receiving_accounts[token].owner != *receiver {
+            msg!("Invalid owner of the receiving token account");
+            return Err(ProgramError::IllegalOwner.into());
+        }
+        require_keys_eq!(
+            dispensing_custodies[token].key(),
+            auction.tokens[token].account,
+            LaunchpadError::InvalidDispenserAddress
+        );
+        require_keys_eq!(
+            dispensing_custodies[token].mint,
+            receiving_accounts[token].mint,
+            LaunchpadError::InvalidReceivingAddress
+        );
+        require_keys_eq!(
+            dispensing_custodies[token].mint,
+            dispensing_mints[token].key(),
+            LaunchpadError::InvalidDispenserAddress
+        );
+    }
+
+    Ok((receiving_accounts, dispensing_custodies, dispensing_mints))
+}
+
+/// Creates missing vesting escrow accounts of the bid. Escrows are token
+/// accounts at the ["vesting", bid, mint] addresses owned by the bid, so vested
+/// tokens are only released by claim_vested. Accounts are laid out as in
+/// load_dispensing_accounts with escrows in place of receiving accounts.
+pub fn init_vesting_accounts<'a>(
+    accounts: &'a [AccountInfo<'a>],
+    bid: AccountInfo<'a>,
+    payer: AccountInfo<'a>,
+    system_program: AccountInfo<'a>,
+    token_program: AccountInfo<'a>,
+) -> Result<()> {
+    if accounts.is_empty() || accounts.len() % 3 != 0 {
+        return Err(ProgramError::NotEnoughAccountKeys.into());
+    }
+    let accounts_third_len = accounts.len() / 3;
+    let escrows = &accounts[..accounts_third_len];
+    let mints = &accounts[accounts_third_len * 2..];
+
+    for (escrow, mint) in escrows.iter().zip(mints) {
+        let (escrow_key, escrow_bump) = Pubkey::find_program_address(
+            &[b"vesting", bid.key.as_ref(), mint.key.as_ref()],
+            &crate::ID,
+        );
+        require_keys_eq!(
+            escrow.key(),
+            escrow_key,
+            LaunchpadError::InvalidReceivingAddress
+        );
+        if !is_empty_account(escrow)? {
+            continue;
+        }
+
+        let cpi_accounts = token_interface::GetAccountDataSize { mint: mint.clone() };
+        let cpi_context =
+            anchor_lang::context::CpiContext::new(token_program.clone(), cpi_accounts);
+        let len = token_interface::get_account_data_size(cpi_context, &[])?;
+        initialize_account(
+            payer.clone(),
+            escrow.clone(),
+            system_program.clone(),
+            token_program.key,
+            &[&[
+                b"vesting",
+                bid.key.as_ref(),
+                mint.key.as_ref(),
+                &[escrow_bump],
+            ]],
+            len as usize,
+        )?;
+        let cpi_accounts = token_interface::InitializeAccount3 {
+            account: escrow.clone(),
+            mint: mint.clone(),
+            authority: bid.clone(),
+        };
+        let cpi_context =
+            anchor_lang::context::CpiContext::new(token_program.clone(), cpi_accounts);
+        token_interface::initialize_account3(cpi_context)?;
+    }
+
+    Ok(())
+}
+
+/// Loads auction's dispensing mints in the order of dispensing accounts
+pub fn load_dispensing_mints<'a>(
+    accounts: &'a [AccountInfo<'a>],
+    dispensing_custodies: &[InterfaceAccount<TokenAccount>],
+) -> Result<Vec<InterfaceAccount<'a, Mint>>> {
+    if accounts.len() != dispensing_custodies.len() {
+        return Err(ProgramError::NotEnoughAccountKeys.into());
+    }
+    let dispensing_mints = load_interface_accounts::<Mint>(accounts)?;
+    for (dispenser, mint) in dispensing_custodies.iter().zip(&dispensing_mints) {
+        require_keys_eq!(
+            dispenser.mint,
+            mint.key(),
+            LaunchpadError::InvalidDispenserAddress
+        );
+    }
+
+    Ok(dispensing_mints)
+}
+
+/// Returns pseudo-random number derived from the most recent slot hash
+pub fn get_random_seed(recent_slothashes: &AccountInfo) -> Result<u64> {
+    let slothashes_data = recent_slothashes.try_borrow_data()?;
+    if slothashes_data.len() < 20 {
+        return Err(ProgramError::InvalidAccountData.into());
+    }
+    Ok(u64::from_le_bytes(
+        slothashes_data[12..20].try_into().unwrap(),
+    ))
+}
+
+/// Checks that the current instruction is the only instruction in the transaction,
+/// so the outcome of the slot hash based selection can't be checked by other instructions
+pub fn check_single_instruction(instructions: &AccountInfo) -> Result<()> {
+    require!(
+        solana_program::sysvar::instructions::load_current_index_checked(instructions)? == 0
+            && solana_program::sysvar::instructions::load_instruction_at_checked(1, instructions)
+                .is_err(),
+        LaunchpadError::MustBeSingleInstruction
+    );
+    Ok(())
+}
+
+/// Computes the amount of payment tokens for the given number of units at
+/// the given price in pricing tokens. Also returns payment token price, which
+/// is only loaded from the oracle if custodies differ or if requested.
+#[allow(clippy::too_many_arguments)]
+pub fn get_payment_amount(
+    price: u64,
+    amount: u64,
+    pricing_custody: &Account<Custody>,
+    pricing_oracle_account: &AccountInfo,
+    payment_custody: &Account<Custody>,
+    payment_oracle_account: &AccountInfo,
+    load_payment_token_price: bool,
+    curtime: i64,
+) -> Result<(u64, OraclePrice)> {
+    let (token_pair_price, payment_token_price) = get_token_pair_price(
+        pricing_custody,
+        pricing_oracle_account,
+        payment_custody,
+        payment_oracle_account,
+        load_payment_token_price,
+        curtime,
+    )?;
+
+    Ok((
+        convert_payment_amount(
+            price,
+            amount,
+            pricing_custody,
+            payment_custody,
+            &token_pair_price,
+        )?,
+        payment_token_price,
+    ))
+}
+
+/// Loads oracle prices needed to convert pricing token amounts into payment
+/// token amounts. Returns pricing to payment token price, which is None if
+/// custodies are the same, and payment token price, which is only loaded if
+/// custodies differ or if requested.
+pub fn get_token_pair_price(
+    pricing_custody: &Account<Custody>,
+    pricing_oracle_account: &AccountInfo,
+    payment_custody: &Account<Custody>,
+    payment_oracle_account: &AccountInfo,
+    load_payment_token_price: bool,
+    curtime: i64,
+) -> Result<(Option<OraclePrice>, OraclePrice)> {
+    let same_custody = payment_custody.key() == pricing_custody.key();
+    let payment_token_price = if load_payment_token_price || !same_custody {
+        OraclePrice::new_from_oracle(
+            payment_custody.oracle_type,
+            payment_oracle_account,
+            payment_custody.max_oracle_price_error,
+            payment_custody.max_oracle_price_age_sec,
+            curtime,
+        )?
+    } else {
+        OraclePrice::new(0, 0)
+    };
+
+    if same_custody {
+        return Ok((None, payment_token_price));
+    }
+
+    let auction_token_price = OraclePrice::new_from_oracle(
+        pricing_custody.oracle_type,
+        pricing_oracle_account,
+        pricing_custody.max_oracle_price_error,
+        pricing_custody.max_oracle_price_age_sec,
+        curtime,
+    )?;
+
+    Ok((
+        Some(auction_token_price.checked_div(&payment_token_price)?),
+        payment_token_price,
+    ))
+}
+
+/// Computes the amount of payment tokens for the given number of units at
+/// the given price in pricing tokens, see get_token_pair_price()
+pub fn convert_payment_amount(
+    price: u64,
+    amount: u64,
+    pricing_custody: &Custody,
+    payment_custody: &Custody,
+    token_pair_price: &Option<OraclePrice>,
+) -> Result<u64> {
+    let price_per_token = if let Some(token_pair_price) = token_pair_price {
+        math::checked_decimal_ceil_mul(
+            price,
+            -(pricing_custody.decimals as i32),
+            token_pair_price.price,
+            token_pair_price.exponent,
+            -(payment_custody.decimals as i32),
+        )?
+    } else {
+        price
+    };
+
+    math::checked_mul(price_per_token, amount)
+}
+
+/// Records the fee collected from escrowed funds, which have already been
+/// transferred to the custody
+pub fn collect_escrowed_fee(
+    launchpad: &mut Launchpad,
+    custody: &mut Custody,
+    oracle_account: &AccountInfo,
+    fee_amount: u64,
+    invalid_bid: bool,
+    curtime: i64,
+) -> Result<()> {
+    custody.collected_fees = math::checked_add(custody.collected_fees, fee_amount)?;
+
+    let oracle_price = OraclePrice::new_from_oracle(
+        custody.oracle_type,
+        oracle_account,
+        custody.max_oracle_price_error,
+        custody.max_oracle_price_age_sec,
+        curtime,
+    )?;
+    let fees_in_usdc = math::to_token_amount(
+        oracle_price.get_asset_value_usd(fee_amount, custody.decimals)?,
+        6,
+    )?;
+
+    if invalid_bid {
+        launchpad.collected_fees.invalid_bid_usdc = launchpad
+            .collected_fees
+            .invalid_bid_usdc
+            .wrapping_add(fees_in_usdc);
+    } else {
+        launchpad.collected_fees.trade_usdc = launchpad
+            .collected_fees
+            .trade_usdc
+            .wrapping_add(fees_in_usdc);
+    }
+
+    Ok(())
+}
+
 pub fn save_accounts<T: AccountSerialize + AccountDeserialize + Owner + Clone>(
     accounts: &[Account<T>],
 ) -> Result<()> {
@@ -151,7 +593,7 @@ pub fn save_accounts<T: AccountSerialize + AccountDeserialize + Owner + Clone>(
 }
 
 pub fn create_bid_accounts<'a>(
-    accounts: &[AccountInfo<'a>],
+    accounts: &'a [AccountInfo<'a>],
     owners: &[Pubkey],
     payer: AccountInfo<'a>,
     auction: &Pubkey,
@@ -201,16 +643,16 @@ pub fn create_bid_accounts<'a>(
 
 #[allow(clippy::too_many_arguments)]
 pub fn create_token_accounts<'a>(
-    accounts: &[AccountInfo<'a>],
-    mints: &[AccountInfo<'a>],
+    accounts: &'a [AccountInfo<'a>],
+    mints: &'a [AccountInfo<'a>],
     authority: AccountInfo<'a>,
     payer: AccountInfo<'a>,
     auction: &Pubkey,
     system_program: AccountInfo<'a>,
     token_program: AccountInfo<'a>,
     rent: AccountInfo<'a>,
-) -> Result<Vec<Account<'a, TokenAccount>>> {
-    let mut res: Vec<Account<TokenAccount>> = Vec::with_capacity(accounts.len());
+) -> Result<Vec<InterfaceAccount<'a, TokenAccount>>> {
+    let mut res: Vec<InterfaceAccount<TokenAccount>> = Vec::with_capacity(accounts.len());
     let mut decimals = 0;
 
     for (token_account, mint) in accounts.iter().zip(mints) {
@@ -224,7 +666,13 @@ pub fn create_token_accounts<'a>(
             expected_token_account_key,
             LaunchpadError::InvalidDispenserAddress
         );
-        let mint_data = Account::<Mint>::try_from(mint)?;
+        // all dispensed tokens are transferred with the same token program
+        require_keys_eq!(
+            *mint.owner,
+            token_program.key(),
+            ErrorCode::AccountOwnedByWrongProgram
+        );
+        let mint_data = InterfaceAccount::<Mint>::try_from(mint)?;
         if res.is_empty() {
             decimals = mint_data.decimals;
         } else if decimals != mint_data.decimals {
@@ -242,10 +690,10 @@ pub fn create_token_accounts<'a>(
                 authority.clone(),
                 &[&[b"dispense", mint.key().as_ref(), auction.as_ref(), &[bump]]],
             )?;
-        } else if token_account.owner != &anchor_spl::token::ID {
+        } else if token_account.owner != token_program.key {
             return Err(ProgramError::IllegalOwner.into());
         }
-        res.push(Account::<TokenAccount>::try_from(token_account)?);
+        res.push(InterfaceAccount::<TokenAccount>::try_from(token_account)?);
     }
 
     Ok(res)
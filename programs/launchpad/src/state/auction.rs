@@ -1,4 +1,11 @@
-use {crate::math, anchor_lang::prelude::*};
+use {
+    crate::{
+        error::LaunchpadError,
+        math,
+        state::{randomness::RandomnessParams, vesting::VestingParams},
+    },
+    anchor_lang::prelude::*,
+};
 
 #[derive(Copy, Clone, PartialEq, AnchorSerialize, AnchorDeserialize, Default, Debug)]
 pub struct BidderStats {
@@ -9,14 +16,64 @@ pub struct BidderStats {
     pub num_trades: u64,
 }
 
+#[derive(Copy, Clone, PartialEq, AnchorSerialize, AnchorDeserialize, Default, Debug)]
+pub struct ClearingStats {
+    pub settle_time: i64,
+    pub clearing_price: u64,
+    pub supply: u64,
+    pub demand_above: u64,
+    pub demand_at: u64,
+    pub unclaimed_amount: u64,
+    // escrowed bid amounts aggregated by price level, where level n
+    // corresponds to the price of min_price + n * tick_size
+    pub demand: [u64; 32], // Auction::MAX_PRICE_LEVELS
+}
+
+#[derive(Copy, Clone, PartialEq, AnchorSerialize, AnchorDeserialize, Default, Debug)]
+pub struct LeadingBid {
+    pub bid: Pubkey,
+    pub owner: Pubkey,
+    // outbid funds are returned to this account
+    pub refund_account: Pubkey,
+    pub price: u64,
+    pub bid_time: i64,
+}
+
+#[derive(Copy, Clone, PartialEq, AnchorSerialize, AnchorDeserialize, Default, Debug)]
+pub struct EscrowStats {
+    // payments and fees in pricing tokens held until the soft cap is met
+    pub amount: u64,
+    pub fee: u64,
+}
+
 #[derive(Copy, Clone, PartialEq, AnchorSerialize, AnchorDeserialize, Default, Debug)]
 pub struct AuctionStats {
     pub first_trade_time: i64,
     pub last_trade_time: i64,
     pub last_amount: u64,
     pub last_price: u64,
+    // fill volumes of the rolling demand window split into time slots of
+    // demand_window / Auction::DEMAND_SLOTS seconds, slot n is kept in the
+    // bucket n modulo Auction::DEMAND_SLOTS, see Auction::get_demand_volume
+    pub demand_slot: i64,
+    pub demand_buckets: [u64; 10], // Auction::DEMAND_SLOTS
     pub wl_bidders: BidderStats,
     pub reg_bidders: BidderStats,
+    // value of all fills in pricing tokens with the payment custody price
+    // adjustments applied, counted towards the minimum and maximum raise
+    pub raised_amount: u128,
+    pub clearing: ClearingStats,
+    pub leading: LeadingBid,
+    pub escrow: EscrowStats,
+    // time the maximum raise was reached, the auction is ended after that
+    pub hard_cap_time: i64,
+    // anti-sniping extensions of the end times
+    pub presale_end_time_extension: i64,
+    pub end_time_extension: i64,
+    // units sold but not dispensed yet, see Bid::pending_amount
+    pub pending_amount: u64,
+    // units dispensed of every token, indexed as Auction::tokens
+    pub token_fills: [u64; 10], // Auction::MAX_TOKENS
 }
 
 #[derive(Clone, PartialEq, AnchorSerialize, AnchorDeserialize, Default, Debug)]
@@ -33,48 +90,84 @@ pub struct CommonParams {
     pub fill_limit_wl_address: u64,
     pub order_limit_reg_address: u64,
     pub order_limit_wl_address: u64,
+    pub reveal_start_time: i64,
+    pub reveal_end_time: i64,
+    // soft cap in pricing tokens, if not raised by the end time buyers
+    // can claim refunds, zero means no soft cap
+    pub min_raise: u64,
+    // hard cap in pricing tokens, the auction ends once it is reached,
+    // zero means no hard cap
+    pub max_raise: u64,
+    // fills within extension_window seconds before the end time push it out
+    // by extension_duration seconds, up to max_extension in total, zero window
+    // disables the extension
+    pub extension_window: i64,
+    pub extension_duration: i64,
+    pub max_extension: i64,
+    // time purchased tokens can be claimed in the deferred claim mode and
+    // vesting starts, zero means the end time
+    pub tge_time: i64,
+    // minimum number of units of a resting limit bid, zero means no minimum
+    pub min_limit_order_amount: u64,
+}
+
+#[derive(Copy, Clone, PartialEq, AnchorSerialize, AnchorDeserialize, Default, Debug)]
+pub struct PaymentCustody {
+    pub custody: Pubkey,
+    // price premium (positive) or discount (negative) for paying with
+    // this custody, in basis points
+    pub price_adjustment_bps: i64,
 }
 
 #[derive(Copy, Clone, PartialEq, AnchorSerialize, AnchorDeserialize, Default, Debug)]
 pub struct PaymentParams {
-    pub accept_sol: bool,
-    pub accept_usdc: bool,
-    pub accept_other_tokens: bool,
+    // custodies bids can be paid with
+    pub custodies: [PaymentCustody; 8], // Auction::MAX_PAYMENT_CUSTODIES
+    pub num_custodies: u8,
 }
 
-#[derive(Copy, Clone, PartialEq, AnchorSerialize, AnchorDeserialize, Debug)]
+#[derive(Copy, Clone, PartialEq, AnchorSerialize, AnchorDeserialize, Default, Debug)]
 pub enum PricingModel {
+    #[default]
     Fixed,
     DynamicDutchAuction,
+    BatchAuction,
+    BondingCurve,
+    EnglishAuction,
+    WeightedPool,
 }
 
-impl Default for PricingModel {
-    fn default() -> Self {
-        Self::Fixed
-    }
-}
-
-#[derive(Copy, Clone, PartialEq, AnchorSerialize, AnchorDeserialize, Debug)]
+#[derive(Copy, Clone, PartialEq, AnchorSerialize, AnchorDeserialize, Default, Debug)]
 pub enum RepriceFunction {
+    #[default]
     Linear,
     Exponential,
+    Piecewise,
+    Step,
 }
 
-impl Default for RepriceFunction {
-    fn default() -> Self {
-        Self::Linear
-    }
-}
-
-#[derive(Copy, Clone, PartialEq, AnchorSerialize, AnchorDeserialize, Debug)]
+#[derive(Copy, Clone, PartialEq, AnchorSerialize, AnchorDeserialize, Default, Debug)]
 pub enum AmountFunction {
+    #[default]
     Fixed,
+    Linear,
+    Geometric,
 }
 
-impl Default for AmountFunction {
-    fn default() -> Self {
-        Self::Fixed
-    }
+#[derive(Copy, Clone, PartialEq, AnchorSerialize, AnchorDeserialize, Default, Debug)]
+pub enum CurveFunction {
+    #[default]
+    Linear,
+    Exponential,
+    Power,
+}
+
+#[derive(Copy, Clone, PartialEq, AnchorSerialize, AnchorDeserialize, Default, Debug)]
+pub struct RepricePoint {
+    // seconds since the last trade (or auction start) plus reprice delay
+    pub time_offset: i64,
+    // fraction of the last trade price, fixed-point, see math::FIXED_POINT_ONE
+    pub price_ratio: u64,
 }
 
 #[derive(Copy, Clone, PartialEq, AnchorSerialize, AnchorDeserialize, Default, Debug)]
@@ -85,9 +178,41 @@ pub struct PricingParams {
     pub max_price: u64,
     pub min_price: u64,
     pub reprice_delay: i64,
-    pub reprice_coef: f64,
+    // fixed-point, see math::FIXED_POINT_ONE
+    pub reprice_coef: u64,
     pub reprice_function: RepriceFunction,
+    // Step function drops the price by tick_size every reprice_interval seconds
+    pub reprice_interval: i64,
+    // Piecewise function interpolates the price linearly between the points,
+    // starting from the last trade price at zero offset
+    pub reprice_points: [RepricePoint; 8], // Auction::MAX_REPRICE_POINTS
+    pub num_reprice_points: u8,
+    // Dutch auction best offer steps up by demand_step whenever the fill
+    // volume within demand_window seconds reaches demand_target units,
+    // zero demand_target disables upward repricing
+    pub demand_window: i64,
+    pub demand_target: u64,
+    pub demand_step: u64,
     pub amount_function: AmountFunction,
+    // Linear function changes the amount offered at every next price level by
+    // amount_coef units, Geometric function multiplies it by amount_coef
+    // (fixed-point, see math::FIXED_POINT_ONE)
+    pub amount_coef: i64,
+    // bonding curve price is start_price * f(x), where x is the number of
+    // units sold divided by amount_per_level, and f(x) is one of:
+    //   Linear: 1 + curve_coef * x
+    //   Exponential: e^(curve_coef * x)
+    //   Power: (1 + x)^curve_coef
+    pub curve_function: CurveFunction,
+    // fixed-point, see math::FIXED_POINT_ONE
+    pub curve_coef: u64,
+    // weighted pool token weight shifts linearly from start_weight to end_weight
+    // over the auction, payment weight is one minus token weight (fixed-point)
+    pub start_weight: u64,
+    pub end_weight: u64,
+    // virtual token reserve of the weighted pool in units, the payment reserve
+    // is derived from start_price and start_weight and grows with every fill
+    pub pool_reserve: u64,
     pub amount_per_level: u64,
     pub tick_size: u64,
     pub unit_size: u64,
@@ -105,6 +230,8 @@ pub struct AuctionToken {
     // likely than first).
     pub ratio: u64,
     pub account: Pubkey,
+    // amount of the token in every unit of the basket auction, see Auction::basket
+    pub basket_amount: u64,
 }
 
 #[account]
@@ -115,6 +242,14 @@ pub struct Auction {
     pub enabled: bool,
     pub updatable: bool,
     pub fixed_amount: bool,
+    // every unit is a basket of all offered tokens instead of a random one
+    pub basket: bool,
+    // bids only record purchased units, tokens are claimed with claim_tokens
+    // after the claim time, see Auction::get_claim_time()
+    pub deferred_claim: bool,
+    // fills the selected dispenser can't cover are routed to other dispensers
+    // proportionally to their ratios instead of being cut short
+    pub split_fills: bool,
 
     pub common: CommonParams,
     pub payment: PaymentParams,
@@ -122,6 +257,8 @@ pub struct Auction {
     pub stats: AuctionStats,
     pub tokens: [AuctionToken; 10], // Auction::MAX_TOKENS
     pub num_tokens: u8,
+    pub randomness: RandomnessParams,
+    pub vesting: VestingParams,
 
     // time of creation, also used as current wall clock time for testing
     pub creation_time: i64,
@@ -140,12 +277,87 @@ impl CommonParams {
                     && self.presale_end_time > curtime
                     && ((self.end_time == 0 && self.start_time == 0)
                         || self.presale_end_time <= self.start_time)))
+            && ((self.reveal_end_time == 0 && self.reveal_start_time == 0)
+                || (self.reveal_end_time > self.reveal_start_time
+                    && self.reveal_start_time >= self.end_time
+                    && self.reveal_start_time >= self.presale_end_time))
+            && (self.tge_time == 0
+                || (self.tge_time >= self.end_time && self.tge_time >= self.presale_end_time))
+            && ((self.extension_window == 0
+                && self.extension_duration == 0
+                && self.max_extension == 0)
+                || (self.extension_window > 0
+                    && self.extension_duration > 0
+                    && self.max_extension > 0))
     }
 }
 
 impl PaymentParams {
+    pub const BPS_DENOMINATOR: i64 = 10000;
+
     pub fn validate(&self) -> bool {
-        self.accept_sol || self.accept_usdc || self.accept_other_tokens
+        if self.num_custodies == 0 || self.num_custodies as usize > Auction::MAX_PAYMENT_CUSTODIES {
+            return false;
+        }
+        let custodies = &self.custodies[..self.num_custodies as usize];
+        custodies.iter().enumerate().all(|(i, payment)| {
+            payment.custody != Pubkey::default()
+                && payment.price_adjustment_bps > -PaymentParams::BPS_DENOMINATOR
+                && payment.price_adjustment_bps <= PaymentParams::BPS_DENOMINATOR
+                && custodies[..i]
+                    .iter()
+                    .all(|other| other.custody != payment.custody)
+        })
+    }
+
+    /// Checks if bids can be paid with the custody
+    pub fn is_accepted(&self, custody: &Pubkey) -> bool {
+        self.get_payment_custody(custody).is_some()
+    }
+
+    /// Applies the custody price premium or discount to the payment amount,
+    /// fails if the custody is not accepted
+    pub fn adjust_payment_amount(&self, custody: &Pubkey, amount: u64) -> Result<u64> {
+        math::checked_as_u64(PaymentParams::adjust_value(
+            amount as u128,
+            self.get_price_adjustment_bps(custody)?,
+        )?)
+    }
+
+    /// Returns the price adjustment of the custody in basis points,
+    /// fails if the custody is not accepted
+    pub fn get_price_adjustment_bps(&self, custody: &Pubkey) -> Result<i64> {
+        Ok(self
+            .get_payment_custody(custody)
+            .ok_or(LaunchpadError::InvalidPaymentCustody)?
+            .price_adjustment_bps)
+    }
+
+    /// Returns the lowest price adjustment across accepted custodies
+    pub fn get_min_price_adjustment_bps(&self) -> i64 {
+        self.custodies[..std::cmp::min(self.num_custodies as usize, Auction::MAX_PAYMENT_CUSTODIES)]
+            .iter()
+            .map(|payment| payment.price_adjustment_bps)
+            .min()
+            .unwrap_or(0)
+    }
+
+    /// Applies the price adjustment in basis points to the value
+    pub fn adjust_value(value: u128, price_adjustment_bps: i64) -> Result<u128> {
+        if price_adjustment_bps == 0 {
+            return Ok(value);
+        }
+        let factor = math::checked_add(PaymentParams::BPS_DENOMINATOR, price_adjustment_bps)?;
+        math::checked_div(
+            math::checked_mul(value, factor as u128)?,
+            PaymentParams::BPS_DENOMINATOR as u128,
+        )
+    }
+
+    fn get_payment_custody(&self, custody: &Pubkey) -> Option<&PaymentCustody> {
+        self.custodies[..std::cmp::min(self.num_custodies as usize, Auction::MAX_PAYMENT_CUSTODIES)]
+            .iter()
+            .find(|payment| payment.custody == *custody)
     }
 }
 
@@ -159,21 +371,119 @@ impl PricingParams {
                 && self.max_price >= self.min_price
                 && self.start_price >= self.min_price))
             && self.reprice_delay >= 0
-            && (self.pricing_model == PricingModel::Fixed
-                || (self.amount_per_level > 0 && self.tick_size > 0))
+            && (self.demand_target == 0 || (self.demand_window > 0 && self.demand_step > 0))
+            && self.validate_reprice_function()
+            && self.validate_amount_function()
+            && match self.pricing_model {
+                PricingModel::Fixed => true,
+                PricingModel::DynamicDutchAuction => {
+                    self.amount_per_level > 0 && self.tick_size > 0
+                }
+                PricingModel::BatchAuction => {
+                    self.tick_size > 0
+                        && (self.max_price - self.min_price) / self.tick_size
+                            < Auction::MAX_PRICE_LEVELS as u64
+                }
+                PricingModel::BondingCurve => self.amount_per_level > 0 && self.start_price > 0,
+                PricingModel::EnglishAuction => self.tick_size > 0,
+                PricingModel::WeightedPool => {
+                    self.start_price > 0
+                        && self.pool_reserve > 0
+                        && self.start_weight > 0
+                        && (self.start_weight as u128) < math::FIXED_POINT_ONE
+                        && self.end_weight > 0
+                        && (self.end_weight as u128) < math::FIXED_POINT_ONE
+                }
+            }
             && self.unit_size > 0
     }
 }
 
+impl PricingParams {
+    fn validate_reprice_function(&self) -> bool {
+        if self.num_reprice_points as usize > Auction::MAX_REPRICE_POINTS {
+            return false;
+        }
+        let points = &self.reprice_points[..self.num_reprice_points as usize];
+        match self.reprice_function {
+            RepriceFunction::Linear | RepriceFunction::Exponential => true,
+            RepriceFunction::Piecewise => {
+                !points.is_empty()
+                    && points[0].time_offset > 0
+                    && points
+                        .windows(2)
+                        .all(|pair| pair[0].time_offset < pair[1].time_offset)
+            }
+            RepriceFunction::Step => self.reprice_interval > 0,
+        }
+    }
+
+    fn validate_amount_function(&self) -> bool {
+        match self.amount_function {
+            AmountFunction::Fixed | AmountFunction::Linear => true,
+            AmountFunction::Geometric => self.amount_coef > 0,
+        }
+    }
+}
+
 impl Auction {
     pub const LEN: usize = 8 + std::mem::size_of::<Auction>();
     pub const MAX_TOKENS: usize = 10;
+    pub const MAX_PRICE_LEVELS: usize = 32;
+    pub const MAX_REPRICE_POINTS: usize = 8;
+    pub const MAX_PAYMENT_CUSTODIES: usize = 8;
+    pub const DEMAND_SLOTS: usize = 10;
 
     pub fn validate(&self) -> Result<bool> {
         Ok(self.common.name.len() >= 6
             && self.common.validate(self.get_time()?)
             && self.payment.validate()
-            && self.pricing.validate())
+            && self.pricing.validate()
+            // bids are revealed after the sale, other models would fill all of them
+            // at the final price in the reveal order, so only batch auctions are sealed
+            && (!self.is_sealed() || self.pricing.pricing_model == PricingModel::BatchAuction)
+            && (self.pricing.pricing_model != PricingModel::EnglishAuction || self.num_tokens == 1)
+            && (!self.has_soft_cap()
+                || (self.pricing.pricing_model != PricingModel::BatchAuction
+                    && self.pricing.pricing_model != PricingModel::EnglishAuction
+                    && self.num_tokens == 1))
+            && (!self.has_hard_cap()
+                || (self.pricing.pricing_model != PricingModel::BatchAuction
+                    && self.pricing.pricing_model != PricingModel::EnglishAuction
+                    && self.common.max_raise >= self.common.min_raise))
+            && (self.common.extension_window == 0
+                || (self.pricing.pricing_model != PricingModel::BatchAuction
+                    && self.pricing.pricing_model != PricingModel::EnglishAuction))
+            && self.randomness.validate()
+            && (!self.basket
+                || (self.pricing.pricing_model != PricingModel::BatchAuction
+                    && self.pricing.pricing_model != PricingModel::EnglishAuction
+                    && !self.randomness.is_deferred()
+                    && self.num_tokens > 1
+                    && self.tokens[..self.num_tokens as usize]
+                        .iter()
+                        .all(|token| token.basket_amount > 0)))
+            && (!self.randomness.is_deferred()
+                || (self.pricing.pricing_model != PricingModel::BatchAuction
+                    && self.pricing.pricing_model != PricingModel::EnglishAuction))
+            && self.vesting.validate()
+            && (!self.vesting.is_enabled()
+                || (self.pricing.pricing_model != PricingModel::BatchAuction
+                    && self.pricing.pricing_model != PricingModel::EnglishAuction
+                    && !self.randomness.is_deferred()
+                    && !self.has_soft_cap()))
+            && (!self.deferred_claim
+                || (self.pricing.pricing_model != PricingModel::BatchAuction
+                    && self.pricing.pricing_model != PricingModel::EnglishAuction
+                    && !self.randomness.is_deferred()
+                    && !self.vesting.is_enabled()
+                    && !self.has_soft_cap()))
+            && (!self.split_fills
+                || (self.pricing.pricing_model != PricingModel::BatchAuction
+                    && self.pricing.pricing_model != PricingModel::EnglishAuction
+                    && !self.randomness.is_deferred()
+                    && !self.basket
+                    && !self.deferred_claim)))
     }
 
     /// checks if auction has started
@@ -184,7 +494,122 @@ impl Auction {
 
     /// Checks if the auction is ended
     pub fn is_ended(&self, curtime: i64, whitelisted: bool) -> bool {
-        curtime >= self.get_end_time(whitelisted)
+        curtime >= self.get_end_time(whitelisted) || self.is_hard_cap_met()
+    }
+
+    /// Checks if the auction accepts sealed bids only
+    pub fn is_sealed(&self) -> bool {
+        self.common.reveal_end_time > 0
+    }
+
+    /// Checks if sealed bids can be revealed
+    pub fn is_reveal_period(&self, curtime: i64) -> bool {
+        self.is_sealed()
+            && curtime >= self.common.reveal_start_time
+            && curtime < self.common.reveal_end_time
+    }
+
+    /// Checks if the auction can be settled
+    pub fn is_settle_period(&self, curtime: i64) -> bool {
+        self.is_ended(curtime, true) && curtime >= self.common.reveal_end_time
+    }
+
+    /// Checks if the clearing price has been computed
+    pub fn is_settled(&self) -> bool {
+        self.stats.clearing.settle_time > 0
+    }
+
+    /// Checks if the auction has a minimum raise
+    pub fn has_soft_cap(&self) -> bool {
+        self.common.min_raise > 0
+    }
+
+    /// Checks if the minimum raise has been reached, always true without the soft cap
+    pub fn is_soft_cap_met(&self) -> bool {
+        self.get_raised_amount() >= self.common.min_raise as u128
+    }
+
+    /// Checks if the auction has ended without reaching the minimum raise
+    pub fn is_soft_cap_failed(&self, curtime: i64) -> bool {
+        self.is_ended(curtime, true) && !self.is_soft_cap_met()
+    }
+
+    /// Checks if the auction has a maximum raise
+    pub fn has_hard_cap(&self) -> bool {
+        self.common.max_raise > 0
+    }
+
+    /// Checks if the auction has been ended by reaching the maximum raise
+    pub fn is_hard_cap_met(&self) -> bool {
+        self.stats.hard_cap_time > 0
+    }
+
+    /// Returns the number of units, up to the given amount, that can be sold
+    /// without exceeding the maximum raise when paid with the given price adjustment
+    pub fn get_hard_cap_amount(
+        &self,
+        amount: u64,
+        curtime: i64,
+        price_adjustment_bps: i64,
+    ) -> Result<u64> {
+        if !self.has_hard_cap() || amount == 0 {
+            return Ok(amount);
+        }
+        let raised = self.get_raised_amount();
+        if raised >= self.common.max_raise as u128 {
+            return Ok(0);
+        }
+        let remaining = self.common.max_raise as u128 - raised;
+        let fits = |amount: u64| -> Result<bool> {
+            Ok(PaymentParams::adjust_value(
+                math::checked_mul(
+                    amount as u128,
+                    self.get_auction_price(amount, curtime)? as u128,
+                )?,
+                price_adjustment_bps,
+            )? <= remaining)
+        };
+        if fits(amount)? {
+            return Ok(amount);
+        }
+
+        // find the largest amount that fits the remaining raise
+        let mut low = 0u64;
+        let mut high = amount - 1;
+        while low < high {
+            let mid = low + (high - low) / 2 + 1;
+            if fits(mid)? {
+                low = mid;
+            } else {
+                high = mid - 1;
+            }
+        }
+
+        Ok(low)
+    }
+
+    /// Returns the total value of fills in pricing tokens, adjusted by the
+    /// price premium or discount of the custodies they were paid with
+    pub fn get_raised_amount(&self) -> u128 {
+        self.stats.raised_amount
+    }
+
+    /// Checks if the English auction has received a bid
+    pub fn has_leading_bid(&self) -> bool {
+        self.stats.leading.bid != Pubkey::default()
+    }
+
+    /// Checks if limit bids can rest on the auction until filled by crank_fills,
+    /// resting bids are dispensed like immediate fills with a single token
+    pub fn accepts_limit_bids(&self) -> bool {
+        self.pricing.pricing_model != PricingModel::BatchAuction
+            && self.pricing.pricing_model != PricingModel::EnglishAuction
+            && !self.has_soft_cap()
+            && !self.randomness.is_deferred()
+            && !self.basket
+            && !self.vesting.is_enabled()
+            && !self.deferred_claim
+            && !self.split_fills
     }
 
     #[cfg(feature = "test")]
@@ -214,18 +639,87 @@ impl Auction {
         }
     }
 
+    /// Returns the end time including anti-sniping extensions
     pub fn get_end_time(&self, whitelisted: bool) -> i64 {
+        let (presale_end_time, end_time) = self.get_extended_end_times();
         if whitelisted {
-            std::cmp::max(self.common.presale_end_time, self.common.end_time)
+            std::cmp::max(presale_end_time, end_time)
+        } else {
+            end_time
+        }
+    }
+
+    /// Returns the time deferred claims are released and vesting starts
+    pub fn get_claim_time(&self) -> i64 {
+        std::cmp::max(self.common.tge_time, self.get_end_time(true))
+    }
+
+    /// Returns presale and regular end times including anti-sniping extensions
+    fn get_extended_end_times(&self) -> (i64, i64) {
+        let extend = |end_time: i64, extension: i64| {
+            if end_time > 0 {
+                end_time.saturating_add(extension)
+            } else {
+                end_time
+            }
+        };
+        (
+            extend(
+                self.common.presale_end_time,
+                self.stats.presale_end_time_extension,
+            ),
+            extend(self.common.end_time, self.stats.end_time_extension),
+        )
+    }
+
+    /// Pushes the end time of the current sale period out if the fill happened
+    /// within the anti-sniping window
+    fn update_end_time_extension(&mut self, curtime: i64) -> Result<()> {
+        let (presale_end_time, end_time) = self.get_extended_end_times();
+        let (end_time, extension, max_extension) = if curtime < presale_end_time {
+            // the presale can't be extended into the public sale
+            let max_extension = if self.common.start_time > 0 {
+                std::cmp::min(
+                    self.common.max_extension,
+                    self.common
+                        .start_time
+                        .saturating_sub(self.common.presale_end_time)
+                        .max(0),
+                )
+            } else {
+                self.common.max_extension
+            };
+            (
+                presale_end_time,
+                &mut self.stats.presale_end_time_extension,
+                max_extension,
+            )
+        } else if curtime < end_time {
+            (
+                end_time,
+                &mut self.stats.end_time_extension,
+                self.common.max_extension,
+            )
         } else {
-            self.common.end_time
+            return Ok(());
+        };
+        if curtime >= math::checked_sub(end_time, self.common.extension_window)? {
+            *extension = std::cmp::min(
+                math::checked_add(*extension, self.common.extension_duration)?,
+                max_extension,
+            );
         }
+        Ok(())
     }
 
     pub fn get_auction_amount(&self, price: u64, curtime: i64) -> Result<u64> {
         match self.pricing.pricing_model {
             PricingModel::Fixed => self.get_auction_amount_fixed(),
             PricingModel::DynamicDutchAuction => self.get_auction_amount_dda(price, curtime),
+            PricingModel::BatchAuction => self.get_auction_amount_batch(price),
+            PricingModel::BondingCurve => self.get_auction_amount_curve(price),
+            PricingModel::EnglishAuction => self.get_auction_amount_english(price),
+            PricingModel::WeightedPool => self.get_auction_amount_pool(price, curtime),
         }
     }
 
@@ -233,6 +727,104 @@ impl Auction {
         match self.pricing.pricing_model {
             PricingModel::Fixed => self.get_auction_price_fixed(),
             PricingModel::DynamicDutchAuction => self.get_auction_price_dda(amount, curtime),
+            PricingModel::BatchAuction => self.get_auction_price_batch(),
+            PricingModel::BondingCurve => self.get_auction_price_curve(amount),
+            PricingModel::EnglishAuction => self.get_auction_price_english(),
+            PricingModel::WeightedPool => self.get_auction_price_pool(amount, curtime),
+        }
+    }
+
+    /// Returns the price level for the given price rounded down to the tick size
+    pub fn get_price_level(&self, price: u64) -> Result<usize> {
+        if price < self.pricing.min_price || price > self.pricing.max_price {
+            return err!(LaunchpadError::InvalidBidPrice);
+        }
+        let level = math::checked_div(
+            math::checked_sub(price, self.pricing.min_price)?,
+            self.pricing.tick_size,
+        )?;
+        if level >= Auction::MAX_PRICE_LEVELS as u64 {
+            return err!(LaunchpadError::InvalidBidPrice);
+        }
+        Ok(level as usize)
+    }
+
+    pub fn get_level_price(&self, level: usize) -> Result<u64> {
+        math::checked_add(
+            self.pricing.min_price,
+            math::checked_mul(level as u64, self.pricing.tick_size)?,
+        )
+    }
+
+    /// Records escrowed bid amount at the given price level
+    pub fn add_demand(&mut self, level: usize, amount: u64) -> Result<()> {
+        self.stats.clearing.demand[level] =
+            math::checked_add(self.stats.clearing.demand[level], amount)?;
+        Ok(())
+    }
+
+    /// Computes uniform clearing price for the batch auction given the amount
+    /// of units available for sale. Clearing price is the highest price level
+    /// at which cumulative demand meets the supply. If auction is undersubscribed
+    /// all bids are filled at min_price.
+    pub fn settle(&mut self, supply: u64, curtime: i64) -> Result<()> {
+        let mut demand_above = 0u64;
+        let mut clearing_level = None;
+
+        for level in (0..Auction::MAX_PRICE_LEVELS).rev() {
+            let demand_at = self.stats.clearing.demand[level];
+            if demand_at == 0 {
+                continue;
+            }
+            if math::checked_add(demand_above, demand_at)? >= supply {
+                clearing_level = Some(level);
+                break;
+            }
+            demand_above = math::checked_add(demand_above, demand_at)?;
+        }
+
+        let clearing_price = if let Some(level) = clearing_level {
+            self.get_level_price(level)?
+        } else {
+            self.pricing.min_price
+        };
+
+        let clearing = &mut self.stats.clearing;
+        clearing.settle_time = curtime;
+        clearing.clearing_price = clearing_price;
+        clearing.supply = supply;
+        if let Some(level) = clearing_level {
+            clearing.demand_above = demand_above;
+            clearing.demand_at = clearing.demand[level];
+            clearing.unclaimed_amount = supply;
+        } else {
+            // undersubscribed, everybody gets filled at the reserve price
+            clearing.demand_above = 0;
+            clearing.demand_at = demand_above;
+            clearing.unclaimed_amount = demand_above;
+        }
+
+        Ok(())
+    }
+
+    /// Returns amount filled at the clearing price for the given bid.
+    /// Bids at the clearing price are filled pro-rata.
+    pub fn get_clearing_fill_amount(&self, bid_price: u64, bid_amount: u64) -> Result<u64> {
+        let clearing = &self.stats.clearing;
+        if bid_price < clearing.clearing_price {
+            Ok(0)
+        } else if bid_price > clearing.clearing_price
+            || math::checked_add(clearing.demand_above, clearing.demand_at)? <= clearing.supply
+        {
+            Ok(bid_amount)
+        } else {
+            math::checked_as_u64(math::checked_div(
+                math::checked_mul(
+                    bid_amount as u128,
+                    math::checked_sub(clearing.supply, clearing.demand_above)? as u128,
+                )?,
+                clearing.demand_at as u128,
+            )?)
         }
     }
 
@@ -244,6 +836,320 @@ impl Auction {
         Ok(self.pricing.start_price)
     }
 
+    fn get_auction_amount_batch(&self, price: u64) -> Result<u64> {
+        if price < self.pricing.min_price {
+            Ok(0)
+        } else {
+            Ok(u64::MAX)
+        }
+    }
+
+    fn get_auction_price_batch(&self) -> Result<u64> {
+        if self.is_settled() {
+            Ok(self.stats.clearing.clearing_price)
+        } else {
+            Ok(self.pricing.min_price)
+        }
+    }
+
+    fn get_auction_amount_english(&self, price: u64) -> Result<u64> {
+        if price < self.get_auction_price_english()? {
+            Ok(0)
+        } else {
+            Ok(1)
+        }
+    }
+
+    /// Returns the minimum price the next English auction bid must offer,
+    /// i.e. the reserve price or the leading bid plus the minimum increment
+    fn get_auction_price_english(&self) -> Result<u64> {
+        if self.has_leading_bid() {
+            math::checked_add(self.stats.leading.price, self.pricing.tick_size)
+        } else {
+            Ok(self.pricing.start_price)
+        }
+    }
+
+    fn get_auction_amount_curve(&self, price: u64) -> Result<u64> {
+        let units_sold = self.get_units_sold()?;
+
+        // sale halts once the marginal price reaches max_price
+        let max_amount = self.get_curve_max_amount(units_sold)?;
+        if max_amount == 0 || price < self.get_curve_average_price(units_sold, 1)? {
+            return Ok(0);
+        }
+        if self.pricing.curve_coef == 0 {
+            return Ok(max_amount);
+        }
+
+        // find the largest amount with average price not exceeding user's price
+        let mut low = 1u64;
+        let mut high = max_amount;
+        while low < high {
+            let mid = low + (high - low) / 2 + 1;
+            if self.get_curve_average_price(units_sold, mid)? <= price {
+                low = mid;
+            } else {
+                high = mid - 1;
+            }
+        }
+
+        Ok(low)
+    }
+
+    fn get_auction_price_curve(&self, amount: u64) -> Result<u64> {
+        if amount == 0 {
+            return Ok(0);
+        }
+        self.get_curve_average_price(self.get_units_sold()?, amount)
+    }
+
+    fn get_units_sold(&self) -> Result<u64> {
+        math::checked_add(
+            self.stats.wl_bidders.fills_volume,
+            self.stats.reg_bidders.fills_volume,
+        )
+    }
+
+    /// Returns the average bonding curve price over the purchase of the given
+    /// amount of units, i.e. the integral of the curve divided by the amount.
+    /// Every intermediate value is rounded up, so the price is never below the
+    /// exact one.
+    fn get_curve_average_price(&self, units_sold: u64, amount: u64) -> Result<u64> {
+        let one = math::FIXED_POINT_ONE;
+        let coef = self.pricing.curve_coef as u128;
+        let scale = self.pricing.amount_per_level as u128;
+
+        let multiplier = match self.pricing.curve_function {
+            CurveFunction::Linear => {
+                // 1 + coef * (from + to) / 2
+                let midpoint = math::checked_ceil_div(
+                    math::checked_mul(
+                        math::checked_add(
+                            math::checked_mul(units_sold as u128, 2)?,
+                            amount as u128,
+                        )?,
+                        one,
+                    )?,
+                    math::checked_mul(scale, 2)?,
+                )?;
+                math::checked_add(one, math::checked_fixed_ceil_mul(coef, midpoint)?)?
+            }
+            CurveFunction::Exponential => {
+                // e^(coef * from) * (e^(coef * width) - 1) / (coef * width)
+                let from = math::checked_fixed_ceil_div(units_sold as u128, scale)?;
+                let width = math::checked_fixed_ceil_div(amount as u128, scale)?;
+                math::checked_fixed_ceil_mul(
+                    math::checked_fixed_ceil_exp(math::checked_fixed_ceil_mul(coef, from)?)?,
+                    math::checked_fixed_ceil_exprel(math::checked_fixed_ceil_mul(coef, width)?)?,
+                )?
+            }
+            CurveFunction::Power => {
+                // ((1 + to)^(coef + 1) - (1 + from)^(coef + 1)) / ((coef + 1) * width),
+                // rewritten as (1 + from)^coef * (e^u - 1) / u * ln(1 + z) / z,
+                // where z = width / (1 + from) and u = (coef + 1) * ln(1 + z)
+                let base_units = math::checked_add(scale, units_sold as u128)?;
+                let base = math::checked_fixed_ceil_div(base_units, scale)?;
+                let growth = math::checked_fixed_ceil_exp(math::checked_fixed_ceil_mul(
+                    coef,
+                    math::checked_fixed_ceil_ln(base)?,
+                )?)?;
+                let z_ceil = math::checked_fixed_ceil_div(amount as u128, base_units)?;
+                let z_floor = math::checked_fixed_div(amount as u128, base_units)?;
+                let u = math::checked_fixed_ceil_mul(
+                    math::checked_add(coef, one)?,
+                    math::checked_fixed_ceil_ln(math::checked_add(one, z_ceil)?)?,
+                )?;
+                math::checked_fixed_ceil_mul(
+                    math::checked_fixed_ceil_mul(growth, math::checked_fixed_ceil_exprel(u)?)?,
+                    math::checked_fixed_ceil_lnrel(z_floor)?,
+                )?
+            }
+        };
+
+        math::checked_as_u64(math::checked_fixed_ceil_mul(
+            self.pricing.start_price as u128,
+            multiplier,
+        )?)
+    }
+
+    /// Returns the number of units that can be sold before the marginal
+    /// bonding curve price exceeds max_price, rounded down
+    fn get_curve_max_amount(&self, units_sold: u64) -> Result<u64> {
+        let coef = self.pricing.curve_coef as u128;
+        if coef == 0 {
+            return Ok(u64::MAX);
+        }
+        let ratio = math::checked_fixed_div(
+            self.pricing.max_price as u128,
+            self.pricing.start_price as u128,
+        )?;
+        let max_x = match self.pricing.curve_function {
+            CurveFunction::Linear => {
+                math::checked_fixed_div(math::checked_sub(ratio, math::FIXED_POINT_ONE)?, coef)?
+            }
+            CurveFunction::Exponential => {
+                math::checked_fixed_div(math::checked_fixed_ln(ratio)?, coef)?
+            }
+            CurveFunction::Power => {
+                let power = math::checked_fixed_div(math::checked_fixed_ln(ratio)?, coef)?;
+                if power > math::FIXED_POINT_MAX_EXP {
+                    return Ok(u64::MAX);
+                }
+                math::checked_sub(math::checked_fixed_exp(power)?, math::FIXED_POINT_ONE)?
+            }
+        };
+        let max_units = u64::try_from(math::checked_fixed_mul(
+            max_x,
+            self.pricing.amount_per_level as u128,
+        )?)
+        .unwrap_or(u64::MAX);
+
+        Ok(max_units.saturating_sub(units_sold))
+    }
+
+    fn get_auction_amount_pool(&self, price: u64, curtime: i64) -> Result<u64> {
+        // one unit always stays in the pool
+        let token_reserve = self
+            .pricing
+            .pool_reserve
+            .saturating_sub(self.get_units_sold()?);
+        if token_reserve <= 1 {
+            return Ok(0);
+        }
+        let max_amount = token_reserve - 1;
+
+        // sale halts once the average price reaches max_price
+        let price = std::cmp::min(price, self.pricing.max_price);
+        if price < self.get_pool_average_price(1, curtime)? {
+            return Ok(0);
+        }
+
+        // find the largest amount with average price not exceeding user's price
+        let mut low = 1u64;
+        let mut high = max_amount;
+        while low < high {
+            let mid = low + (high - low) / 2 + 1;
+            if self.get_pool_average_price(mid, curtime)? <= price {
+                low = mid;
+            } else {
+                high = mid - 1;
+            }
+        }
+
+        Ok(low)
+    }
+
+    fn get_auction_price_pool(&self, amount: u64, curtime: i64) -> Result<u64> {
+        if amount == 0 {
+            return Ok(0);
+        }
+        self.get_pool_average_price(amount, curtime)
+    }
+
+    /// Returns the weighted pool token weight at the given time
+    fn get_pool_weight(&self, curtime: i64) -> Result<u128> {
+        let start_weight = self.pricing.start_weight as u128;
+        let end_weight = self.pricing.end_weight as u128;
+        let start_time = self.get_start_time(true);
+        let end_time = self.get_end_time(true);
+        if start_time == 0 || curtime <= start_time {
+            return Ok(start_weight);
+        }
+        if curtime >= end_time {
+            return Ok(end_weight);
+        }
+
+        // rounded towards the higher token weight, i.e. the higher price
+        let progress = math::checked_sub(curtime, start_time)? as u128;
+        let span = math::checked_sub(end_time, start_time)? as u128;
+        if end_weight >= start_weight {
+            math::checked_add(
+                start_weight,
+                math::checked_ceil_div(
+                    math::checked_mul(end_weight - start_weight, progress)?,
+                    span,
+                )?,
+            )
+        } else {
+            math::checked_sub(
+                start_weight,
+                math::checked_div(
+                    math::checked_mul(start_weight - end_weight, progress)?,
+                    span,
+                )?,
+            )
+        }
+    }
+
+    /// Returns the average weighted pool price over the purchase of the given
+    /// amount of units, floored at min_price and rounded up. With token reserve
+    /// A, payment reserve P, and weight ratio r = w / (1 - w), buying x units
+    /// costs P * ((A / (A - x))^r - 1), rewritten to avoid cancellation as
+    /// P / (A - x) * r * ln(1 + z) / z * (e^u - 1) / u,
+    /// where z = x / (A - x) and u = r * ln(1 + z).
+    /// Saturates at u64::MAX if the pool can't cover the amount.
+    fn get_pool_average_price(&self, amount: u64, curtime: i64) -> Result<u64> {
+        let one = math::FIXED_POINT_ONE;
+        let token_reserve = self
+            .pricing
+            .pool_reserve
+            .saturating_sub(self.get_units_sold()?);
+        if amount >= token_reserve {
+            return Ok(u64::MAX);
+        }
+
+        // payment reserve sets the initial spot price to start_price, every fill
+        // adds its payment to the reserve
+        let start_weight = self.pricing.start_weight as u128;
+        let initial_payment_reserve = math::checked_ceil_div(
+            math::checked_mul(
+                math::checked_mul(
+                    self.pricing.start_price as u128,
+                    self.pricing.pool_reserve as u128,
+                )?,
+                one - start_weight,
+            )?,
+            start_weight,
+        )?;
+        let payment_reserve = math::checked_add(
+            initial_payment_reserve,
+            math::checked_add(
+                self.stats.wl_bidders.weighted_fills_sum,
+                self.stats.reg_bidders.weighted_fills_sum,
+            )?,
+        )?;
+
+        let weight = self.get_pool_weight(curtime)?;
+        let ratio = math::checked_fixed_ceil_div(weight, one - weight)?;
+        let remaining = (token_reserve - amount) as u128;
+        let z_ceil = math::checked_fixed_ceil_div(amount as u128, remaining)?;
+        let z_floor = math::checked_fixed_div(amount as u128, remaining)?;
+        let u = math::checked_fixed_ceil_mul(
+            ratio,
+            math::checked_fixed_ceil_ln(math::checked_add(one, z_ceil)?)?,
+        )?;
+        if u > math::FIXED_POINT_MAX_EXP {
+            return Ok(u64::MAX);
+        }
+
+        let spot_price = math::checked_fixed_ceil_mul(
+            math::checked_fixed_ceil_div(payment_reserve, remaining)?,
+            ratio,
+        )?;
+        let base_price =
+            math::checked_fixed_ceil_mul(spot_price, math::checked_fixed_ceil_lnrel(z_floor)?)?;
+        let price = match base_price.checked_mul(math::checked_fixed_ceil_exprel(u)?) {
+            Some(scaled) => math::checked_ceil_div(scaled, one * one)?,
+            None => return Ok(u64::MAX),
+        };
+
+        Ok(std::cmp::max(
+            u64::try_from(price).unwrap_or(u64::MAX),
+            self.pricing.min_price,
+        ))
+    }
+
     fn get_auction_amount_dda(&self, price: u64, curtime: i64) -> Result<u64> {
         // compute current best offer price
         let best_offer_price = self.get_best_offer_price(curtime)?;
@@ -275,10 +1181,7 @@ impl Auction {
         let best_offer_price = self.get_best_offer_price(curtime)?;
 
         // get number of price levels required to take
-        let price_levels = math::checked_sub(
-            math::checked_ceil_div(amount, self.pricing.amount_per_level)?,
-            1,
-        )?;
+        let price_levels = math::checked_sub(self.get_price_levels(amount, best_offer_price)?, 1)?;
 
         // compute the auction price
         let price = math::checked_add(
@@ -305,32 +1208,33 @@ impl Auction {
         if curtime <= last_trade_time || curtime >= end_time {
             return Ok(last_price);
         }
-        let step = math::checked_float_div(
-            math::checked_sub(curtime, last_trade_time)? as f64,
-            math::checked_sub(end_time, last_trade_time)? as f64,
-        )?;
 
-        let mut best_offer_price = match self.pricing.reprice_function {
-            RepriceFunction::Exponential => math::checked_as_u64(math::checked_div(
-                math::checked_mul(
-                    last_price as u128,
-                    math::checked_as_u128(math::checked_float_mul(
-                        f64::exp(
-                            -self.pricing.reprice_coef * math::checked_float_mul(step, 100f64)?,
-                        ),
-                        10000.0,
-                    )?)?,
-                )?,
-                10000u128,
-            )?)?,
-            RepriceFunction::Linear => math::checked_as_u64(math::checked_div(
-                math::checked_mul(
-                    last_price as u128,
-                    math::checked_as_u128(math::checked_float_mul(1.0 - step, 10000.0)?)?,
-                )?,
-                10000u128,
-            )?)?,
+        // step is rounded down and the decayed price is rounded up in seller's favor
+        let elapsed = math::checked_sub(curtime, last_trade_time)?;
+        let step = math::checked_fixed_div(
+            elapsed as u128,
+            math::checked_sub(end_time, last_trade_time)? as u128,
+        )?;
+        let multiplier = match self.pricing.reprice_function {
+            RepriceFunction::Exponential => {
+                math::checked_fixed_ceil_exp_neg(math::checked_fixed_mul(
+                    math::checked_mul(self.pricing.reprice_coef as u128, 100)?,
+                    step,
+                )?)?
+            }
+            RepriceFunction::Linear => math::checked_sub(math::FIXED_POINT_ONE, step)?,
+            RepriceFunction::Piecewise => self.get_piecewise_multiplier(elapsed)?,
+            RepriceFunction::Step => math::FIXED_POINT_ONE,
         };
+        let mut best_offer_price = math::checked_as_u64(math::checked_fixed_ceil_mul(
+            last_price as u128,
+            multiplier,
+        )?)?;
+        if self.pricing.reprice_function == RepriceFunction::Step {
+            let num_steps = math::checked_div(elapsed, self.pricing.reprice_interval)?;
+            best_offer_price = best_offer_price
+                .saturating_sub(math::checked_mul(num_steps as u64, self.pricing.tick_size)?);
+        }
 
         // round to tick size
         if best_offer_price % self.pricing.tick_size != 0 {
@@ -347,24 +1251,487 @@ impl Auction {
         Ok(best_offer_price)
     }
 
+    /// Returns the fraction of the last trade price given by the piecewise
+    /// schedule at the given offset, rounded up
+    fn get_piecewise_multiplier(&self, elapsed: i64) -> Result<u128> {
+        let mut prev_offset = 0i64;
+        let mut prev_ratio = math::FIXED_POINT_ONE;
+        for point in self.pricing.reprice_points[..self.pricing.num_reprice_points as usize].iter()
+        {
+            let ratio = point.price_ratio as u128;
+            if elapsed < point.time_offset {
+                let progress = math::checked_sub(elapsed, prev_offset)? as u128;
+                let span = math::checked_sub(point.time_offset, prev_offset)? as u128;
+                return if ratio >= prev_ratio {
+                    math::checked_add(
+                        prev_ratio,
+                        math::checked_ceil_div(
+                            math::checked_mul(ratio - prev_ratio, progress)?,
+                            span,
+                        )?,
+                    )
+                } else {
+                    math::checked_sub(
+                        prev_ratio,
+                        math::checked_div(math::checked_mul(prev_ratio - ratio, progress)?, span)?,
+                    )
+                };
+            }
+            prev_offset = point.time_offset;
+            prev_ratio = ratio;
+        }
+        Ok(prev_ratio)
+    }
+
+    /// Returns the total amount offered at the given number of price levels
+    /// starting from the best offer. Non-constant amount functions round the
+    /// amount down and saturate at u64::MAX.
     pub fn get_offer_size(&self, price_levels: u64) -> Result<u64> {
         match self.pricing.amount_function {
             AmountFunction::Fixed => math::checked_mul(price_levels, self.pricing.amount_per_level),
+            AmountFunction::Linear => self.get_offer_size_linear(price_levels),
+            AmountFunction::Geometric => self.get_offer_size_geometric(price_levels),
         }
     }
-}
 
-#[cfg(test)]
-mod test {
-    use super::*;
+    fn get_offer_size_linear(&self, price_levels: u64) -> Result<u64> {
+        let base_amount = self.pricing.amount_per_level as u128;
+        let increment = self.pricing.amount_coef.unsigned_abs() as u128;
+        let mut levels = price_levels as u128;
 
-    fn get_fixture() -> Auction {
-        let mut auction = Auction {
-            creation_time: 100,
-            ..Default::default()
+        let total = if self.pricing.amount_coef >= 0 {
+            // n * a + d * n * (n - 1) / 2
+            let ramp =
+                increment.saturating_mul(levels.saturating_mul(levels.saturating_sub(1)) / 2);
+            base_amount.saturating_mul(levels).saturating_add(ramp)
+        } else {
+            // levels past the one where the amount drops to zero offer nothing
+            levels = std::cmp::min(levels, math::checked_ceil_div(base_amount, increment)?);
+            let ramp = math::checked_mul(
+                increment,
+                math::checked_div(math::checked_mul(levels, levels.saturating_sub(1))?, 2)?,
+            )?;
+            math::checked_sub(math::checked_mul(base_amount, levels)?, ramp)?
         };
 
-        auction.common.name = "test_auction".to_string();
+        Ok(u64::try_from(total).unwrap_or(u64::MAX))
+    }
+
+    fn get_offer_size_geometric(&self, price_levels: u64) -> Result<u64> {
+        let one = math::FIXED_POINT_ONE;
+        let base_amount = self.pricing.amount_per_level as u128;
+        let ratio = self.pricing.amount_coef as u128;
+
+        // a * (r^n - 1) / (r - 1)
+        let total = match ratio.cmp(&one) {
+            std::cmp::Ordering::Equal => base_amount.saturating_mul(price_levels as u128),
+            std::cmp::Ordering::Greater => {
+                let power = math::checked_fixed_powi(ratio, price_levels)?;
+                if power == u128::MAX {
+                    u128::MAX
+                } else if let Some(scaled) = base_amount.checked_mul(power - one) {
+                    math::checked_div(scaled, ratio - one)?
+                } else {
+                    u128::MAX
+                }
+            }
+            std::cmp::Ordering::Less => {
+                let power = math::checked_fixed_ceil_powi(ratio, price_levels)?;
+                math::checked_div(
+                    math::checked_mul(base_amount, math::checked_sub(one, power)?)?,
+                    one - ratio,
+                )?
+            }
+        };
+
+        Ok(u64::try_from(total).unwrap_or(u64::MAX))
+    }
+
+    /// Returns the number of price levels starting from the best offer
+    /// required to fill the given amount. If the amount exceeds what is
+    /// offered up to max_price, the returned levels go beyond max_price.
+    fn get_price_levels(&self, amount: u64, best_offer_price: u64) -> Result<u64> {
+        if self.pricing.amount_function == AmountFunction::Fixed {
+            return math::checked_ceil_div(amount, self.pricing.amount_per_level);
+        }
+
+        let max_levels = math::checked_add(
+            math::checked_div(
+                self.pricing.max_price.saturating_sub(best_offer_price),
+                self.pricing.tick_size,
+            )?,
+            1,
+        )?;
+        if self.get_offer_size(max_levels)? < amount {
+            return math::checked_add(max_levels, 1);
+        }
+
+        let mut low = 1u64;
+        let mut high = max_levels;
+        while low < high {
+            let mid = low + (high - low) / 2;
+            if self.get_offer_size(mid)? >= amount {
+                high = mid;
+            } else {
+                low = mid + 1;
+            }
+        }
+
+        Ok(low)
+    }
+
+    /// Returns the fill volume within the rolling demand window ending at curtime
+    pub fn get_demand_volume(&self, curtime: i64) -> Result<u64> {
+        let cur_slot = self.get_demand_slot(curtime)?;
+        let mut volume = 0u64;
+        for offset in 0..Auction::DEMAND_SLOTS as i64 {
+            let slot = self.stats.demand_slot - offset;
+            if slot <= cur_slot - Auction::DEMAND_SLOTS as i64 {
+                break;
+            }
+            volume = math::checked_add(
+                volume,
+                self.stats.demand_buckets[slot.rem_euclid(Auction::DEMAND_SLOTS as i64) as usize],
+            )?;
+        }
+        Ok(volume)
+    }
+
+    fn get_demand_slot(&self, curtime: i64) -> Result<i64> {
+        let slot_duration = std::cmp::max(
+            math::checked_ceil_div(self.pricing.demand_window, Auction::DEMAND_SLOTS as i64)?,
+            1,
+        );
+        math::checked_div(curtime, slot_duration)
+    }
+
+    /// Accumulates fill volume within the rolling demand window and steps the
+    /// last price up once the volume reaches the target. The window is cleared
+    /// after the target is hit.
+    fn update_demand_window(&mut self, fill_amount: u64, curtime: i64) -> Result<()> {
+        // clear buckets of the slots that have passed since the last fill
+        let cur_slot = self.get_demand_slot(curtime)?;
+        if cur_slot > self.stats.demand_slot {
+            let num_expired = std::cmp::min(
+                math::checked_sub(cur_slot, self.stats.demand_slot)?,
+                Auction::DEMAND_SLOTS as i64,
+            );
+            for offset in 0..num_expired {
+                let slot = cur_slot - offset;
+                self.stats.demand_buckets[slot.rem_euclid(Auction::DEMAND_SLOTS as i64) as usize] =
+                    0;
+            }
+            self.stats.demand_slot = cur_slot;
+        }
+        let bucket = self
+            .stats
+            .demand_slot
+            .rem_euclid(Auction::DEMAND_SLOTS as i64) as usize;
+        self.stats.demand_buckets[bucket] =
+            math::checked_add(self.stats.demand_buckets[bucket], fill_amount)?;
+
+        if self.get_demand_volume(curtime)? >= self.pricing.demand_target {
+            self.stats.last_price = std::cmp::min(
+                math::checked_add(self.stats.last_price, self.pricing.demand_step)?,
+                self.pricing.max_price,
+            );
+            self.stats.demand_buckets = [0; Auction::DEMAND_SLOTS];
+        }
+
+        Ok(())
+    }
+
+    /// Picks a token to dispense at random with probability proportional to
+    /// its ratio, see AuctionToken. Zero ratios are replaced with the available
+    /// amount, dispensers that can't cover min_amount units are skipped.
+    /// Returns None if none of the dispensers can cover the amount.
+    pub fn select_token(
+        &mut self,
+        dispenser_amounts: &[u64],
+        min_amount: u64,
+        rand_seed: u64,
+    ) -> Result<Option<usize>> {
+        require_eq!(
+            dispenser_amounts.len(),
+            self.num_tokens as usize,
+            LaunchpadError::InvalidDispenserAddress
+        );
+
+        let mut total_ratio = 0u128;
+        let mut eligible = [false; Auction::MAX_TOKENS];
+        for (token, &amount) in dispenser_amounts.iter().enumerate() {
+            if self.tokens[token].ratio == 0 {
+                self.tokens[token].ratio = amount;
+            }
+            if math::checked_div(amount, self.pricing.unit_size)? >= min_amount
+                && self.tokens[token].ratio > 0
+            {
+                eligible[token] = true;
+                total_ratio = math::checked_add(total_ratio, self.tokens[token].ratio as u128)?;
+            }
+        }
+        if total_ratio == 0 {
+            return Ok(None);
+        }
+
+        let mut target = rand_seed as u128 % total_ratio;
+        for (token, token_eligible) in eligible.iter().enumerate().take(dispenser_amounts.len()) {
+            if !token_eligible {
+                continue;
+            }
+            let ratio = self.tokens[token].ratio as u128;
+            if target < ratio {
+                return Ok(Some(token));
+            }
+            target -= ratio;
+        }
+
+        Err(ProgramError::InvalidArgument.into())
+    }
+
+    /// Splits the fill between dispensers. The selected token is dispensed first,
+    /// the remainder is routed to other dispensers proportionally to their ratios.
+    /// Returns the number of units to dispense from every dispenser.
+    pub fn route_fill(
+        &self,
+        dispenser_amounts: &[u64],
+        token_num: usize,
+        fill_amount: u64,
+    ) -> Result<Vec<u64>> {
+        require_eq!(
+            dispenser_amounts.len(),
+            self.num_tokens as usize,
+            LaunchpadError::InvalidDispenserAddress
+        );
+        let mut avail_amounts = Vec::with_capacity(dispenser_amounts.len());
+        for &amount in dispenser_amounts {
+            avail_amounts.push(math::checked_div(amount, self.pricing.unit_size)?);
+        }
+
+        let mut fills = vec![0u64; dispenser_amounts.len()];
+        fills[token_num] = std::cmp::min(avail_amounts[token_num], fill_amount);
+        avail_amounts[token_num] -= fills[token_num];
+        let mut remaining = fill_amount - fills[token_num];
+
+        while remaining > 0 {
+            let mut total_ratio = 0u128;
+            let mut best_token: Option<usize> = None;
+            for (token, &avail_amount) in avail_amounts.iter().enumerate() {
+                let ratio = self.tokens[token].ratio;
+                if avail_amount == 0 || ratio == 0 {
+                    continue;
+                }
+                total_ratio = math::checked_add(total_ratio, ratio as u128)?;
+                match best_token {
+                    Some(best) if self.tokens[best].ratio >= ratio => {}
+                    _ => best_token = Some(token),
+                }
+            }
+            let best_token = best_token.ok_or(LaunchpadError::InsufficientAmount)?;
+
+            let mut routed = 0;
+            for (token, avail_amount) in avail_amounts.iter_mut().enumerate() {
+                if *avail_amount == 0 || self.tokens[token].ratio == 0 {
+                    continue;
+                }
+                let share = std::cmp::min(
+                    *avail_amount,
+                    math::checked_as_u64(math::checked_div(
+                        math::checked_mul(remaining as u128, self.tokens[token].ratio as u128)?,
+                        total_ratio,
+                    )?)?,
+                );
+                fills[token] += share;
+                *avail_amount -= share;
+                routed += share;
+            }
+
+            // rounding leftovers go to the dispenser with the highest ratio
+            if routed == 0 {
+                routed = std::cmp::min(avail_amounts[best_token], remaining);
+                fills[best_token] += routed;
+                avail_amounts[best_token] -= routed;
+            }
+            remaining -= routed;
+        }
+
+        Ok(fills)
+    }
+
+    /// Routes a claim that starts from the selected token, see route_fill.
+    /// The claim is cut to the amount the selected token and dispensers with
+    /// non-zero ratios can cover together.
+    /// Returns the number of units to dispense from every dispenser.
+    pub fn route_claim(
+        &self,
+        dispenser_amounts: &[u64],
+        token_num: usize,
+        claim_amount: u64,
+    ) -> Result<Vec<u64>> {
+        require_eq!(
+            dispenser_amounts.len(),
+            self.num_tokens as usize,
+            LaunchpadError::InvalidDispenserAddress
+        );
+        let mut avail_amount = 0u64;
+        for (token, &amount) in dispenser_amounts.iter().enumerate() {
+            if token == token_num || self.tokens[token].ratio > 0 {
+                avail_amount = math::checked_add(
+                    avail_amount,
+                    math::checked_div(amount, self.pricing.unit_size)?,
+                )?;
+            }
+        }
+        self.route_fill(
+            dispenser_amounts,
+            token_num,
+            std::cmp::min(claim_amount, avail_amount),
+        )
+    }
+
+    /// Returns the number of basket units the dispensers can cover
+    pub fn get_basket_units(&self, dispenser_amounts: &[u64]) -> Result<u64> {
+        require_eq!(
+            dispenser_amounts.len(),
+            self.num_tokens as usize,
+            LaunchpadError::InvalidDispenserAddress
+        );
+        if dispenser_amounts.is_empty() {
+            return Ok(0);
+        }
+        let mut units = u64::MAX;
+        for (token, &amount) in dispenser_amounts.iter().enumerate() {
+            units = std::cmp::min(
+                units,
+                math::checked_div(amount, self.tokens[token].basket_amount)?,
+            );
+        }
+        Ok(units)
+    }
+
+    /// Returns the number of units the dispensers can cover on top of pending
+    /// bids. Deferred claims reserve supply at bid time, so the sale can't
+    /// commit more units than the dispensers hold.
+    pub fn get_deferred_supply(&self, dispenser_amounts: &[u64]) -> Result<u64> {
+        let units = if self.basket {
+            self.get_basket_units(dispenser_amounts)?
+        } else {
+            require_eq!(
+                dispenser_amounts.len(),
+                self.num_tokens as usize,
+                LaunchpadError::InvalidDispenserAddress
+            );
+            let mut units = 0u64;
+            for &amount in dispenser_amounts {
+                units =
+                    math::checked_add(units, math::checked_div(amount, self.pricing.unit_size)?)?;
+            }
+            units
+        };
+        Ok(units.saturating_sub(self.stats.pending_amount))
+    }
+
+    pub fn update_stats(
+        &mut self,
+        fill_amount: u64,
+        fill_price: u64,
+        price_adjustment_bps: i64,
+        whitelisted: bool,
+        curtime: i64,
+    ) -> Result<()> {
+        if self.stats.first_trade_time == 0 {
+            self.stats.first_trade_time = curtime;
+        }
+        self.stats.last_trade_time = curtime;
+        self.stats.last_amount = fill_amount;
+        self.stats.last_price = fill_price;
+        if self.pricing.pricing_model == PricingModel::DynamicDutchAuction
+            && self.pricing.demand_target > 0
+        {
+            self.update_demand_window(fill_amount, curtime)?;
+        }
+        if self.common.extension_window > 0 {
+            self.update_end_time_extension(curtime)?;
+        }
+
+        let bidder_stats = if whitelisted {
+            &mut self.stats.wl_bidders
+        } else {
+            &mut self.stats.reg_bidders
+        };
+        bidder_stats.fills_volume = math::checked_add(bidder_stats.fills_volume, fill_amount)?;
+        bidder_stats.weighted_fills_sum = math::checked_add(
+            bidder_stats.weighted_fills_sum,
+            math::checked_mul(fill_amount as u128, fill_price as u128)?,
+        )?;
+        if fill_price < bidder_stats.min_fill_price {
+            bidder_stats.min_fill_price = fill_price;
+        }
+        if fill_price > bidder_stats.max_fill_price {
+            bidder_stats.max_fill_price = fill_price;
+        }
+        bidder_stats.num_trades = bidder_stats.num_trades.wrapping_add(1);
+        self.stats.raised_amount = math::checked_add(
+            self.stats.raised_amount,
+            PaymentParams::adjust_value(
+                math::checked_mul(fill_amount as u128, fill_price as u128)?,
+                price_adjustment_bps,
+            )?,
+        )?;
+
+        // end the auction if the rest of the maximum raise can't buy another
+        // unit with the cheapest payment custody
+        if self.has_hard_cap()
+            && !self.is_hard_cap_met()
+            && self.get_hard_cap_amount(1, curtime, self.payment.get_min_price_adjustment_bps())?
+                == 0
+        {
+            self.stats.hard_cap_time = curtime;
+        }
+
+        Ok(())
+    }
+
+    /// Removes units that were sold but couldn't be dispensed from fill stats
+    pub fn revert_stats(
+        &mut self,
+        amount: u64,
+        fill_price: u64,
+        price_adjustment_bps: i64,
+        whitelisted: bool,
+    ) -> Result<()> {
+        let fill_value = math::checked_mul(amount as u128, fill_price as u128)?;
+        self.stats.raised_amount =
+            self.stats
+                .raised_amount
+                .saturating_sub(PaymentParams::adjust_value(
+                    fill_value,
+                    price_adjustment_bps,
+                )?);
+        let bidder_stats = if whitelisted {
+            &mut self.stats.wl_bidders
+        } else {
+            &mut self.stats.reg_bidders
+        };
+        bidder_stats.fills_volume = bidder_stats.fills_volume.saturating_sub(amount);
+        bidder_stats.weighted_fills_sum =
+            bidder_stats.weighted_fills_sum.saturating_sub(fill_value);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn get_fixture() -> Auction {
+        let mut auction = Auction {
+            creation_time: 100,
+            ..Default::default()
+        };
+
+        auction.common.name = "test_auction".to_string();
         auction.common.start_time = 350;
         auction.common.end_time = 500;
         auction.common.presale_start_time = 200;
@@ -375,14 +1742,15 @@ mod test {
         auction.pricing.max_price = 2000;
         auction.pricing.min_price = 50;
         auction.pricing.reprice_delay = 10;
-        auction.pricing.reprice_coef = 0.05;
+        auction.pricing.reprice_coef = 50_000_000;
         auction.pricing.reprice_function = RepriceFunction::Exponential;
         auction.pricing.amount_function = AmountFunction::Fixed;
         auction.pricing.amount_per_level = 20;
         auction.pricing.tick_size = 10;
         auction.pricing.unit_size = 100;
 
-        auction.payment.accept_sol = true;
+        auction.payment.custodies[0].custody = Pubkey::new_unique();
+        auction.payment.num_custodies = 1;
 
         assert!(auction.validate().unwrap());
 
@@ -415,6 +1783,58 @@ mod test {
         assert_eq!(50, auction.get_best_offer_price(499).unwrap());
     }
 
+    #[test]
+    fn get_best_offer_price_piecewise() {
+        let mut auction = get_fixture();
+
+        auction.pricing.reprice_function = RepriceFunction::Piecewise;
+        assert!(!auction.validate().unwrap());
+
+        auction.pricing.reprice_points[0] = RepricePoint {
+            time_offset: 20,
+            price_ratio: 500_000_000,
+        };
+        auction.pricing.reprice_points[1] = RepricePoint {
+            time_offset: 100,
+            price_ratio: 400_000_000,
+        };
+        auction.pricing.num_reprice_points = 2;
+        assert!(auction.validate().unwrap());
+
+        assert_eq!(1000, auction.get_best_offer_price(100).unwrap());
+        assert_eq!(1000, auction.get_best_offer_price(210).unwrap());
+        assert_eq!(750, auction.get_best_offer_price(220).unwrap());
+        assert_eq!(500, auction.get_best_offer_price(230).unwrap());
+        assert_eq!(480, auction.get_best_offer_price(250).unwrap());
+        assert_eq!(1000, auction.get_best_offer_price(350).unwrap());
+        assert_eq!(480, auction.get_best_offer_price(400).unwrap());
+        assert_eq!(400, auction.get_best_offer_price(499).unwrap());
+
+        auction.pricing.reprice_points[1].time_offset = 20;
+        assert!(!auction.validate().unwrap());
+    }
+
+    #[test]
+    fn get_best_offer_price_step() {
+        let mut auction = get_fixture();
+
+        auction.pricing.reprice_function = RepriceFunction::Step;
+        assert!(!auction.validate().unwrap());
+
+        auction.pricing.reprice_interval = 15;
+        assert!(auction.validate().unwrap());
+
+        assert_eq!(1000, auction.get_best_offer_price(100).unwrap());
+        assert_eq!(1000, auction.get_best_offer_price(224).unwrap());
+        assert_eq!(990, auction.get_best_offer_price(225).unwrap());
+        assert_eq!(980, auction.get_best_offer_price(250).unwrap());
+        assert_eq!(1000, auction.get_best_offer_price(350).unwrap());
+        assert_eq!(910, auction.get_best_offer_price(499).unwrap());
+
+        auction.pricing.reprice_interval = 1;
+        assert_eq!(50, auction.get_best_offer_price(499).unwrap());
+    }
+
     #[test]
     fn get_auction_price_dda() {
         let mut auction = get_fixture();
@@ -503,4 +1923,667 @@ mod test {
                 .unwrap()
         );
     }
+
+    #[test]
+    fn get_best_offer_price_demand() {
+        let mut auction = get_fixture();
+        auction.pricing.reprice_function = RepriceFunction::Linear;
+        auction.pricing.demand_target = 50;
+        assert!(!auction.validate().unwrap());
+
+        auction.pricing.demand_window = 60;
+        auction.pricing.demand_step = 100;
+        assert!(auction.validate().unwrap());
+
+        auction.update_stats(30, 1000, 0, false, 360).unwrap();
+        assert_eq!(1000, auction.get_best_offer_price(370).unwrap());
+        assert_eq!(30, auction.get_demand_volume(370).unwrap());
+
+        // target reached within the window, price steps up
+        auction.update_stats(20, 1000, 0, false, 380).unwrap();
+        assert_eq!(1100, auction.get_best_offer_price(390).unwrap());
+        assert_eq!(0, auction.get_demand_volume(390).unwrap());
+
+        // the window rolls, fills older than the window drop out
+        auction.update_stats(10, 1100, 0, false, 381).unwrap();
+        auction.update_stats(30, 1100, 0, false, 420).unwrap();
+        assert_eq!(40, auction.get_demand_volume(420).unwrap());
+        assert_eq!(30, auction.get_demand_volume(440).unwrap());
+        assert_eq!(0, auction.get_demand_volume(500).unwrap());
+        assert_eq!(1100, auction.stats.last_price);
+
+        // target reached within the last 60 seconds
+        auction.update_stats(20, 1100, 0, false, 442).unwrap();
+        assert_eq!(1200, auction.stats.last_price);
+        assert_eq!(0, auction.get_demand_volume(442).unwrap());
+
+        // volume starts over after the step up
+        auction.update_stats(30, 1100, 0, false, 450).unwrap();
+        assert_eq!(1100, auction.get_best_offer_price(460).unwrap());
+        assert_eq!(30, auction.get_demand_volume(460).unwrap());
+
+        // quiet periods still decay the price
+        assert_eq!(550, auction.get_best_offer_price(480).unwrap());
+
+        // step up is capped at max_price
+        auction.update_stats(30, 1950, 0, false, 470).unwrap();
+        assert_eq!(2000, auction.stats.last_price);
+    }
+
+    #[test]
+    fn get_auction_amount_linear() {
+        let mut auction = get_fixture();
+
+        auction.pricing.amount_function = AmountFunction::Linear;
+        auction.pricing.amount_coef = 10;
+        assert_eq!(20, auction.get_auction_amount_dda(1000, 100).unwrap());
+        assert_eq!(50, auction.get_auction_amount_dda(1010, 100).unwrap());
+        assert_eq!(90, auction.get_auction_amount_dda(1020, 100).unwrap());
+        assert_eq!(1000, auction.get_auction_price_dda(20, 100).unwrap());
+        assert_eq!(1010, auction.get_auction_price_dda(21, 100).unwrap());
+        assert_eq!(1010, auction.get_auction_price_dda(50, 100).unwrap());
+        assert_eq!(1020, auction.get_auction_price_dda(51, 100).unwrap());
+        assert_eq!(
+            u64::MAX,
+            auction.get_auction_amount_dda(u64::MAX, 100).unwrap()
+        );
+
+        // amount shrinks towards the top of the book
+        auction.pricing.amount_coef = -5;
+        assert_eq!(20, auction.get_auction_amount_dda(1000, 100).unwrap());
+        assert_eq!(35, auction.get_auction_amount_dda(1010, 100).unwrap());
+        assert_eq!(50, auction.get_auction_amount_dda(1030, 100).unwrap());
+        assert_eq!(50, auction.get_auction_amount_dda(2000, 100).unwrap());
+        assert_eq!(1030, auction.get_auction_price_dda(50, 100).unwrap());
+        assert_eq!(2000, auction.get_auction_price_dda(51, 100).unwrap());
+    }
+
+    #[test]
+    fn get_auction_amount_geometric() {
+        let mut auction = get_fixture();
+
+        auction.pricing.amount_function = AmountFunction::Geometric;
+        auction.pricing.amount_coef = 0;
+        assert!(!auction.validate().unwrap());
+
+        auction.pricing.amount_coef = 2_000_000_000;
+        assert!(auction.validate().unwrap());
+        assert_eq!(20, auction.get_auction_amount_dda(1000, 100).unwrap());
+        assert_eq!(60, auction.get_auction_amount_dda(1010, 100).unwrap());
+        assert_eq!(140, auction.get_auction_amount_dda(1020, 100).unwrap());
+        assert_eq!(1010, auction.get_auction_price_dda(21, 100).unwrap());
+        assert_eq!(1020, auction.get_auction_price_dda(61, 100).unwrap());
+        assert_eq!(
+            u64::MAX,
+            auction.get_auction_amount_dda(u64::MAX / 2, 100).unwrap()
+        );
+
+        // amount shrinks towards the top of the book
+        auction.pricing.amount_coef = 500_000_000;
+        assert_eq!(20, auction.get_auction_amount_dda(1000, 100).unwrap());
+        assert_eq!(30, auction.get_auction_amount_dda(1010, 100).unwrap());
+        assert_eq!(35, auction.get_auction_amount_dda(1020, 100).unwrap());
+        assert_eq!(39, auction.get_auction_amount_dda(2000, 100).unwrap());
+        assert_eq!(1020, auction.get_auction_price_dda(35, 100).unwrap());
+        assert_eq!(2000, auction.get_auction_price_dda(40, 100).unwrap());
+    }
+
+    #[test]
+    fn get_auction_price_english() {
+        let mut auction = get_fixture();
+        auction.pricing.pricing_model = PricingModel::EnglishAuction;
+        auction.num_tokens = 2;
+        assert!(!auction.validate().unwrap());
+
+        auction.num_tokens = 1;
+        assert!(auction.validate().unwrap());
+
+        assert_eq!(1000, auction.get_auction_price(1, 0).unwrap());
+        assert_eq!(0, auction.get_auction_amount(999, 0).unwrap());
+        assert_eq!(1, auction.get_auction_amount(1000, 0).unwrap());
+
+        auction.stats.leading.bid = Pubkey::new_unique();
+        auction.stats.leading.price = 1200;
+        assert_eq!(1210, auction.get_auction_price(1, 0).unwrap());
+        assert_eq!(0, auction.get_auction_amount(1209, 0).unwrap());
+        assert_eq!(1, auction.get_auction_amount(1210, 0).unwrap());
+    }
+
+    #[test]
+    fn get_auction_price_pool() {
+        let mut auction = get_fixture();
+        auction.pricing.pricing_model = PricingModel::WeightedPool;
+        auction.pricing.pool_reserve = 1000;
+        auction.pricing.start_weight = 500_000_000;
+        auction.pricing.end_weight = 200_000_000;
+        assert!(auction.validate().unwrap());
+
+        // equal weights, spot price equals start_price
+        assert_eq!(0, auction.get_auction_price(0, 200).unwrap());
+        assert_eq!(1002, auction.get_auction_price(1, 200).unwrap());
+        assert_eq!(1011, auction.get_auction_price(10, 200).unwrap());
+        assert_eq!(0, auction.get_auction_amount(1001, 200).unwrap());
+        assert_eq!(499, auction.get_auction_amount(u64::MAX, 200).unwrap());
+
+        // token weight decreases over time
+        assert_eq!(539, auction.get_auction_price(1, 350).unwrap());
+        assert_eq!(251, auction.get_auction_price(1, 500).unwrap());
+        assert_eq!(251, auction.get_auction_price(1, 600).unwrap());
+
+        // fills add payments to the reserve
+        auction.stats.reg_bidders.fills_volume = 100;
+        auction.stats.reg_bidders.weighted_fills_sum = 110_000;
+        assert_eq!(1235, auction.get_auction_price(1, 200).unwrap());
+        assert_eq!(344, auction.get_auction_amount(u64::MAX, 200).unwrap());
+
+        // min_price is the floor
+        auction.pricing.min_price = 400;
+        assert_eq!(400, auction.get_auction_price(1, 500).unwrap());
+
+        // one unit always stays in the pool
+        auction.pricing.max_price = u64::MAX;
+        assert_eq!(899, auction.get_auction_amount(u64::MAX, 200).unwrap());
+    }
+
+    #[test]
+    fn settle_batch_auction() {
+        let mut auction = get_fixture();
+        auction.pricing.pricing_model = PricingModel::BatchAuction;
+        auction.pricing.min_price = 100;
+        auction.pricing.start_price = 100;
+        auction.pricing.max_price = 400;
+        auction.pricing.tick_size = 10;
+        assert!(auction.validate().unwrap());
+
+        assert_eq!(0, auction.get_price_level(100).unwrap());
+        assert_eq!(0, auction.get_price_level(109).unwrap());
+        assert_eq!(30, auction.get_price_level(400).unwrap());
+        assert!(auction.get_price_level(99).is_err());
+        assert!(auction.get_price_level(401).is_err());
+
+        auction
+            .add_demand(auction.get_price_level(300).unwrap(), 50)
+            .unwrap();
+        auction
+            .add_demand(auction.get_price_level(200).unwrap(), 40)
+            .unwrap();
+        auction
+            .add_demand(auction.get_price_level(200).unwrap(), 40)
+            .unwrap();
+        auction
+            .add_demand(auction.get_price_level(150).unwrap(), 100)
+            .unwrap();
+
+        // oversubscribed, pro-rata fill at the clearing price
+        let mut settled = auction.clone();
+        settled.settle(90, 600).unwrap();
+        assert!(settled.is_settled());
+        assert_eq!(200, settled.stats.clearing.clearing_price);
+        assert_eq!(90, settled.stats.clearing.unclaimed_amount);
+        assert_eq!(200, settled.get_auction_price(1, 600).unwrap());
+        assert_eq!(50, settled.get_clearing_fill_amount(300, 50).unwrap());
+        assert_eq!(20, settled.get_clearing_fill_amount(200, 40).unwrap());
+        assert_eq!(0, settled.get_clearing_fill_amount(150, 100).unwrap());
+
+        // exact match
+        let mut settled = auction.clone();
+        settled.settle(130, 600).unwrap();
+        assert_eq!(200, settled.stats.clearing.clearing_price);
+        assert_eq!(40, settled.get_clearing_fill_amount(200, 40).unwrap());
+        assert_eq!(0, settled.get_clearing_fill_amount(150, 100).unwrap());
+
+        // undersubscribed, everybody is filled at min price
+        let mut settled = auction.clone();
+        settled.settle(1000, 600).unwrap();
+        assert_eq!(100, settled.stats.clearing.clearing_price);
+        assert_eq!(230, settled.stats.clearing.unclaimed_amount);
+        assert_eq!(50, settled.get_clearing_fill_amount(300, 50).unwrap());
+        assert_eq!(100, settled.get_clearing_fill_amount(150, 100).unwrap());
+
+        // nothing to sell
+        let mut settled = auction.clone();
+        settled.settle(0, 600).unwrap();
+        assert_eq!(300, settled.stats.clearing.clearing_price);
+        assert_eq!(0, settled.get_clearing_fill_amount(300, 50).unwrap());
+        assert_eq!(0, settled.get_clearing_fill_amount(200, 40).unwrap());
+    }
+
+    #[test]
+    fn validate_sealed_auction() {
+        let mut auction = get_fixture();
+        auction.common.reveal_start_time = 500;
+        auction.common.reveal_end_time = 600;
+        assert!(!auction.validate().unwrap());
+
+        auction.pricing.pricing_model = PricingModel::BatchAuction;
+        auction.pricing.tick_size = 100;
+        assert!(auction.validate().unwrap());
+        assert!(auction.is_sealed());
+        assert!(!auction.is_reveal_period(499));
+        assert!(auction.is_reveal_period(500));
+        assert!(!auction.is_reveal_period(600));
+        assert!(!auction.is_settle_period(599));
+        assert!(auction.is_settle_period(600));
+
+        auction.common.reveal_start_time = 450;
+        assert!(!auction.validate().unwrap());
+
+        // open price models can't be sealed
+        auction.common.reveal_start_time = 500;
+        assert!(auction.validate().unwrap());
+        auction.pricing.pricing_model = PricingModel::DynamicDutchAuction;
+        assert!(!auction.validate().unwrap());
+        auction.pricing.pricing_model = PricingModel::Fixed;
+        assert!(!auction.validate().unwrap());
+    }
+
+    #[test]
+    fn soft_cap() {
+        let mut auction = get_fixture();
+        auction.common.min_raise = 10_000;
+        assert!(!auction.validate().unwrap());
+
+        auction.num_tokens = 1;
+        assert!(auction.validate().unwrap());
+        assert!(auction.has_soft_cap());
+        assert!(!auction.is_soft_cap_met());
+        assert!(!auction.is_soft_cap_failed(499));
+        assert!(auction.is_soft_cap_failed(500));
+
+        auction.update_stats(5, 1000, 0, false, 400).unwrap();
+        auction.update_stats(4, 1000, 0, true, 410).unwrap();
+        assert_eq!(9_000, auction.get_raised_amount());
+        assert!(!auction.is_soft_cap_met());
+
+        auction.update_stats(1, 1000, 0, false, 420).unwrap();
+        assert!(auction.is_soft_cap_met());
+        assert!(!auction.is_soft_cap_failed(500));
+
+        auction.pricing.pricing_model = PricingModel::BatchAuction;
+        auction.pricing.tick_size = 100;
+        assert!(!auction.validate().unwrap());
+
+        auction.common.min_raise = 0;
+        assert!(auction.validate().unwrap());
+        assert!(!auction.has_soft_cap());
+        assert!(auction.is_soft_cap_met());
+    }
+
+    #[test]
+    fn hard_cap() {
+        let mut auction = get_fixture();
+        auction.pricing.pricing_model = PricingModel::Fixed;
+        auction.pricing.start_price = 100;
+        auction.pricing.min_price = 100;
+        auction.pricing.max_price = 100;
+        auction.common.max_raise = 1_050;
+        assert!(auction.validate().unwrap());
+
+        auction.common.min_raise = 2_000;
+        auction.num_tokens = 1;
+        assert!(!auction.validate().unwrap());
+        auction.common.min_raise = 0;
+
+        assert!(auction.has_hard_cap());
+        assert_eq!(10, auction.get_hard_cap_amount(20, 400, 0).unwrap());
+        assert_eq!(5, auction.get_hard_cap_amount(5, 400, 0).unwrap());
+        assert_eq!(10, auction.get_hard_cap_amount(20, 400, 500).unwrap());
+        assert_eq!(9, auction.get_hard_cap_amount(20, 400, 1000).unwrap());
+
+        // fills paid with a premium count at the adjusted value
+        auction.update_stats(4, 100, 500, false, 400).unwrap();
+        assert_eq!(420, auction.get_raised_amount());
+        assert_eq!(6, auction.get_hard_cap_amount(20, 410, 0).unwrap());
+        assert_eq!(7, auction.get_hard_cap_amount(20, 410, -2000).unwrap());
+        assert!(!auction.is_ended(410, false));
+
+        auction.revert_stats(4, 100, 500, false).unwrap();
+        assert_eq!(0, auction.get_raised_amount());
+        auction.update_stats(4, 100, 0, false, 400).unwrap();
+
+        // the remaining 50 can't buy another unit
+        auction.update_stats(6, 100, 0, false, 410).unwrap();
+        assert_eq!(0, auction.get_hard_cap_amount(20, 420, 0).unwrap());
+        assert!(auction.is_hard_cap_met());
+        assert_eq!(410, auction.stats.hard_cap_time);
+        assert!(auction.is_ended(420, false));
+        assert!(auction.is_ended(420, true));
+
+        auction.pricing.pricing_model = PricingModel::BatchAuction;
+        auction.pricing.tick_size = 100;
+        assert!(!auction.validate().unwrap());
+    }
+
+    #[test]
+    fn end_time_extension() {
+        let mut auction = get_fixture();
+        auction.common.extension_window = 20;
+        assert!(!auction.validate().unwrap());
+
+        auction.common.extension_duration = 30;
+        auction.common.max_extension = 50;
+        assert!(auction.validate().unwrap());
+
+        // fills outside of the window don't extend the auction
+        auction.update_stats(1, 1000, 0, false, 400).unwrap();
+        assert_eq!(500, auction.get_end_time(false));
+
+        auction.update_stats(1, 1000, 0, false, 490).unwrap();
+        assert_eq!(530, auction.get_end_time(false));
+        assert_eq!(530, auction.get_end_time(true));
+        assert!(!auction.is_ended(510, false));
+
+        // total extension is capped
+        auction.update_stats(1, 1000, 0, false, 515).unwrap();
+        assert_eq!(550, auction.get_end_time(false));
+        auction.update_stats(1, 1000, 0, false, 540).unwrap();
+        assert_eq!(550, auction.get_end_time(false));
+        assert!(auction.is_ended(550, true));
+
+        // presale is extended separately
+        auction.update_stats(1, 1000, 0, true, 290).unwrap();
+        assert_eq!(30, auction.stats.presale_end_time_extension);
+        assert_eq!(550, auction.get_end_time(true));
+
+        // presale extension stops at the public sale start
+        auction.common.max_extension = 100;
+        auction.update_stats(1, 1000, 0, true, 325).unwrap();
+        assert_eq!(50, auction.stats.presale_end_time_extension);
+        auction.update_stats(1, 1000, 0, true, 345).unwrap();
+        assert_eq!(50, auction.stats.presale_end_time_extension);
+        assert_eq!(
+            auction.common.start_time,
+            auction.get_extended_end_times().0
+        );
+
+        auction.pricing.pricing_model = PricingModel::BatchAuction;
+        auction.pricing.tick_size = 100;
+        assert!(!auction.validate().unwrap());
+    }
+
+    #[test]
+    fn deferred_claim() {
+        let mut auction = get_fixture();
+        auction.deferred_claim = true;
+        assert!(auction.validate().unwrap());
+        assert_eq!(500, auction.get_claim_time());
+
+        auction.common.tge_time = 450;
+        assert!(!auction.validate().unwrap());
+
+        auction.common.tge_time = 600;
+        assert!(auction.validate().unwrap());
+        assert_eq!(600, auction.get_claim_time());
+
+        auction.vesting.duration = 1000;
+        assert!(!auction.validate().unwrap());
+
+        auction.vesting.duration = 0;
+        auction.pricing.pricing_model = PricingModel::BatchAuction;
+        auction.pricing.tick_size = 100;
+        assert!(!auction.validate().unwrap());
+    }
+
+    #[test]
+    fn get_deferred_supply() {
+        let mut auction = get_fixture();
+        auction.deferred_claim = true;
+        auction.num_tokens = 2;
+        auction.tokens[0].ratio = 1;
+        auction.tokens[1].ratio = 1;
+
+        // units of all dispensers are available
+        assert_eq!(7, auction.get_deferred_supply(&[350, 420]).unwrap());
+
+        // pending bids are reserved
+        auction.stats.pending_amount = 5;
+        assert_eq!(2, auction.get_deferred_supply(&[350, 420]).unwrap());
+        auction.stats.pending_amount = 8;
+        assert_eq!(0, auction.get_deferred_supply(&[350, 420]).unwrap());
+
+        // basket units take all tokens
+        auction.basket = true;
+        auction.tokens[0].basket_amount = 10;
+        auction.tokens[1].basket_amount = 40;
+        assert_eq!(2, auction.get_deferred_supply(&[350, 400]).unwrap());
+
+        assert!(auction.get_deferred_supply(&[350]).is_err());
+    }
+
+    #[test]
+    fn select_token() {
+        let mut auction = get_fixture();
+        auction.pricing.unit_size = 1;
+        auction.num_tokens = 3;
+        auction.tokens[0].ratio = 100;
+        auction.tokens[1].ratio = 200;
+
+        let count_selected = |auction: &mut Auction, amounts: &[u64], num_seeds: u64| {
+            let mut counts = [0u64; 3];
+            for seed in 0..num_seeds {
+                counts[auction.select_token(amounts, 1, seed).unwrap().unwrap()] += 1;
+            }
+            counts
+        };
+
+        // zero ratio is replaced with the available amount
+        assert_eq!(
+            [1000, 2000, 3000],
+            count_selected(&mut auction, &[500, 500, 300], 6000)
+        );
+        assert_eq!(300, auction.tokens[2].ratio);
+
+        // depleted dispensers are skipped
+        assert_eq!(
+            [1000, 0, 3000],
+            count_selected(&mut auction, &[500, 0, 300], 4000)
+        );
+        assert_eq!(None, auction.select_token(&[5, 0, 0], 10, 0).unwrap());
+        assert_eq!(
+            Some(0),
+            auction.select_token(&[10, 0, 0], 10, 12345).unwrap()
+        );
+
+        // amounts are counted in units
+        auction.pricing.unit_size = 100;
+        assert_eq!(None, auction.select_token(&[99, 99, 99], 1, 0).unwrap());
+        assert!(auction.select_token(&[99, 99], 1, 0).is_err());
+    }
+
+    #[test]
+    fn payment_custodies() {
+        let mut auction = get_fixture();
+        let custody = auction.payment.custodies[0].custody;
+        let other_custody = Pubkey::new_unique();
+        assert!(auction.payment.is_accepted(&custody));
+        assert!(!auction.payment.is_accepted(&other_custody));
+        assert_eq!(
+            1000,
+            auction
+                .payment
+                .adjust_payment_amount(&custody, 1000)
+                .unwrap()
+        );
+        assert!(auction
+            .payment
+            .adjust_payment_amount(&other_custody, 1000)
+            .is_err());
+
+        auction.payment.custodies[1] = PaymentCustody {
+            custody: other_custody,
+            price_adjustment_bps: -250,
+        };
+        auction.payment.num_custodies = 2;
+        assert!(auction.validate().unwrap());
+        assert_eq!(
+            975,
+            auction
+                .payment
+                .adjust_payment_amount(&other_custody, 1000)
+                .unwrap()
+        );
+
+        auction.payment.custodies[1].price_adjustment_bps = 500;
+        assert_eq!(
+            1050,
+            auction
+                .payment
+                .adjust_payment_amount(&other_custody, 1000)
+                .unwrap()
+        );
+
+        // duplicates and full discounts are not allowed
+        auction.payment.custodies[1].price_adjustment_bps = -10000;
+        assert!(!auction.validate().unwrap());
+        auction.payment.custodies[1] = auction.payment.custodies[0];
+        assert!(!auction.validate().unwrap());
+
+        auction.payment.num_custodies = 0;
+        assert!(!auction.validate().unwrap());
+    }
+
+    #[test]
+    fn route_fill() {
+        let mut auction = get_fixture();
+        auction.pricing.unit_size = 10;
+        auction.num_tokens = 3;
+        auction.tokens[0].ratio = 1;
+        auction.tokens[1].ratio = 1;
+        auction.tokens[2].ratio = 2;
+        auction.split_fills = true;
+        assert!(auction.validate().unwrap());
+
+        // the selected dispenser covers the fill
+        assert_eq!(
+            vec![0, 5, 0],
+            auction.route_fill(&[1000, 1000, 1000], 1, 5).unwrap()
+        );
+
+        // the remainder is split by ratios
+        assert_eq!(
+            vec![5, 3, 12],
+            auction.route_fill(&[1000, 30, 1000], 1, 20).unwrap()
+        );
+
+        // capped dispensers pass the rest to others
+        assert_eq!(
+            vec![2, 25, 3],
+            auction.route_fill(&[20, 1000, 30], 0, 30).unwrap()
+        );
+
+        // not enough tokens in all dispensers
+        assert!(auction.route_fill(&[20, 20, 20], 0, 7).is_err());
+        assert_eq!(
+            vec![2, 2, 2],
+            auction.route_fill(&[20, 20, 20], 0, 6).unwrap()
+        );
+
+        auction.basket = true;
+        assert!(!auction.validate().unwrap());
+    }
+
+    #[test]
+    fn route_claim() {
+        let mut auction = get_fixture();
+        auction.pricing.unit_size = 10;
+        auction.num_tokens = 2;
+        auction.tokens[0].ratio = 1;
+        auction.tokens[1].ratio = 1;
+
+        // the selected dispenser covers the claim
+        assert_eq!(vec![4, 0], auction.route_claim(&[50, 30], 0, 4).unwrap());
+
+        // the claim exceeds either dispenser
+        assert_eq!(vec![5, 2], auction.route_claim(&[50, 30], 0, 7).unwrap());
+        assert_eq!(vec![1, 3], auction.route_claim(&[50, 30], 1, 4).unwrap());
+
+        // the claim exceeds both dispensers
+        assert_eq!(vec![5, 3], auction.route_claim(&[50, 30], 1, 10).unwrap());
+
+        // dispensers with zero ratios only serve the claim when selected
+        auction.tokens[1].ratio = 0;
+        assert_eq!(vec![5, 0], auction.route_claim(&[50, 30], 0, 7).unwrap());
+        assert_eq!(vec![4, 3], auction.route_claim(&[50, 30], 1, 7).unwrap());
+
+        assert!(auction.route_claim(&[50], 0, 7).is_err());
+    }
+
+    #[test]
+    fn accepts_limit_bids() {
+        let mut auction = get_fixture();
+        assert!(auction.accepts_limit_bids());
+
+        auction.common.min_raise = 1000;
+        assert!(!auction.accepts_limit_bids());
+        auction.common.min_raise = 0;
+
+        auction.split_fills = true;
+        assert!(!auction.accepts_limit_bids());
+        auction.split_fills = false;
+
+        auction.pricing.pricing_model = PricingModel::BatchAuction;
+        assert!(!auction.accepts_limit_bids());
+    }
+
+    #[test]
+    fn get_basket_units() {
+        let mut auction = get_fixture();
+        auction.basket = true;
+        auction.num_tokens = 3;
+        auction.tokens[0].basket_amount = 100;
+        auction.tokens[1].basket_amount = 50;
+        assert!(!auction.validate().unwrap());
+
+        auction.tokens[2].basket_amount = 1;
+        assert!(auction.validate().unwrap());
+
+        assert_eq!(7, auction.get_basket_units(&[1000, 350, 10]).unwrap());
+        assert_eq!(5, auction.get_basket_units(&[599, 1000, 1000]).unwrap());
+        assert_eq!(0, auction.get_basket_units(&[1000, 1000, 0]).unwrap());
+        assert!(auction.get_basket_units(&[1000, 1000]).is_err());
+
+        auction.randomness.source = crate::state::randomness::RandomnessSource::CommitReveal;
+        assert!(!auction.validate().unwrap());
+    }
+
+    #[test]
+    fn get_auction_price_curve() {
+        let mut auction = get_fixture();
+        auction.pricing.pricing_model = PricingModel::BondingCurve;
+        auction.pricing.start_price = 100;
+        auction.pricing.amount_per_level = 1;
+        auction.pricing.curve_coef = 20_000_000;
+        auction.pricing.curve_function = CurveFunction::Linear;
+        assert!(auction.validate().unwrap());
+
+        assert_eq!(0, auction.get_auction_price(0, 0).unwrap());
+        assert_eq!(101, auction.get_auction_price(1, 0).unwrap());
+        assert_eq!(110, auction.get_auction_price(10, 0).unwrap());
+        assert_eq!(0, auction.get_auction_amount(100, 0).unwrap());
+        assert_eq!(10, auction.get_auction_amount(110, 0).unwrap());
+        assert_eq!(950, auction.get_auction_amount(u64::MAX, 0).unwrap());
+
+        // price moves up the curve with units sold
+        auction.stats.reg_bidders.fills_volume = 40;
+        auction.stats.wl_bidders.fills_volume = 10;
+        assert_eq!(210, auction.get_auction_price(10, 0).unwrap());
+        assert_eq!(900, auction.get_auction_amount(u64::MAX, 0).unwrap());
+
+        // sale halts at max_price
+        auction.stats.reg_bidders.fills_volume = 940;
+        assert_eq!(0, auction.get_auction_amount(u64::MAX, 0).unwrap());
+
+        auction.stats.reg_bidders.fills_volume = 0;
+        auction.stats.wl_bidders.fills_volume = 0;
+        auction.pricing.curve_function = CurveFunction::Exponential;
+        auction.pricing.curve_coef = 693_147_181;
+        assert_eq!(145, auction.get_auction_price(1, 0).unwrap());
+        assert_eq!(4, auction.get_auction_amount(u64::MAX, 0).unwrap());
+
+        auction.pricing.curve_function = CurveFunction::Power;
+        auction.pricing.curve_coef = 2_000_000_000;
+        assert_eq!(234, auction.get_auction_price(1, 0).unwrap());
+        assert_eq!(3, auction.get_auction_amount(u64::MAX, 0).unwrap());
+        assert_eq!(1, auction.get_auction_amount(300, 0).unwrap());
+    }
 }
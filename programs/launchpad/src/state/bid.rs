@@ -1,30 +1,25 @@
-use anchor_lang::prelude::*;
+use {
+    crate::state::vesting::VestingBalance,
+    anchor_lang::{prelude::*, solana_program::hash},
+};
 
-#[derive(Copy, Clone, PartialEq, AnchorSerialize, AnchorDeserialize, Debug)]
+#[derive(Copy, Clone, PartialEq, AnchorSerialize, AnchorDeserialize, Default, Debug)]
 pub enum BidType {
+    #[default]
     Ioc,
     Fok,
+    // escrowed and filled by crank_fills once the auction price drops to the limit
+    Limit,
 }
 
-impl Default for BidType {
-    fn default() -> Self {
-        Self::Ioc
-    }
-}
-
-#[derive(Copy, Clone, PartialEq, AnchorSerialize, AnchorDeserialize, Debug)]
+#[derive(Copy, Clone, PartialEq, AnchorSerialize, AnchorDeserialize, Default, Debug)]
 pub enum BadBidType {
+    #[default]
     None,
     TooEarly,
     FillLimit,
 }
 
-impl Default for BadBidType {
-    fn default() -> Self {
-        Self::None
-    }
-}
-
 #[account]
 #[derive(Default, Debug)]
 pub struct Bid {
@@ -40,9 +35,86 @@ pub struct Bid {
     pub fill_time: i64,
     pub fill_price: u64,
     pub fill_amount: u64,
+    // units dispensed of every token, indexed as Auction::tokens
+    pub token_fills: [u64; 10], // Auction::MAX_TOKENS
+    // payment held by the program until the bid is settled or the soft cap is met
+    pub escrowed: bool,
+    pub escrow_custody: Pubkey,
+    pub escrow_amount: u64,
+    pub escrow_fee: u64,
+    // hash of the sealed bid, see Bid::get_commitment()
+    pub sealed: bool,
+    pub commitment: [u8; 32],
+    // units paid for but not dispensed until the token is picked with
+    // deferred randomness committed at pending_slot, or claimed with
+    // claim_tokens in the deferred claim mode
+    pub pending_amount: u64,
+    pub pending_slot: u64,
+    // tokens locked in the vesting escrow accounts, indexed as Auction::tokens
+    pub vesting: [VestingBalance; 10], // Auction::MAX_TOKENS
     pub bump: u8,
 }
 
 impl Bid {
     pub const LEN: usize = 8 + std::mem::size_of::<Bid>();
+
+    /// Returns the hash that seals bid price and amount until revealed
+    pub fn get_commitment(owner: &Pubkey, price: u64, amount: u64, salt: &[u8; 32]) -> [u8; 32] {
+        hash::hashv(&[
+            owner.as_ref(),
+            &price.to_le_bytes(),
+            &amount.to_le_bytes(),
+            salt,
+        ])
+        .to_bytes()
+    }
+
+    /// Releases escrowed payment and fee for a fill. Transfer fees withheld by
+    /// Token-2022 mints leave less in escrow than the fill price implies, so
+    /// the release is clamped to the remaining escrow.
+    /// Returns released payment and fee.
+    pub fn release_escrow(&mut self, payment_amount: u64, fee_amount: u64) -> (u64, u64) {
+        let payment_amount = std::cmp::min(payment_amount, self.escrow_amount);
+        let fee_amount = std::cmp::min(fee_amount, self.escrow_fee);
+        self.escrow_amount -= payment_amount;
+        self.escrow_fee -= fee_amount;
+        (payment_amount, fee_amount)
+    }
+
+    /// Checks if some of the vested tokens haven't been claimed yet
+    pub fn has_locked_tokens(&self) -> bool {
+        self.vesting
+            .iter()
+            .any(|balance| balance.claimed < balance.amount)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn release_escrow() {
+        // 10 units at 100 with 1% trade fee paid in a pricing mint that
+        // withholds 1% transfer fee, 1010 sent and 1000 received
+        let mut bid = Bid {
+            escrowed: true,
+            escrow_amount: 990,
+            escrow_fee: 10,
+            ..Bid::default()
+        };
+
+        assert_eq!((500, 5), bid.release_escrow(500, 5));
+        assert_eq!((490, 5), bid.release_escrow(500, 5));
+        assert_eq!(0, bid.escrow_amount);
+        assert_eq!(0, bid.escrow_fee);
+        assert_eq!((0, 0), bid.release_escrow(500, 5));
+
+        // fills below the limit price leave the rest for cancel_limit_bid
+        bid.escrow_amount = 990;
+        bid.escrow_fee = 10;
+        assert_eq!((900, 9), bid.release_escrow(900, 9));
+        assert_eq!(90, bid.escrow_amount);
+        assert_eq!(1, bid.escrow_fee);
+    }
 }
@@ -1,4 +1,8 @@
-use {crate::math, anchor_lang::prelude::*, anchor_spl::token::Transfer};
+use {
+    crate::math,
+    anchor_lang::prelude::*,
+    anchor_spl::token_interface::{self, TransferChecked},
+};
 
 #[derive(Copy, Clone, PartialEq, AnchorSerialize, AnchorDeserialize, Default, Debug)]
 pub struct Fee {
@@ -72,27 +76,31 @@ impl Launchpad {
             && self.fees.trade.numerator < self.fees.trade.denominator
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn transfer_tokens<'info>(
         &self,
         from: AccountInfo<'info>,
         to: AccountInfo<'info>,
+        mint: AccountInfo<'info>,
         authority: AccountInfo<'info>,
         token_program: AccountInfo<'info>,
         amount: u64,
+        decimals: u8,
     ) -> Result<()> {
         let authority_seeds: &[&[&[u8]]] =
             &[&[b"transfer_authority", &[self.transfer_authority_bump]]];
 
         let context = CpiContext::new(
             token_program,
-            Transfer {
+            TransferChecked {
                 from,
+                mint,
                 to,
                 authority,
             },
         )
         .with_signer(authority_seeds);
 
-        anchor_spl::token::transfer(context, amount)
+        token_interface::transfer_checked(context, amount, decimals)
     }
 }
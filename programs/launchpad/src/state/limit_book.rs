@@ -0,0 +1,157 @@
+//! Resting limit bids
+
+use {crate::error::LaunchpadError, anchor_lang::prelude::*};
+
+#[zero_copy]
+#[derive(Default, PartialEq, Debug)]
+pub struct LimitOrder {
+    pub bid: Pubkey,
+    pub price: u64,
+    pub bid_time: i64,
+    // number of units left to fill
+    pub amount: u64,
+}
+
+/// Limit bids waiting for the auction price to drop to their limit, sorted
+/// in price-time priority, the order at index 0 is filled first
+#[account(zero_copy)]
+pub struct LimitBook {
+    pub auction: Pubkey,
+    pub num_orders: u64,
+    pub orders: [LimitOrder; 64], // LimitBook::MAX_ORDERS
+    pub bump: u8,
+    // zero-copy accounts can't have implicit padding
+    pub padding: [u8; 7],
+}
+
+impl LimitBook {
+    pub const LEN: usize = 8 + std::mem::size_of::<LimitBook>();
+    pub const MAX_ORDERS: usize = 64;
+
+    /// Inserts the order behind all orders with the same or better price.
+    /// If the book is full, the worst order is evicted to make room for a
+    /// better priced one and returned, its escrow is refunded by cancel_limit_bid.
+    pub fn insert(&mut self, order: LimitOrder) -> Result<Option<LimitOrder>> {
+        let mut num_orders = self.num_orders as usize;
+        let mut evicted = None;
+        if num_orders >= LimitBook::MAX_ORDERS {
+            if self.orders[num_orders - 1].price >= order.price {
+                return err!(LaunchpadError::LimitBookFull);
+            }
+            evicted = Some(self.orders[num_orders - 1]);
+            num_orders -= 1;
+        }
+        let index = self.orders[..num_orders]
+            .iter()
+            .position(|resting| {
+                resting.price < order.price
+                    || (resting.price == order.price && resting.bid_time > order.bid_time)
+            })
+            .unwrap_or(num_orders);
+        self.orders.copy_within(index..num_orders, index + 1);
+        self.orders[index] = order;
+        self.num_orders = num_orders as u64 + 1;
+
+        Ok(evicted)
+    }
+
+    /// Removes the order of the given bid, returns false if it's not in the book
+    pub fn remove(&mut self, bid: &Pubkey) -> bool {
+        let num_orders = self.num_orders as usize;
+        let index = match self.orders[..num_orders]
+            .iter()
+            .position(|order| order.bid == *bid)
+        {
+            Some(index) => index,
+            None => return false,
+        };
+        self.orders.copy_within(index + 1..num_orders, index);
+        self.orders[num_orders - 1] = LimitOrder::default();
+        self.num_orders -= 1;
+
+        true
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn get_fixture() -> LimitBook {
+        LimitBook {
+            auction: Pubkey::default(),
+            num_orders: 0,
+            orders: [LimitOrder::default(); LimitBook::MAX_ORDERS],
+            bump: 0,
+            padding: [0; 7],
+        }
+    }
+
+    fn get_order(price: u64, bid_time: i64) -> LimitOrder {
+        LimitOrder {
+            bid: Pubkey::new_unique(),
+            price,
+            bid_time,
+            amount: 1,
+        }
+    }
+
+    #[test]
+    fn insert() {
+        let mut book = get_fixture();
+
+        let order1 = get_order(100, 10);
+        let order2 = get_order(120, 20);
+        let order3 = get_order(100, 30);
+        let order4 = get_order(100, 5);
+        book.insert(order1).unwrap();
+        book.insert(order2).unwrap();
+        book.insert(order3).unwrap();
+        book.insert(order4).unwrap();
+
+        assert_eq!(4, book.num_orders);
+        assert_eq!(order2, book.orders[0]);
+        assert_eq!(order4, book.orders[1]);
+        assert_eq!(order1, book.orders[2]);
+        assert_eq!(order3, book.orders[3]);
+
+        for i in 4..LimitBook::MAX_ORDERS {
+            book.insert(get_order(50, i as i64)).unwrap();
+        }
+
+        // full book evicts the worst order for a better priced one
+        let worst = book.orders[LimitBook::MAX_ORDERS - 1];
+        let order5 = get_order(60, 100);
+        assert_eq!(Some(worst), book.insert(order5).unwrap());
+        assert_eq!(LimitBook::MAX_ORDERS as u64, book.num_orders);
+        assert_eq!(order5, book.orders[4]);
+        assert!(!book.remove(&worst.bid));
+
+        // same or worse price is rejected
+        assert!(book.insert(get_order(50, 0)).is_err());
+        assert!(book.insert(get_order(40, 0)).is_err());
+    }
+
+    #[test]
+    fn remove() {
+        let mut book = get_fixture();
+
+        let order1 = get_order(100, 10);
+        let order2 = get_order(90, 20);
+        let order3 = get_order(80, 30);
+        book.insert(order1).unwrap();
+        book.insert(order2).unwrap();
+        book.insert(order3).unwrap();
+
+        assert!(book.remove(&order2.bid));
+        assert!(!book.remove(&order2.bid));
+        assert_eq!(2, book.num_orders);
+        assert_eq!(order1, book.orders[0]);
+        assert_eq!(order3, book.orders[1]);
+        assert_eq!(LimitOrder::default(), book.orders[2]);
+
+        assert!(book.remove(&order1.bid));
+        assert!(book.remove(&order3.bid));
+        assert_eq!(0, book.num_orders);
+    }
+}
@@ -8,7 +8,7 @@ use {
 };
 
 #[repr(packed)]
-#[account(zero_copy)]
+#[account(zero_copy(unsafe))]
 #[derive(Default)]
 pub struct Multisig {
     pub num_signers: u8,
@@ -32,6 +32,7 @@ pub enum AdminInstruction {
     SetTestOraclePrice,
     SetTestTime,
     DeleteAuction,
+    SetTestVrf,
 }
 
 impl Multisig {
@@ -56,8 +57,8 @@ impl Multisig {
     }
 
     /// Returns all accounts for the given context
-    pub fn get_account_infos<'info, T: ToAccountInfos<'info>>(
-        ctx: &Context<'_, '_, '_, 'info, T>,
+    pub fn get_account_infos<'info, T: ToAccountInfos<'info> + anchor_lang::Bumps>(
+        ctx: &Context<'_, '_, 'info, 'info, T>,
     ) -> Vec<AccountInfo<'info>> {
         let mut infos = ctx.accounts.to_account_infos();
         infos.extend_from_slice(ctx.remaining_accounts);
@@ -9,19 +9,14 @@ const ORACLE_EXPONENT_SCALE: i32 = -9;
 const ORACLE_PRICE_SCALE: u64 = 1_000_000_000;
 const ORACLE_MAX_PRICE: u64 = (1 << 28) - 1;
 
-#[derive(Copy, Clone, AnchorSerialize, AnchorDeserialize, Debug)]
+#[derive(Copy, Clone, AnchorSerialize, AnchorDeserialize, Default, Debug)]
 pub enum OracleType {
+    #[default]
     None,
     Test,
     Pyth,
 }
 
-impl Default for OracleType {
-    fn default() -> Self {
-        Self::None
-    }
-}
-
 #[derive(Copy, Clone, AnchorSerialize, AnchorDeserialize, Debug)]
 pub struct OraclePrice {
     pub price: u64,
@@ -57,7 +52,7 @@ impl OraclePrice {
     pub fn new_from_oracle(
         oracle_type: OracleType,
         oracle_account: &AccountInfo,
-        max_price_error: f64,
+        max_price_error: u64,
         max_price_age_sec: u32,
         current_time: i64,
     ) -> Result<Self> {
@@ -160,7 +155,7 @@ impl OraclePrice {
     // private helpers
     fn get_test_price(
         test_price_info: &AccountInfo,
-        max_price_error: f64,
+        max_price_error: u64,
         max_price_age_sec: u32,
         current_time: i64,
     ) -> Result<OraclePrice> {
@@ -169,7 +164,12 @@ impl OraclePrice {
             LaunchpadError::InvalidOracleAccount
         );
 
-        let oracle_acc = Account::<TestOracle>::try_from(test_price_info)?;
+        require_keys_eq!(
+            *test_price_info.owner,
+            crate::ID,
+            LaunchpadError::InvalidOracleAccount
+        );
+        let oracle_acc = TestOracle::try_deserialize(&mut &test_price_info.try_borrow_data()?[..])?;
 
         let last_update_age_sec = math::checked_sub(current_time, oracle_acc.publish_time)?;
         if last_update_age_sec > max_price_age_sec as i64 {
@@ -178,8 +178,8 @@ impl OraclePrice {
         }
 
         if oracle_acc.price == 0
-            || math::checked_float_div(oracle_acc.conf as f64, oracle_acc.price as f64)?
-                > max_price_error
+            || math::checked_fixed_div(oracle_acc.conf as u128, oracle_acc.price as u128)?
+                > max_price_error as u128
         {
             msg!("Error: Test oracle price is out of bounds");
             return err!(LaunchpadError::InvalidOraclePrice);
@@ -194,7 +194,7 @@ impl OraclePrice {
 
     fn get_pyth_price(
         pyth_price_info: &AccountInfo,
-        max_price_error: f64,
+        max_price_error: u64,
         max_price_age_sec: u32,
         current_time: i64,
     ) -> Result<OraclePrice> {
@@ -204,19 +204,17 @@ impl OraclePrice {
         );
         let price_feed = pyth_sdk_solana::load_price_feed_from_account_info(pyth_price_info)
             .map_err(|_| LaunchpadError::InvalidOracleAccount)?;
-        let pyth_price = price_feed
-            .get_current_price()
-            .ok_or(LaunchpadError::InvalidOracleState)?;
+        let pyth_price = price_feed.get_price_unchecked();
 
-        let last_update_age_sec = math::checked_sub(current_time, price_feed.publish_time)?;
+        let last_update_age_sec = math::checked_sub(current_time, pyth_price.publish_time)?;
         if last_update_age_sec > max_price_age_sec as i64 {
             msg!("Error: Pyth oracle price is stale");
             return err!(LaunchpadError::StaleOraclePrice);
         }
 
         if pyth_price.price <= 0
-            || math::checked_float_div(pyth_price.conf as f64, pyth_price.price as f64)?
-                > max_price_error
+            || math::checked_fixed_div(pyth_price.conf as u128, pyth_price.price as u128)?
+                > max_price_error as u128
         {
             msg!("Error: Pyth oracle price is out of bounds");
             return err!(LaunchpadError::InvalidOraclePrice);
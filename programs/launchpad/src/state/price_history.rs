@@ -0,0 +1,133 @@
+//! Auction price history
+
+use {
+    crate::{error::LaunchpadError, math},
+    anchor_lang::prelude::*,
+};
+
+#[zero_copy]
+#[derive(Default, PartialEq, Debug)]
+pub struct Candle {
+    pub start_time: i64,
+    pub open: u64,
+    pub high: u64,
+    pub low: u64,
+    pub close: u64,
+    // number of units filled
+    pub volume: u64,
+}
+
+/// Ring buffer of the most recent fill candles, the candle at last_index is
+/// the latest one. Buckets without fills don't get a candle.
+#[account(zero_copy)]
+pub struct PriceHistory {
+    pub auction: Pubkey,
+    pub candle_duration: i64,
+    pub num_candles: u64,
+    pub last_index: u64,
+    pub candles: [Candle; 200], // PriceHistory::MAX_CANDLES
+    pub bump: u8,
+    // zero-copy accounts can't have implicit padding
+    pub padding: [u8; 7],
+}
+
+impl PriceHistory {
+    pub const LEN: usize = 8 + std::mem::size_of::<PriceHistory>();
+    pub const MAX_CANDLES: usize = 200;
+
+    /// Adds the fill to the candle of the current time bucket
+    pub fn record_fill(&mut self, price: u64, amount: u64, curtime: i64) -> Result<()> {
+        require_gt!(
+            self.candle_duration,
+            0i64,
+            LaunchpadError::InvalidAuctionConfig
+        );
+        let start_time = math::checked_sub(curtime, curtime.rem_euclid(self.candle_duration))?;
+
+        if self.num_candles > 0 {
+            let candle = &mut self.candles[self.last_index as usize];
+            if candle.start_time == start_time {
+                candle.high = std::cmp::max(candle.high, price);
+                candle.low = std::cmp::min(candle.low, price);
+                candle.close = price;
+                candle.volume = math::checked_add(candle.volume, amount)?;
+                return Ok(());
+            }
+            self.last_index = (self.last_index + 1) % PriceHistory::MAX_CANDLES as u64;
+        }
+
+        self.candles[self.last_index as usize] = Candle {
+            start_time,
+            open: price,
+            high: price,
+            low: price,
+            close: price,
+            volume: amount,
+        };
+        if self.num_candles < PriceHistory::MAX_CANDLES as u64 {
+            self.num_candles += 1;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn get_fixture() -> PriceHistory {
+        PriceHistory {
+            auction: Pubkey::default(),
+            candle_duration: 60,
+            num_candles: 0,
+            last_index: 0,
+            candles: [Candle::default(); PriceHistory::MAX_CANDLES],
+            bump: 0,
+            padding: [0; 7],
+        }
+    }
+
+    #[test]
+    fn record_fill() {
+        let mut history = get_fixture();
+
+        history.record_fill(100, 5, 125).unwrap();
+        history.record_fill(120, 1, 130).unwrap();
+        history.record_fill(90, 2, 179).unwrap();
+        assert_eq!(1, history.num_candles);
+        assert_eq!(
+            Candle {
+                start_time: 120,
+                open: 100,
+                high: 120,
+                low: 90,
+                close: 90,
+                volume: 8,
+            },
+            history.candles[0]
+        );
+
+        // empty buckets are skipped
+        history.record_fill(110, 3, 300).unwrap();
+        assert_eq!(2, history.num_candles);
+        assert_eq!(1, history.last_index);
+        assert_eq!(300, history.candles[1].start_time);
+        assert_eq!(3, history.candles[1].volume);
+    }
+
+    #[test]
+    fn record_fill_wraps_around() {
+        let mut history = get_fixture();
+
+        for i in 0..PriceHistory::MAX_CANDLES as i64 + 5 {
+            history.record_fill(100 + i as u64, 1, i * 60).unwrap();
+        }
+        assert_eq!(PriceHistory::MAX_CANDLES as u64, history.num_candles);
+        assert_eq!(4, history.last_index);
+        assert_eq!(104 + PriceHistory::MAX_CANDLES as u64, {
+            history.candles[4].close
+        });
+        assert_eq!(105, history.candles[5].open);
+    }
+}
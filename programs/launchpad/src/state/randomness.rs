@@ -0,0 +1,405 @@
+//! Randomness sources for picking dispensed tokens
+
+use {
+    crate::{error::LaunchpadError, state},
+    anchor_lang::{prelude::*, solana_program::hash},
+    solana_program::{pubkey, sysvar},
+};
+
+// number of slots between the bid and the slot which hash is used to pick the token
+pub const REVEAL_SLOT_DELAY: u64 = 1;
+
+// Switchboard on-demand program ids (mainnet, devnet)
+pub const SWITCHBOARD_PROGRAM_IDS: [Pubkey; 2] = [
+    pubkey!("SBondMDrcV3K4kxZR1HNVT7osZxAHVHgYXL5Ze1oMUv"),
+    pubkey!("Aio4gaXjXzJNVLtzwtNVmSqGKpANtXhybbkhtAC94ji2"),
+];
+// discriminator of the Switchboard on-demand RandomnessAccountData
+pub const SWITCHBOARD_RANDOMNESS_DISCRIMINATOR: [u8; 8] = [10, 66, 229, 135, 220, 239, 217, 114];
+// field offsets of RandomnessAccountData as laid out by switchboard-on-demand 0.1:
+// discriminator, authority, queue, seed_slothash, seed_slot, oracle, reveal_slot, value
+pub const SWITCHBOARD_SEED_SLOT_OFFSET: usize = 8 + 32 + 32 + 32;
+pub const SWITCHBOARD_REVEAL_SLOT_OFFSET: usize = SWITCHBOARD_SEED_SLOT_OFFSET + 8 + 32;
+pub const SWITCHBOARD_VALUE_OFFSET: usize = SWITCHBOARD_REVEAL_SLOT_OFFSET + 8;
+
+#[derive(Copy, Clone, PartialEq, AnchorSerialize, AnchorDeserialize, Default, Debug)]
+pub enum RandomnessSource {
+    // token is picked upon the bid from the most recent slot hash
+    #[default]
+    SlotHash,
+    // bid commits to a later slot, the token is picked from its hash upon dispense_tokens,
+    // the leader of that slot can bias the pick by withholding its block, so
+    // high-value draws should use Vrf
+    CommitReveal,
+    // token is picked from the VRF output produced after the bid upon dispense_tokens
+    Vrf,
+}
+
+#[derive(Copy, Clone, PartialEq, AnchorSerialize, AnchorDeserialize, Default, Debug)]
+pub enum VrfOracleType {
+    #[default]
+    None,
+    Test,
+    Switchboard,
+}
+
+#[derive(Copy, Clone, PartialEq, AnchorSerialize, AnchorDeserialize, Default, Debug)]
+pub struct RandomnessParams {
+    pub source: RandomnessSource,
+    // VRF oracle type and account, unused by slot hash based sources
+    pub vrf_oracle_type: VrfOracleType,
+    pub vrf_account: Pubkey,
+}
+
+#[account]
+#[derive(Default, Debug)]
+pub struct TestVrf {
+    pub randomness: [u8; 32],
+    // slot the randomness has been produced at
+    pub slot: u64,
+}
+
+impl TestVrf {
+    pub const LEN: usize = 8 + std::mem::size_of::<TestVrf>();
+}
+
+#[derive(Copy, Clone, PartialEq, Default, Debug)]
+pub struct VrfOutput {
+    pub randomness: [u8; 32],
+    // the first slot which hash the randomness depends on, the randomness
+    // can't be predicted before that slot
+    pub seed_slot: u64,
+}
+
+impl VrfOutput {
+    /// Loads the VRF output from the oracle account. Returns None if the
+    /// randomness hasn't been revealed yet.
+    pub fn new_from_oracle(
+        oracle_type: VrfOracleType,
+        vrf_account: &AccountInfo,
+    ) -> Result<Option<Self>> {
+        require!(
+            !state::is_empty_account(vrf_account)?,
+            LaunchpadError::InvalidVrfAccount
+        );
+        match oracle_type {
+            VrfOracleType::Test => Self::get_test_output(vrf_account),
+            VrfOracleType::Switchboard => Self::get_switchboard_output(vrf_account),
+            _ => err!(LaunchpadError::UnsupportedRandomnessSource),
+        }
+    }
+
+    // private helpers
+    fn get_test_output(vrf_account: &AccountInfo) -> Result<Option<Self>> {
+        require_keys_eq!(
+            *vrf_account.owner,
+            crate::ID,
+            LaunchpadError::InvalidVrfAccount
+        );
+        let vrf = TestVrf::try_deserialize(&mut &vrf_account.try_borrow_data()?[..])?;
+
+        Ok(Some(Self {
+            randomness: vrf.randomness,
+            seed_slot: vrf.slot,
+        }))
+    }
+
+    fn get_switchboard_output(vrf_account: &AccountInfo) -> Result<Option<Self>> {
+        require!(
+            SWITCHBOARD_PROGRAM_IDS.contains(vrf_account.owner),
+            LaunchpadError::InvalidVrfAccount
+        );
+        let data = vrf_account.try_borrow_data()?;
+        require!(
+            data.len() >= 8 && data[..8] == SWITCHBOARD_RANDOMNESS_DISCRIMINATOR,
+            LaunchpadError::InvalidVrfAccount
+        );
+        require!(
+            data.len() >= SWITCHBOARD_VALUE_OFFSET + 32,
+            LaunchpadError::InvalidVrfAccount
+        );
+
+        let read_slot =
+            |offset: usize| u64::from_le_bytes(data[offset..offset + 8].try_into().unwrap());
+        if read_slot(SWITCHBOARD_REVEAL_SLOT_OFFSET) == 0 {
+            return Ok(None);
+        }
+
+        Ok(Some(Self {
+            randomness: data[SWITCHBOARD_VALUE_OFFSET..SWITCHBOARD_VALUE_OFFSET + 32]
+                .try_into()
+                .unwrap(),
+            seed_slot: read_slot(SWITCHBOARD_SEED_SLOT_OFFSET),
+        }))
+    }
+}
+
+impl RandomnessParams {
+    pub fn validate(&self) -> bool {
+        match self.source {
+            RandomnessSource::SlotHash | RandomnessSource::CommitReveal => {
+                self.vrf_oracle_type == VrfOracleType::None && self.vrf_account == Pubkey::default()
+            }
+            RandomnessSource::Vrf => {
+                self.vrf_oracle_type != VrfOracleType::None && self.vrf_account != Pubkey::default()
+            }
+        }
+    }
+
+    /// Checks if the token is picked after the bid by dispense_tokens
+    pub fn is_deferred(&self) -> bool {
+        self.source != RandomnessSource::SlotHash
+    }
+
+    /// Returns the account dispense_tokens reads randomness from
+    pub fn get_randomness_account(&self) -> Pubkey {
+        match self.source {
+            RandomnessSource::SlotHash | RandomnessSource::CommitReveal => {
+                sysvar::slot_hashes::id()
+            }
+            RandomnessSource::Vrf => self.vrf_account,
+        }
+    }
+
+    /// Returns randomness that wasn't known at the time of the commit slot.
+    /// Returns None if it is not available, commit-reveal slot hashes are only
+    /// kept by the SlotHashes sysvar for the most recent 512 slots.
+    pub fn get_randomness(
+        &self,
+        randomness_account: &AccountInfo,
+        commit_slot: u64,
+        current_slot: u64,
+    ) -> Result<Option<[u8; 32]>> {
+        match self.source {
+            RandomnessSource::CommitReveal => {
+                let reveal_slot = commit_slot.saturating_add(REVEAL_SLOT_DELAY);
+                require_gt!(
+                    current_slot,
+                    reveal_slot,
+                    LaunchpadError::RandomnessNotAvailable
+                );
+                get_slot_hash(randomness_account, reveal_slot)
+            }
+            RandomnessSource::Vrf => {
+                // the randomness must be seeded after the commit
+                let vrf = VrfOutput::new_from_oracle(self.vrf_oracle_type, randomness_account)?
+                    .ok_or(LaunchpadError::RandomnessNotAvailable)?;
+                require_gt!(
+                    vrf.seed_slot,
+                    commit_slot,
+                    LaunchpadError::RandomnessNotAvailable
+                );
+                Ok(Some(vrf.randomness))
+            }
+            RandomnessSource::SlotHash => err!(LaunchpadError::UnsupportedRandomnessSource),
+        }
+    }
+}
+
+/// Returns the hash of the given slot from the SlotHashes sysvar
+pub fn get_slot_hash(recent_slothashes: &AccountInfo, slot: u64) -> Result<Option<[u8; 32]>> {
+    const ENTRY_LEN: usize = 40;
+
+    let slothashes_data = recent_slothashes.try_borrow_data()?;
+    if slothashes_data.len() < 8 {
+        return Err(ProgramError::InvalidAccountData.into());
+    }
+    let num_entries = u64::from_le_bytes(slothashes_data[..8].try_into().unwrap()) as usize;
+    if slothashes_data.len() < 8 + num_entries * ENTRY_LEN {
+        return Err(ProgramError::InvalidAccountData.into());
+    }
+
+    // entries are sorted by slot in descending order
+    for entry in slothashes_data[8..8 + num_entries * ENTRY_LEN].chunks_exact(ENTRY_LEN) {
+        let entry_slot = u64::from_le_bytes(entry[..8].try_into().unwrap());
+        if entry_slot == slot {
+            return Ok(Some(entry[8..].try_into().unwrap()));
+        }
+        if entry_slot < slot {
+            break;
+        }
+    }
+
+    Ok(None)
+}
+
+/// Derives the bid specific random seed, so bids sharing the randomness
+/// get independent tokens
+pub fn get_bid_seed(randomness: &[u8; 32], bid: &Pubkey) -> u64 {
+    let seed = hash::hashv(&[randomness, bid.as_ref()]).to_bytes();
+    u64::from_le_bytes(seed[..8].try_into().unwrap())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn get_slot_hashes_data(slots: &[u64]) -> Vec<u8> {
+        let mut data = (slots.len() as u64).to_le_bytes().to_vec();
+        for slot in slots {
+            data.extend_from_slice(&slot.to_le_bytes());
+            data.extend_from_slice(&[*slot as u8; 32]);
+        }
+        data
+    }
+
+    #[test]
+    fn get_slot_hash() {
+        let key = sysvar::slot_hashes::id();
+        let mut lamports = 0;
+        let mut data = get_slot_hashes_data(&[105, 104, 102, 101]);
+        let owner = sysvar::id();
+        let account = AccountInfo::new(
+            &key,
+            false,
+            false,
+            &mut lamports,
+            &mut data,
+            &owner,
+            false,
+            0,
+        );
+
+        assert_eq!(
+            Some([104; 32]),
+            super::get_slot_hash(&account, 104).unwrap()
+        );
+        assert_eq!(
+            Some([101; 32]),
+            super::get_slot_hash(&account, 101).unwrap()
+        );
+        // skipped, expired or future slots
+        assert_eq!(None, super::get_slot_hash(&account, 103).unwrap());
+        assert_eq!(None, super::get_slot_hash(&account, 100).unwrap());
+        assert_eq!(None, super::get_slot_hash(&account, 106).unwrap());
+    }
+
+    #[test]
+    fn get_bid_seed() {
+        let bid1 = Pubkey::new_unique();
+        let bid2 = Pubkey::new_unique();
+        assert_eq!(
+            super::get_bid_seed(&[1; 32], &bid1),
+            super::get_bid_seed(&[1; 32], &bid1)
+        );
+        assert_ne!(
+            super::get_bid_seed(&[1; 32], &bid1),
+            super::get_bid_seed(&[1; 32], &bid2)
+        );
+        assert_ne!(
+            super::get_bid_seed(&[1; 32], &bid1),
+            super::get_bid_seed(&[2; 32], &bid1)
+        );
+    }
+
+    fn get_switchboard_data(seed_slot: u64, reveal_slot: u64) -> Vec<u8> {
+        let mut data = vec![0; SWITCHBOARD_VALUE_OFFSET + 256];
+        data[..8].copy_from_slice(&SWITCHBOARD_RANDOMNESS_DISCRIMINATOR);
+        data[SWITCHBOARD_SEED_SLOT_OFFSET..SWITCHBOARD_SEED_SLOT_OFFSET + 8]
+            .copy_from_slice(&seed_slot.to_le_bytes());
+        data[SWITCHBOARD_REVEAL_SLOT_OFFSET..SWITCHBOARD_REVEAL_SLOT_OFFSET + 8]
+            .copy_from_slice(&reveal_slot.to_le_bytes());
+        data[SWITCHBOARD_VALUE_OFFSET..SWITCHBOARD_VALUE_OFFSET + 32].copy_from_slice(&[7; 32]);
+        data
+    }
+
+    #[test]
+    fn get_switchboard_randomness() {
+        let params = RandomnessParams {
+            source: RandomnessSource::Vrf,
+            vrf_oracle_type: VrfOracleType::Switchboard,
+            vrf_account: Pubkey::new_unique(),
+        };
+        let mut lamports = 1;
+        let mut data = get_switchboard_data(105, 106);
+        let owner = SWITCHBOARD_PROGRAM_IDS[0];
+        let account = AccountInfo::new(
+            &params.vrf_account,
+            false,
+            false,
+            &mut lamports,
+            &mut data,
+            &owner,
+            false,
+            0,
+        );
+
+        assert_eq!(
+            Some([7; 32]),
+            params.get_randomness(&account, 104, 110).unwrap()
+        );
+        // seeded before the commit
+        assert!(params.get_randomness(&account, 105, 110).is_err());
+
+        // not revealed yet
+        let mut lamports = 1;
+        let mut data = get_switchboard_data(105, 0);
+        let account = AccountInfo::new(
+            &params.vrf_account,
+            false,
+            false,
+            &mut lamports,
+            &mut data,
+            &owner,
+            false,
+            0,
+        );
+        assert!(params.get_randomness(&account, 104, 110).is_err());
+
+        // not a randomness account
+        let mut lamports = 1;
+        let mut data = get_switchboard_data(105, 106);
+        data[0] = 0;
+        let account = AccountInfo::new(
+            &params.vrf_account,
+            false,
+            false,
+            &mut lamports,
+            &mut data,
+            &owner,
+            false,
+            0,
+        );
+        assert!(params.get_randomness(&account, 104, 110).is_err());
+
+        // owned by another program
+        let mut lamports = 1;
+        let mut data = get_switchboard_data(105, 106);
+        let owner = Pubkey::new_unique();
+        let account = AccountInfo::new(
+            &params.vrf_account,
+            false,
+            false,
+            &mut lamports,
+            &mut data,
+            &owner,
+            false,
+            0,
+        );
+        assert!(params.get_randomness(&account, 104, 110).is_err());
+    }
+
+    #[test]
+    fn validate() {
+        let mut params = RandomnessParams::default();
+        assert!(params.validate());
+        assert!(!params.is_deferred());
+
+        params.source = RandomnessSource::Vrf;
+        assert!(!params.validate());
+        params.vrf_account = Pubkey::new_unique();
+        assert!(!params.validate());
+        params.vrf_oracle_type = VrfOracleType::Switchboard;
+        assert!(params.validate());
+        assert!(params.is_deferred());
+        assert_eq!(params.vrf_account, params.get_randomness_account());
+
+        params.source = RandomnessSource::CommitReveal;
+        assert!(!params.validate());
+        params.vrf_account = Pubkey::default();
+        assert!(!params.validate());
+        params.vrf_oracle_type = VrfOracleType::None;
+        assert!(params.validate());
+        assert_eq!(sysvar::slot_hashes::id(), params.get_randomness_account());
+    }
+}
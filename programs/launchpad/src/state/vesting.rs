@@ -0,0 +1,141 @@
+//! Vesting of purchased tokens
+
+use {crate::math, anchor_lang::prelude::*};
+
+#[derive(Copy, Clone, PartialEq, AnchorSerialize, AnchorDeserialize, Default, Debug)]
+pub struct VestingParams {
+    // seconds after the claim time (TGE) before linear unlocking starts
+    pub cliff: i64,
+    // seconds over which the locked amount unlocks linearly after the cliff
+    pub duration: i64,
+    // percentage of the amount unlocked at the claim time
+    pub tge_unlock: u64,
+}
+
+#[derive(Copy, Clone, PartialEq, AnchorSerialize, AnchorDeserialize, Default, Debug)]
+pub struct VestingBalance {
+    // total amount of the token moved to the vesting escrow
+    pub amount: u64,
+    pub claimed: u64,
+}
+
+impl VestingParams {
+    pub const PERCENTAGE_DENOMINATOR: u64 = 100;
+
+    pub fn validate(&self) -> bool {
+        if !self.is_enabled() {
+            return self.tge_unlock == 0;
+        }
+        self.cliff >= 0
+            && self.duration >= 0
+            && self.tge_unlock <= VestingParams::PERCENTAGE_DENOMINATOR
+    }
+
+    /// Checks if purchased tokens are locked in the vesting escrow
+    pub fn is_enabled(&self) -> bool {
+        self.cliff != 0 || self.duration != 0
+    }
+
+    /// Returns the portion of the vested amount unlocked at the given time
+    pub fn get_unlocked_amount(&self, amount: u64, start_time: i64, curtime: i64) -> Result<u64> {
+        if curtime < start_time {
+            return Ok(0);
+        }
+        let tge_amount = math::checked_as_u64(math::checked_div(
+            math::checked_mul(amount as u128, self.tge_unlock as u128)?,
+            VestingParams::PERCENTAGE_DENOMINATOR as u128,
+        )?)?;
+
+        let elapsed = math::checked_sub(math::checked_sub(curtime, start_time)?, self.cliff)?;
+        if elapsed < 0 {
+            return Ok(tge_amount);
+        }
+        if elapsed >= self.duration {
+            return Ok(amount);
+        }
+
+        let locked_amount = math::checked_sub(amount, tge_amount)?;
+        math::checked_add(
+            tge_amount,
+            math::checked_as_u64(math::checked_div(
+                math::checked_mul(locked_amount as u128, elapsed as u128)?,
+                self.duration as u128,
+            )?)?,
+        )
+    }
+}
+
+impl VestingBalance {
+    /// Returns the unlocked amount that hasn't been claimed yet
+    pub fn get_claimable_amount(
+        &self,
+        params: &VestingParams,
+        start_time: i64,
+        curtime: i64,
+    ) -> Result<u64> {
+        Ok(params
+            .get_unlocked_amount(self.amount, start_time, curtime)?
+            .saturating_sub(self.claimed))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn validate() {
+        let mut params = VestingParams::default();
+        assert!(params.validate());
+        assert!(!params.is_enabled());
+
+        params.tge_unlock = 10;
+        assert!(!params.validate());
+
+        params.duration = 1000;
+        assert!(params.validate());
+        assert!(params.is_enabled());
+
+        params.tge_unlock = 101;
+        assert!(!params.validate());
+
+        params.tge_unlock = 10;
+        params.cliff = -1;
+        assert!(!params.validate());
+    }
+
+    #[test]
+    fn get_unlocked_amount() {
+        let params = VestingParams {
+            cliff: 100,
+            duration: 1000,
+            tge_unlock: 10,
+        };
+
+        assert_eq!(0, params.get_unlocked_amount(1000, 500, 499).unwrap());
+        assert_eq!(100, params.get_unlocked_amount(1000, 500, 500).unwrap());
+        assert_eq!(100, params.get_unlocked_amount(1000, 500, 600).unwrap());
+        assert_eq!(550, params.get_unlocked_amount(1000, 500, 1100).unwrap());
+        assert_eq!(1000, params.get_unlocked_amount(1000, 500, 1600).unwrap());
+        assert_eq!(1000, params.get_unlocked_amount(1000, 500, 5000).unwrap());
+
+        // cliff only
+        let params = VestingParams {
+            cliff: 100,
+            duration: 0,
+            tge_unlock: 0,
+        };
+        assert_eq!(0, params.get_unlocked_amount(1000, 500, 599).unwrap());
+        assert_eq!(1000, params.get_unlocked_amount(1000, 500, 600).unwrap());
+
+        let balance = VestingBalance {
+            amount: 1000,
+            claimed: 300,
+        };
+        assert_eq!(0, balance.get_claimable_amount(&params, 500, 599).unwrap());
+        assert_eq!(
+            700,
+            balance.get_claimable_amount(&params, 500, 600).unwrap()
+        );
+    }
+}
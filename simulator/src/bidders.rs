@@ -0,0 +1,121 @@
+//! Recorded and synthetic bid streams
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct SimBid {
+    pub time: i64,
+    // maximum price the bidder is willing to pay per unit
+    pub price: u64,
+    pub amount: u64,
+    pub whitelisted: bool,
+}
+
+/// Parses a recorded bid stream, one `time,price,amount,whitelisted` record
+/// per line. Empty lines and lines starting with '#' are skipped.
+pub fn parse_bids(input: &str) -> Result<Vec<SimBid>, String> {
+    let mut bids = vec![];
+    for (line_num, line) in input.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let fields: Vec<&str> = line.split(',').map(|field| field.trim()).collect();
+        if fields.len() != 4 {
+            return Err(format!(
+                "Line {}: expected 4 fields, got {}",
+                line_num + 1,
+                fields.len()
+            ));
+        }
+        let error = |field: &str| format!("Line {}: invalid {}", line_num + 1, field);
+        bids.push(SimBid {
+            time: fields[0].parse().map_err(|_| error("time"))?,
+            price: fields[1].parse().map_err(|_| error("price"))?,
+            amount: fields[2].parse().map_err(|_| error("amount"))?,
+            whitelisted: match fields[3] {
+                "1" | "true" => true,
+                "0" | "false" => false,
+                _ => return Err(error("whitelisted flag")),
+            },
+        });
+    }
+    Ok(bids)
+}
+
+/// Generates bids with arrival times, prices and amounts uniformly
+/// distributed over the given ranges. The same seed yields the same stream.
+#[derive(Copy, Clone, Debug)]
+pub struct SyntheticStream {
+    pub seed: u64,
+    pub num_bids: usize,
+    pub start_time: i64,
+    pub end_time: i64,
+    pub min_price: u64,
+    pub max_price: u64,
+    pub min_amount: u64,
+    pub max_amount: u64,
+    // percentage of bids placed by whitelisted addresses
+    pub whitelisted_share: u64,
+}
+
+impl Default for SyntheticStream {
+    fn default() -> Self {
+        Self {
+            seed: 1,
+            num_bids: 1000,
+            start_time: 0,
+            end_time: 0,
+            min_price: 1,
+            max_price: 1000,
+            min_amount: 1,
+            max_amount: 10,
+            whitelisted_share: 10,
+        }
+    }
+}
+
+impl SyntheticStream {
+    pub fn generate(&self) -> Vec<SimBid> {
+        let mut rng = XorShift::new(self.seed);
+        let mut bids: Vec<SimBid> = (0..self.num_bids)
+            .map(|_| SimBid {
+                time: self.start_time
+                    + rng.next_in_range(0, self.end_time.saturating_sub(self.start_time) as u64)
+                        as i64,
+                price: rng.next_in_range(self.min_price, self.max_price),
+                amount: rng.next_in_range(self.min_amount, self.max_amount),
+                whitelisted: rng.next_in_range(0, 99) < self.whitelisted_share,
+            })
+            .collect();
+        bids.sort_by_key(|bid| bid.time);
+        bids
+    }
+}
+
+// xorshift64* generator, good enough for synthetic streams
+struct XorShift(u64);
+
+impl XorShift {
+    fn new(seed: u64) -> Self {
+        // zero state would get stuck
+        Self(seed.max(1))
+    }
+
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545_f491_4f6c_dd1d)
+    }
+
+    // returns a value in [low, high]
+    fn next_in_range(&mut self, low: u64, high: u64) -> u64 {
+        if high <= low {
+            return low;
+        }
+        let span = high - low;
+        if span == u64::MAX {
+            return self.next();
+        }
+        low + self.next() % (span + 1)
+    }
+}
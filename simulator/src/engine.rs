@@ -0,0 +1,285 @@
+//! Runs bid streams through the auction pricing code
+
+use {
+    crate::{
+        bidders::SimBid,
+        report::{Fill, SimulationReport},
+    },
+    anchor_lang::prelude::*,
+    solana_launchpad::{
+        error::LaunchpadError,
+        math,
+        state::auction::{Auction, AuctionStats, PricingModel},
+    },
+};
+
+/// Sells up to `supply` units with the given auction config. Bids are
+/// processed in time order the same way place_bid does, batch auction bids
+/// are settled and filled at the auction end. Per-address fill limits are
+/// not enforced since bids are anonymous.
+pub fn simulate(auction: &Auction, supply: u64, bids: &[SimBid]) -> Result<SimulationReport> {
+    let mut auction = auction.clone();
+    require!(
+        auction.pricing.validate() && auction.common.validate(auction.get_start_time(true)),
+        LaunchpadError::InvalidAuctionConfig
+    );
+    auction.stats = AuctionStats::default();
+    auction.stats.wl_bidders.min_fill_price = u64::MAX;
+    auction.stats.reg_bidders.min_fill_price = u64::MAX;
+
+    let mut bids = bids.to_vec();
+    bids.sort_by_key(|bid| bid.time);
+
+    let mut report = SimulationReport {
+        supply,
+        num_bids: bids.len(),
+        ..Default::default()
+    };
+
+    match auction.pricing.pricing_model {
+        PricingModel::Fixed
+        | PricingModel::DynamicDutchAuction
+        | PricingModel::BondingCurve
+        | PricingModel::WeightedPool => {
+            simulate_continuous(&mut auction, supply, &bids, &mut report)?
+        }
+        PricingModel::BatchAuction => simulate_batch(&mut auction, supply, &bids, &mut report)?,
+        PricingModel::EnglishAuction => return err!(LaunchpadError::UnsupportedPricingModel),
+    }
+
+    let stats = &auction.stats;
+    report.units_sold = math::checked_add(
+        stats.wl_bidders.fills_volume,
+        stats.reg_bidders.fills_volume,
+    )?;
+    report.revenue = math::checked_add(
+        stats.wl_bidders.weighted_fills_sum,
+        stats.reg_bidders.weighted_fills_sum,
+    )?;
+    report.wl_bidders = stats.wl_bidders;
+    report.reg_bidders = stats.reg_bidders;
+
+    Ok(report)
+}
+
+fn simulate_continuous(
+    auction: &mut Auction,
+    supply: u64,
+    bids: &[SimBid],
+    report: &mut SimulationReport,
+) -> Result<()> {
+    let mut remaining = supply;
+    for bid in bids {
+        if !is_valid_bid(auction, bid) {
+            report.rejected_bids += 1;
+            continue;
+        }
+        let avail_amount = auction.get_hard_cap_amount(
+            std::cmp::min(auction.get_auction_amount(bid.price, bid.time)?, remaining),
+            bid.time,
+            0,
+        )?;
+        if avail_amount == 0 {
+            report.unfilled_bids += 1;
+            continue;
+        }
+        let fill_amount = std::cmp::min(avail_amount, bid.amount);
+        let fill_price = auction.get_auction_price(fill_amount, bid.time)?;
+        if fill_price > bid.price {
+            report.unfilled_bids += 1;
+            continue;
+        }
+
+        auction.update_stats(fill_amount, fill_price, 0, bid.whitelisted, bid.time)?;
+        report.price_path.push(Fill {
+            time: bid.time,
+            price: fill_price,
+            amount: fill_amount,
+            whitelisted: bid.whitelisted,
+        });
+
+        remaining -= fill_amount;
+        if remaining == 0 || auction.is_hard_cap_met() {
+            report.sell_out_time = Some(bid.time);
+        }
+    }
+    Ok(())
+}
+
+fn simulate_batch(
+    auction: &mut Auction,
+    supply: u64,
+    bids: &[SimBid],
+    report: &mut SimulationReport,
+) -> Result<()> {
+    // collect demand
+    let mut accepted = vec![];
+    for bid in bids {
+        if !is_valid_bid(auction, bid) {
+            report.rejected_bids += 1;
+            continue;
+        }
+        // bids above max_price are placed at max_price
+        let price = std::cmp::min(bid.price, auction.pricing.max_price);
+        let level = match auction.get_price_level(price) {
+            Ok(level) => level,
+            Err(_) => {
+                report.rejected_bids += 1;
+                continue;
+            }
+        };
+        auction.add_demand(level, bid.amount)?;
+        accepted.push((bid, auction.get_level_price(level)?));
+    }
+
+    // settle and fill at the clearing price
+    let settle_time = std::cmp::max(auction.get_end_time(true), auction.common.reveal_end_time);
+    auction.settle(supply, settle_time)?;
+    let clearing_price = auction.stats.clearing.clearing_price;
+    let mut remaining = supply;
+    for (bid, bid_price) in accepted {
+        let fill_amount = std::cmp::min(
+            auction.get_clearing_fill_amount(bid_price, bid.amount)?,
+            remaining,
+        );
+        if fill_amount == 0 {
+            report.unfilled_bids += 1;
+            continue;
+        }
+        auction.update_stats(fill_amount, clearing_price, 0, bid.whitelisted, settle_time)?;
+        report.price_path.push(Fill {
+            time: settle_time,
+            price: clearing_price,
+            amount: fill_amount,
+            whitelisted: bid.whitelisted,
+        });
+        remaining -= fill_amount;
+    }
+    if remaining == 0 {
+        report.sell_out_time = Some(settle_time);
+    }
+    Ok(())
+}
+
+fn is_valid_bid(auction: &Auction, bid: &SimBid) -> bool {
+    let order_amount_limit = if bid.whitelisted {
+        std::cmp::max(
+            auction.common.order_limit_wl_address,
+            auction.common.order_limit_reg_address,
+        )
+    } else {
+        auction.common.order_limit_reg_address
+    };
+    bid.amount > 0
+        && bid.amount <= order_amount_limit
+        && bid.price >= auction.pricing.min_price
+        && auction.is_started(bid.time, bid.whitelisted)
+        && !auction.is_ended(bid.time, bid.whitelisted)
+}
+
+#[cfg(test)]
+mod test {
+    use {super::*, solana_launchpad::state::auction::RepriceFunction};
+
+    fn get_fixture() -> Auction {
+        let mut auction = Auction::default();
+        auction.common.name = "test_auction".to_string();
+        auction.common.start_time = 100;
+        auction.common.end_time = 200;
+        auction.common.order_limit_reg_address = u64::MAX;
+        auction.common.fill_limit_reg_address = u64::MAX;
+
+        auction.pricing.pricing_model = PricingModel::DynamicDutchAuction;
+        auction.pricing.start_price = 1000;
+        auction.pricing.max_price = 2000;
+        auction.pricing.min_price = 100;
+        auction.pricing.reprice_function = RepriceFunction::Linear;
+        auction.pricing.amount_per_level = 10;
+        auction.pricing.tick_size = 10;
+        auction.pricing.unit_size = 1;
+
+        auction
+    }
+
+    fn bid(time: i64, price: u64, amount: u64, whitelisted: bool) -> SimBid {
+        SimBid {
+            time,
+            price,
+            amount,
+            whitelisted,
+        }
+    }
+
+    #[test]
+    fn simulate_dutch_auction() {
+        let auction = get_fixture();
+        let bids = [
+            bid(50, 2000, 5, false),
+            bid(150, 400, 5, false),
+            bid(150, 2000, 10, true),
+            bid(160, 1000, 30, false),
+            bid(170, 1000, 30, false),
+        ];
+
+        let report = simulate(&auction, 30, &bids).unwrap();
+        assert_eq!(1, report.rejected_bids);
+        assert_eq!(2, report.unfilled_bids);
+        assert_eq!(30, report.units_sold);
+        assert_eq!(Some(160), report.sell_out_time);
+        assert_eq!(2, report.price_path.len());
+        assert_eq!(500, report.price_path[0].price);
+        assert_eq!(10, report.wl_bidders.fills_volume);
+        assert_eq!(20, report.reg_bidders.fills_volume);
+        assert_eq!(
+            report.revenue,
+            report
+                .price_path
+                .iter()
+                .map(|fill| fill.amount as u128 * fill.price as u128)
+                .sum::<u128>()
+        );
+    }
+
+    #[test]
+    fn simulate_batch_auction() {
+        let mut auction = get_fixture();
+        auction.pricing.pricing_model = PricingModel::BatchAuction;
+        auction.pricing.start_price = 100;
+        auction.pricing.max_price = 400;
+
+        let bids = [
+            bid(110, 300, 50, false),
+            bid(120, 200, 40, true),
+            bid(130, 150, 30, false),
+        ];
+
+        let report = simulate(&auction, 70, &bids).unwrap();
+        assert_eq!(70, report.units_sold);
+        assert_eq!(Some(200), report.sell_out_time);
+        assert!(report.price_path.iter().all(|fill| fill.price == 200));
+        assert_eq!(14_000, report.revenue);
+    }
+
+    #[test]
+    fn simulate_hard_cap() {
+        let mut auction = get_fixture();
+        auction.common.max_raise = 25_000;
+        let bids = [
+            bid(110, 2000, 20, false),
+            bid(120, 2000, 20, false),
+            bid(130, 2000, 20, false),
+        ];
+
+        let report = simulate(&auction, 100, &bids).unwrap();
+        assert!(report.revenue <= 25_000);
+        assert_eq!(Some(120), report.sell_out_time);
+        assert_eq!(1, report.rejected_bids);
+    }
+
+    #[test]
+    fn simulate_invalid_config() {
+        let mut auction = get_fixture();
+        auction.pricing.tick_size = 0;
+        assert!(simulate(&auction, 30, &[]).is_err());
+    }
+}
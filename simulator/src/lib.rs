@@ -0,0 +1,14 @@
+//! Offline launchpad auction simulator
+//!
+//! Runs a bid stream through the on-chain pricing code to evaluate auction
+//! configurations without deploying them.
+
+pub mod bidders;
+pub mod engine;
+pub mod report;
+
+pub use {
+    bidders::{parse_bids, SimBid, SyntheticStream},
+    engine::simulate,
+    report::{Fill, SimulationReport},
+};
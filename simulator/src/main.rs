@@ -0,0 +1,358 @@
+//! Launchpad simulator command line tool
+//!
+//! Every pricing option accepts a comma separated list of values, all
+//! combinations of the listed values are simulated against the same bid
+//! stream, so configurations can be compared side by side.
+
+use {
+    launchpad_simulator::{parse_bids, simulate, SyntheticStream},
+    solana_launchpad::{
+        math,
+        state::auction::{
+            AmountFunction, Auction, CurveFunction, PricingModel, RepriceFunction, RepricePoint,
+        },
+    },
+    std::error::Error,
+};
+
+const USAGE: &str = "\
+Usage: launchpad-simulator [OPTIONS]
+
+General options:
+    --bids <FILE>                  Recorded bid stream, one time,price,amount,whitelisted
+                                   record per line, synthetic stream is used if not set
+    --supply <UNITS>               Number of units for sale [default: 1000]
+    --price-path                   Print every fill
+    --help                         Print this message
+
+Auction options:
+    --start-time, --end-time, --presale-start-time, --presale-end-time
+    --order-limit-reg-address, --order-limit-wl-address, --max-raise
+    --extension-window, --extension-duration, --max-extension
+    --model <fixed|dda|batch|curve|pool>
+    --start-price, --min-price, --max-price, --tick-size, --amount-per-level
+                                   batch auctions derive the tick size from the price
+                                   range if not set, see Auction::MAX_PRICE_LEVELS
+    --reprice-delay, --reprice-coef <DECIMAL>
+    --reprice-function <linear|exponential|piecewise|step>
+    --reprice-points <OFFSET:RATIO/...>
+                                   Piecewise function points, e.g. 60:0.9/300:0.5
+    --reprice-interval             Step function interval in seconds
+    --demand-window, --demand-target, --demand-step
+    --amount-function <fixed|linear|geometric>, --amount-coef
+    --curve-function <linear|exponential|power>, --curve-coef <DECIMAL>
+    --start-weight <DECIMAL>, --end-weight <DECIMAL>, --pool-reserve
+
+Synthetic stream options:
+    --seed, --num-bids, --min-bid-price, --max-bid-price,
+    --min-bid-amount, --max-bid-amount, --wl-share <PERCENT>,
+    --stream-start-time, --stream-end-time [default: auction window]
+";
+
+struct Config {
+    auction: Auction,
+    stream: SyntheticStream,
+    supply: u64,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        let mut auction = Auction::default();
+        auction.common.name = "simulation".to_string();
+        auction.common.start_time = 1;
+        auction.common.end_time = 3601;
+        auction.common.order_limit_reg_address = u64::MAX;
+        auction.common.order_limit_wl_address = u64::MAX;
+        auction.common.fill_limit_reg_address = u64::MAX;
+        auction.common.fill_limit_wl_address = u64::MAX;
+
+        auction.pricing.pricing_model = PricingModel::DynamicDutchAuction;
+        auction.pricing.start_price = 500;
+        auction.pricing.max_price = 1000;
+        auction.pricing.min_price = 100;
+        auction.pricing.reprice_delay = 60;
+        auction.pricing.reprice_coef = 50_000_000;
+        auction.pricing.reprice_function = RepriceFunction::Exponential;
+        auction.pricing.amount_per_level = 10;
+        auction.pricing.tick_size = 10;
+        auction.pricing.unit_size = 1;
+
+        Self {
+            auction,
+            stream: SyntheticStream::default(),
+            supply: 1000,
+        }
+    }
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let mut args = std::env::args().skip(1);
+    let mut bids_file = None;
+    let mut print_price_path = false;
+    let mut options: Vec<(String, Vec<String>)> = vec![];
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--help" | "-h" => {
+                print!("{}", USAGE);
+                return Ok(());
+            }
+            "--price-path" => print_price_path = true,
+            "--bids" => bids_file = Some(args.next().ok_or("Missing value for --bids")?),
+            _ => {
+                let name = arg
+                    .strip_prefix("--")
+                    .ok_or_else(|| format!("Unexpected argument {}\n\n{}", arg, USAGE))?;
+                let values = args
+                    .next()
+                    .ok_or_else(|| format!("Missing value for {}", arg))?;
+                options.push((
+                    name.to_string(),
+                    values.split(',').map(str::to_string).collect(),
+                ));
+            }
+        }
+    }
+
+    let recorded_bids = match bids_file {
+        Some(path) => Some(parse_bids(&std::fs::read_to_string(path)?)?),
+        None => None,
+    };
+
+    for combination in get_combinations(&options) {
+        let mut config = Config::default();
+        let mut stream_window_set = false;
+        let mut tick_size_set = false;
+        for (name, value) in &combination {
+            apply_option(&mut config, name, value)?;
+            stream_window_set |= name == "stream-start-time" || name == "stream-end-time";
+            tick_size_set |= name == "tick-size";
+        }
+        if !tick_size_set {
+            set_batch_tick_size(&mut config.auction);
+        }
+        if !stream_window_set {
+            config.stream.start_time = config.auction.get_start_time(true);
+            config.stream.end_time = config.auction.get_end_time(true);
+        }
+
+        let bids = match &recorded_bids {
+            Some(bids) => bids.clone(),
+            None => config.stream.generate(),
+        };
+
+        if !combination.is_empty() {
+            let labels: Vec<String> = combination
+                .iter()
+                .map(|(name, value)| format!("{}={}", name, value))
+                .collect();
+            println!("# {}", labels.join(" "));
+        }
+        if let Err(err) = check_batch_price_levels(&config.auction) {
+            println!("simulation failed: {}\n", err);
+            continue;
+        }
+        match simulate(&config.auction, config.supply, &bids) {
+            Ok(report) => {
+                print!("{}", report);
+                if print_price_path {
+                    for fill in &report.price_path {
+                        println!(
+                            "  {} {} x {}{}",
+                            fill.time,
+                            fill.price,
+                            fill.amount,
+                            if fill.whitelisted { " (wl)" } else { "" }
+                        );
+                    }
+                }
+            }
+            Err(err) => println!("simulation failed: {}", err),
+        }
+        println!();
+    }
+
+    Ok(())
+}
+
+/// Picks the smallest tick size that fits the batch auction price range
+/// into Auction::MAX_PRICE_LEVELS levels
+fn set_batch_tick_size(auction: &mut Auction) {
+    let pricing = &mut auction.pricing;
+    if pricing.pricing_model == PricingModel::BatchAuction && pricing.max_price >= pricing.min_price
+    {
+        pricing.tick_size = std::cmp::max(
+            pricing.tick_size,
+            (pricing.max_price - pricing.min_price) / Auction::MAX_PRICE_LEVELS as u64 + 1,
+        );
+    }
+}
+
+fn check_batch_price_levels(auction: &Auction) -> Result<(), String> {
+    let pricing = &auction.pricing;
+    if pricing.pricing_model != PricingModel::BatchAuction
+        || pricing.tick_size == 0
+        || pricing.max_price < pricing.min_price
+    {
+        return Ok(());
+    }
+    let num_levels = (pricing.max_price - pricing.min_price) / pricing.tick_size + 1;
+    if num_levels > Auction::MAX_PRICE_LEVELS as u64 {
+        return Err(format!(
+            "batch auction price range needs {} levels at tick size {}, at most {} are supported, \
+             increase --tick-size to at least {}",
+            num_levels,
+            pricing.tick_size,
+            Auction::MAX_PRICE_LEVELS,
+            (pricing.max_price - pricing.min_price) / Auction::MAX_PRICE_LEVELS as u64 + 1
+        ));
+    }
+    Ok(())
+}
+
+/// Returns every combination of the option values
+fn get_combinations(options: &[(String, Vec<String>)]) -> Vec<Vec<(String, String)>> {
+    let mut combinations = vec![vec![]];
+    for (name, values) in options {
+        combinations = combinations
+            .into_iter()
+            .flat_map(|combination| {
+                values.iter().map(move |value| {
+                    let mut combination = combination.clone();
+                    combination.push((name.clone(), value.clone()));
+                    combination
+                })
+            })
+            .collect();
+    }
+    combinations
+}
+
+fn apply_option(config: &mut Config, name: &str, value: &str) -> Result<(), String> {
+    let common = &mut config.auction.common;
+    let pricing = &mut config.auction.pricing;
+    let stream = &mut config.stream;
+    match name {
+        "supply" => config.supply = parse(name, value)?,
+        "start-time" => common.start_time = parse(name, value)?,
+        "end-time" => common.end_time = parse(name, value)?,
+        "presale-start-time" => common.presale_start_time = parse(name, value)?,
+        "presale-end-time" => common.presale_end_time = parse(name, value)?,
+        "order-limit-reg-address" => common.order_limit_reg_address = parse(name, value)?,
+        "order-limit-wl-address" => common.order_limit_wl_address = parse(name, value)?,
+        "max-raise" => common.max_raise = parse(name, value)?,
+        "extension-window" => common.extension_window = parse(name, value)?,
+        "extension-duration" => common.extension_duration = parse(name, value)?,
+        "max-extension" => common.max_extension = parse(name, value)?,
+        "model" => {
+            pricing.pricing_model = match value {
+                "fixed" => PricingModel::Fixed,
+                "dda" => PricingModel::DynamicDutchAuction,
+                "batch" => PricingModel::BatchAuction,
+                "curve" => PricingModel::BondingCurve,
+                "pool" => PricingModel::WeightedPool,
+                _ => return Err(format!("Unsupported pricing model {}", value)),
+            }
+        }
+        "start-price" => pricing.start_price = parse(name, value)?,
+        "min-price" => pricing.min_price = parse(name, value)?,
+        "max-price" => pricing.max_price = parse(name, value)?,
+        "tick-size" => pricing.tick_size = parse(name, value)?,
+        "amount-per-level" => pricing.amount_per_level = parse(name, value)?,
+        "reprice-delay" => pricing.reprice_delay = parse(name, value)?,
+        "reprice-coef" => pricing.reprice_coef = parse_fixed(name, value)?,
+        "reprice-function" => {
+            pricing.reprice_function = match value {
+                "linear" => RepriceFunction::Linear,
+                "exponential" => RepriceFunction::Exponential,
+                "piecewise" => RepriceFunction::Piecewise,
+                "step" => RepriceFunction::Step,
+                _ => return Err(format!("Unsupported reprice function {}", value)),
+            }
+        }
+        "reprice-points" => {
+            let points: Vec<&str> = value.split('/').collect();
+            if points.len() > Auction::MAX_REPRICE_POINTS {
+                return Err(format!(
+                    "Too many points for --{}, at most {} are supported",
+                    name,
+                    Auction::MAX_REPRICE_POINTS
+                ));
+            }
+            pricing.reprice_points = [RepricePoint::default(); Auction::MAX_REPRICE_POINTS];
+            for (point, reprice_point) in points.iter().zip(pricing.reprice_points.iter_mut()) {
+                let (time_offset, price_ratio) = point
+                    .split_once(':')
+                    .ok_or_else(|| format!("Invalid value {} for --{}", value, name))?;
+                *reprice_point = RepricePoint {
+                    time_offset: parse(name, time_offset)?,
+                    price_ratio: parse_fixed(name, price_ratio)?,
+                };
+            }
+            pricing.num_reprice_points = points.len() as u8;
+        }
+        "reprice-interval" => pricing.reprice_interval = parse(name, value)?,
+        "demand-window" => pricing.demand_window = parse(name, value)?,
+        "demand-target" => pricing.demand_target = parse(name, value)?,
+        "demand-step" => pricing.demand_step = parse(name, value)?,
+        "amount-function" => {
+            pricing.amount_function = match value {
+                "fixed" => AmountFunction::Fixed,
+                "linear" => AmountFunction::Linear,
+                "geometric" => AmountFunction::Geometric,
+                _ => return Err(format!("Unsupported amount function {}", value)),
+            }
+        }
+        "amount-coef" => pricing.amount_coef = parse(name, value)?,
+        "curve-function" => {
+            pricing.curve_function = match value {
+                "linear" => CurveFunction::Linear,
+                "exponential" => CurveFunction::Exponential,
+                "power" => CurveFunction::Power,
+                _ => return Err(format!("Unsupported curve function {}", value)),
+            }
+        }
+        "curve-coef" => pricing.curve_coef = parse_fixed(name, value)?,
+        "start-weight" => pricing.start_weight = parse_fixed(name, value)?,
+        "end-weight" => pricing.end_weight = parse_fixed(name, value)?,
+        "pool-reserve" => pricing.pool_reserve = parse(name, value)?,
+        "seed" => stream.seed = parse(name, value)?,
+        "num-bids" => stream.num_bids = parse(name, value)?,
+        "stream-start-time" => stream.start_time = parse(name, value)?,
+        "stream-end-time" => stream.end_time = parse(name, value)?,
+        "min-bid-price" => stream.min_price = parse(name, value)?,
+        "max-bid-price" => stream.max_price = parse(name, value)?,
+        "min-bid-amount" => stream.min_amount = parse(name, value)?,
+        "max-bid-amount" => stream.max_amount = parse(name, value)?,
+        "wl-share" => stream.whitelisted_share = parse(name, value)?,
+        _ => return Err(format!("Unknown option --{}\n\n{}", name, USAGE)),
+    }
+    Ok(())
+}
+
+fn parse<T: std::str::FromStr>(name: &str, value: &str) -> Result<T, String> {
+    value
+        .parse()
+        .map_err(|_| format!("Invalid value {} for --{}", value, name))
+}
+
+/// Parses a decimal number into a fixed-point value, see math::FIXED_POINT_ONE
+fn parse_fixed(name: &str, value: &str) -> Result<u64, String> {
+    let error = || format!("Invalid value {} for --{}", value, name);
+    let decimals = math::FIXED_POINT_DECIMALS as usize;
+    let (integer, fraction) = value.split_once('.').unwrap_or((value, ""));
+    if fraction.len() > decimals || !fraction.chars().all(|c| c.is_ascii_digit()) {
+        return Err(error());
+    }
+    let integer: u64 = if integer.is_empty() {
+        0
+    } else {
+        integer.parse().map_err(|_| error())?
+    };
+    let fraction: u64 = format!("{:0<width$}", fraction, width = decimals)
+        .parse()
+        .map_err(|_| error())?;
+    integer
+        .checked_mul(math::FIXED_POINT_ONE as u64)
+        .and_then(|scaled| scaled.checked_add(fraction))
+        .ok_or_else(error)
+}
@@ -0,0 +1,87 @@
+//! Simulation results
+
+use {solana_launchpad::state::auction::BidderStats, std::fmt};
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct Fill {
+    pub time: i64,
+    pub price: u64,
+    pub amount: u64,
+    pub whitelisted: bool,
+}
+
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct SimulationReport {
+    pub supply: u64,
+    pub units_sold: u64,
+    // sum of fill amounts multiplied by fill prices
+    pub revenue: u128,
+    pub sell_out_time: Option<i64>,
+    pub num_bids: usize,
+    // bids that arrived outside of the auction window or broke its limits
+    pub rejected_bids: usize,
+    // valid bids that got nothing at their price
+    pub unfilled_bids: usize,
+    pub price_path: Vec<Fill>,
+    pub wl_bidders: BidderStats,
+    pub reg_bidders: BidderStats,
+}
+
+impl SimulationReport {
+    pub fn get_average_price(&self) -> u64 {
+        if self.units_sold == 0 {
+            0
+        } else {
+            u64::try_from(self.revenue / self.units_sold as u128).unwrap_or(u64::MAX)
+        }
+    }
+}
+
+impl fmt::Display for SimulationReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let sold_share = if self.supply > 0 {
+            self.units_sold as f64 * 100.0 / self.supply as f64
+        } else {
+            0.0
+        };
+        writeln!(
+            f,
+            "sold {} of {} units ({:.2}%), revenue {}, average price {}",
+            self.units_sold,
+            self.supply,
+            sold_share,
+            self.revenue,
+            self.get_average_price()
+        )?;
+        match self.sell_out_time {
+            Some(time) => writeln!(f, "sold out at {}", time)?,
+            None => writeln!(f, "not sold out")?,
+        }
+        writeln!(
+            f,
+            "bids {}, fills {}, unfilled {}, rejected {}",
+            self.num_bids,
+            self.price_path.len(),
+            self.unfilled_bids,
+            self.rejected_bids
+        )?;
+        write_bidder_stats(f, "whitelisted", &self.wl_bidders)?;
+        write_bidder_stats(f, "regular", &self.reg_bidders)
+    }
+}
+
+fn write_bidder_stats(f: &mut fmt::Formatter, label: &str, stats: &BidderStats) -> fmt::Result {
+    if stats.num_trades == 0 {
+        return writeln!(f, "{} bidders: no fills", label);
+    }
+    writeln!(
+        f,
+        "{} bidders: {} units in {} fills, average price {}, price range {}..{}",
+        label,
+        stats.fills_volume,
+        stats.num_trades,
+        stats.weighted_fills_sum / stats.fills_volume as u128,
+        stats.min_fill_price,
+        stats.max_fill_price
+    )
+}
@@ -125,7 +125,7 @@ describe("launchpad", () => {
 
   it("initCustodies", async () => {
     let config = {
-      maxOraclePriceError: 1,
+      maxOraclePriceError: new BN(1_000_000_000),
       maxOraclePriceAgeSec: 60,
       oracleType: { test: {} },
       oracleAccount: lpd.pricingCustody.oracleAccount,
@@ -154,7 +154,7 @@ describe("launchpad", () => {
 
   it("setOracleConfig", async () => {
     let config = {
-      maxOraclePriceError: 123,
+      maxOraclePriceError: new BN(123_000_000_000),
       maxOraclePriceAgeSec: 900,
       oracleType: { test: {} },
       oracleAccount: lpd.paymentCustody.oracleAccount,
@@ -180,6 +180,9 @@ describe("launchpad", () => {
       enabled: true,
       updatable: true,
       fixedAmount: false,
+      basket: false,
+      deferredClaim: false,
+      splitFills: false,
       common: {
         name: "test auction",
         description: "test only",
@@ -193,11 +196,33 @@ describe("launchpad", () => {
         fillLimitWlAddress: new BN(20),
         orderLimitRegAddress: new BN(5),
         orderLimitWlAddress: new BN(10),
+        revealStartTime: new BN(0),
+        revealEndTime: new BN(0),
+        minRaise: new BN(0),
+        maxRaise: new BN(0),
+        extensionWindow: new BN(0),
+        extensionDuration: new BN(0),
+        maxExtension: new BN(0),
+        tgeTime: new BN(0),
+        minLimitOrderAmount: new BN(0),
       },
       payment: {
-        acceptSol: true,
-        acceptUsdc: true,
-        acceptOtherTokens: true,
+        custodies: [
+          {
+            custody: lpd.paymentCustody.custody,
+            priceAdjustmentBps: new BN(0),
+          },
+          {
+            custody: lpd.pricingCustody.custody,
+            priceAdjustmentBps: new BN(0),
+          },
+        ].concat(
+          new Array(6).fill({
+            custody: PublicKey.default,
+            priceAdjustmentBps: new BN(0),
+          })
+        ),
+        numCustodies: 2,
       },
       pricing: {
         custody: lpd.pricingCustody.custody,
@@ -206,14 +231,41 @@ describe("launchpad", () => {
         maxPrice: new BN(200),
         minPrice: new BN(90),
         repriceDelay: new BN(5),
-        repriceCoef: 0.05,
+        repriceCoef: new BN(50_000_000),
         repriceFunction: { exponential: {} },
+        repriceInterval: new BN(0),
+        repricePoints: new Array(8).fill({
+          timeOffset: new BN(0),
+          priceRatio: new BN(0),
+        }),
+        numRepricePoints: 0,
+        demandWindow: new BN(0),
+        demandTarget: new BN(0),
+        demandStep: new BN(0),
         amountFunction: { fixed: {} },
+        amountCoef: new BN(0),
+        curveFunction: { linear: {} },
+        curveCoef: new BN(0),
+        startWeight: new BN(0),
+        endWeight: new BN(0),
+        poolReserve: new BN(0),
         amountPerLevel: new BN(200),
         tickSize: new BN(2),
         unitSize: lpd.toTokenAmount(1, lpd.dispensingCustodies[0].decimals),
       },
       tokenRatios: [new BN(1), new BN(2)],
+      basketAmounts: [],
+      randomness: {
+        source: { slotHash: {} },
+        vrfOracleType: { none: {} },
+        vrfAccount: PublicKey.default,
+      },
+      vesting: {
+        cliff: new BN(0),
+        duration: new BN(0),
+        tgeUnlock: new BN(0),
+      },
+      candleDuration: new BN(60),
     };
 
     let err = await lpd.ensureFails(lpd.initAuction(auctionParams));
@@ -239,6 +291,9 @@ describe("launchpad", () => {
       enabled: true,
       updatable: true,
       fixedAmount: false,
+      basket: false,
+      deferredClaim: false,
+      splitFills: false,
       common: auctionParams.common,
       payment: auctionParams.payment,
       pricing: auctionParams.pricing,
@@ -247,6 +302,8 @@ describe("launchpad", () => {
         lastTradeTime: "0",
         lastAmount: "0",
         lastPrice: "0",
+        demandSlot: "0",
+        demandBuckets: new Array(10).fill("0"),
         wlBidders: {
           fillsVolume: "0",
           weightedFillsSum: "0",
@@ -261,20 +318,88 @@ describe("launchpad", () => {
           maxFillPrice: "0",
           numTrades: "0",
         },
+        raisedAmount: "0",
+        clearing: {
+          settleTime: "0",
+          clearingPrice: "0",
+          supply: "0",
+          demandAbove: "0",
+          demandAt: "0",
+          unclaimedAmount: "0",
+          demand: new Array(32).fill("0"),
+        },
+        leading: {
+          bid: "11111111111111111111111111111111",
+          owner: "11111111111111111111111111111111",
+          refundAccount: "11111111111111111111111111111111",
+          price: "0",
+          bidTime: "0",
+        },
+        escrow: {
+          amount: "0",
+          fee: "0",
+        },
+        hardCapTime: "0",
+        presaleEndTimeExtension: "0",
+        endTimeExtension: "0",
+        pendingAmount: "0",
+        tokenFills: new Array(10).fill("0"),
       },
       tokens: [
-        { ratio: "1", account: lpd.dispensingCustodies[0].tokenAccount },
-        { ratio: "2", account: lpd.dispensingCustodies[1].tokenAccount },
-        { ratio: "0", account: "11111111111111111111111111111111" },
-        { ratio: "0", account: "11111111111111111111111111111111" },
-        { ratio: "0", account: "11111111111111111111111111111111" },
-        { ratio: "0", account: "11111111111111111111111111111111" },
-        { ratio: "0", account: "11111111111111111111111111111111" },
-        { ratio: "0", account: "11111111111111111111111111111111" },
-        { ratio: "0", account: "11111111111111111111111111111111" },
-        { ratio: "0", account: "11111111111111111111111111111111" },
+        {
+          ratio: "1",
+          account: lpd.dispensingCustodies[0].tokenAccount,
+          basketAmount: "0",
+        },
+        {
+          ratio: "2",
+          account: lpd.dispensingCustodies[1].tokenAccount,
+          basketAmount: "0",
+        },
+        {
+          ratio: "0",
+          account: "11111111111111111111111111111111",
+          basketAmount: "0",
+        },
+        {
+          ratio: "0",
+          account: "11111111111111111111111111111111",
+          basketAmount: "0",
+        },
+        {
+          ratio: "0",
+          account: "11111111111111111111111111111111",
+          basketAmount: "0",
+        },
+        {
+          ratio: "0",
+          account: "11111111111111111111111111111111",
+          basketAmount: "0",
+        },
+        {
+          ratio: "0",
+          account: "11111111111111111111111111111111",
+          basketAmount: "0",
+        },
+        {
+          ratio: "0",
+          account: "11111111111111111111111111111111",
+          basketAmount: "0",
+        },
+        {
+          ratio: "0",
+          account: "11111111111111111111111111111111",
+          basketAmount: "0",
+        },
+        {
+          ratio: "0",
+          account: "11111111111111111111111111111111",
+          basketAmount: "0",
+        },
       ],
       numTokens: 2,
+      randomness: auctionParams.randomness,
+      vesting: auctionParams.vesting,
       creationTime: "0",
       updateTime: "0",
       bump: auction.bump,
@@ -410,6 +535,16 @@ describe("launchpad", () => {
       fillTime: new BN(0),
       fillPrice: new BN(0),
       fillAmount: new BN(0),
+      tokenFills: new Array(10).fill(new BN(0)),
+      escrowed: false,
+      escrowCustody: PublicKey.default,
+      escrowAmount: new BN(0),
+      escrowFee: new BN(0),
+      sealed: false,
+      commitment: new Array(32).fill(0),
+      pendingAmount: new BN(0),
+      pendingSlot: new BN(0),
+      vesting: new Array(10).fill({ amount: new BN(0), claimed: new BN(0) }),
       bump: bid.bump,
     };
     expect(JSON.stringify(bid)).to.equal(JSON.stringify(bidExpected));
@@ -434,6 +569,16 @@ describe("launchpad", () => {
       fillTime: new BN(0),
       fillPrice: new BN(0),
       fillAmount: new BN(0),
+      tokenFills: new Array(10).fill(new BN(0)),
+      escrowed: false,
+      escrowCustody: PublicKey.default,
+      escrowAmount: new BN(0),
+      escrowFee: new BN(0),
+      sealed: false,
+      commitment: new Array(32).fill(0),
+      pendingAmount: new BN(0),
+      pendingSlot: new BN(0),
+      vesting: new Array(10).fill({ amount: new BN(0), claimed: new BN(0) }),
       bump: bid.bump,
     };
     expect(JSON.stringify(bid)).to.equal(JSON.stringify(bidExpected));
@@ -451,6 +596,76 @@ describe("launchpad", () => {
     //expect(price).to.equal(100);
   });
 
+  it("getQuote", async () => {
+    let trade = launchpadExpected.fees.trade;
+    let getFeeAmount = (paymentAmount) =>
+      paymentAmount
+        .mul(trade.numerator)
+        .add(trade.denominator.subn(1))
+        .div(trade.denominator);
+
+    // pay with the pricing token, no conversion
+    let quote = await lpd.getQuote(1, 0, lpd.pricingCustody);
+    let unitPrice = await lpd.getAuctionPrice(1);
+    expect(quote.fillAmount.toNumber()).to.equal(1);
+    expect(quote.unitPrice.toString()).to.equal(unitPrice.toString());
+    expect(quote.paymentAmount.toString()).to.equal(unitPrice.toString());
+    expect(quote.feeAmount.toString()).to.equal(
+      getFeeAmount(unitPrice).toString()
+    );
+    // the token is picked randomly upon the bid
+    expect(quote.tokenMint.toBase58()).to.equal(PublicKey.default.toBase58());
+    expect(JSON.stringify(quote.badBidType)).to.equal(
+      JSON.stringify({ none: {} })
+    );
+
+    // pay with the payment token, converted at oracle prices 200 / 123
+    quote = await lpd.getQuote(1, 0);
+    let pairPrice = new BN(200_000_000_000_000).divn(123_000);
+    let scale = new BN(10).pow(
+      new BN(9 + lpd.pricingCustody.decimals - lpd.paymentCustody.decimals)
+    );
+    let pricePerToken = unitPrice.mul(pairPrice).add(scale.subn(1)).div(scale);
+    expect(quote.fillAmount.toNumber()).to.equal(1);
+    expect(quote.unitPrice.toString()).to.equal(unitPrice.toString());
+    expect(quote.paymentAmount.toString()).to.equal(pricePerToken.toString());
+    expect(quote.feeAmount.toString()).to.equal(
+      getFeeAmount(pricePerToken).toString()
+    );
+    expect(quote.tokenMint.toBase58()).to.equal(PublicKey.default.toBase58());
+
+    // the budget below the cost of the full order limits the amount
+    let fullQuote = await lpd.getQuote(0, 1_000_000, lpd.pricingCustody);
+    let fullCost = fullQuote.paymentAmount.add(fullQuote.feeAmount);
+    expect(fullQuote.fillAmount.toNumber()).to.be.above(1);
+    let budget = fullCost.subn(1).toNumber();
+    quote = await lpd.getQuote(0, budget, lpd.pricingCustody);
+    unitPrice = await lpd.getAuctionPrice(quote.fillAmount.toNumber());
+    let paymentAmount = unitPrice.mul(quote.fillAmount);
+    expect(quote.fillAmount.toNumber()).to.be.below(
+      fullQuote.fillAmount.toNumber()
+    );
+    expect(quote.fillAmount.toNumber()).to.be.above(0);
+    expect(quote.unitPrice.toString()).to.equal(unitPrice.toString());
+    expect(quote.paymentAmount.toString()).to.equal(paymentAmount.toString());
+    expect(quote.feeAmount.toString()).to.equal(
+      getFeeAmount(paymentAmount).toString()
+    );
+    expect(
+      quote.paymentAmount.add(quote.feeAmount).toNumber()
+    ).to.be.at.most(budget);
+
+    // one more unit doesn't fit the budget
+    let nextQuote = await lpd.getQuote(
+      quote.fillAmount.toNumber() + 1,
+      0,
+      lpd.pricingCustody
+    );
+    expect(
+      nextQuote.paymentAmount.add(nextQuote.feeAmount).toNumber()
+    ).to.be.above(budget);
+  });
+
   it("placeBid", async () => {
     let user = lpd.users[0];
 
@@ -498,6 +713,8 @@ describe("launchpad", () => {
       lpd.auction.publicKey
     );
     //expect(auction).to.equal(auctionExpected);
+    let tokenFillsSum = bid.tokenFills.reduce((a, b) => a + b.toNumber(), 0);
+    expect(tokenFillsSum).to.equal(bidAmount);
     let bidExpected = {
       owner: user.wallet.publicKey,
       auction: lpd.auction.publicKey,
@@ -511,10 +728,28 @@ describe("launchpad", () => {
       fillTime: auction.creationTime,
       fillPrice: new BN(100),
       fillAmount: new BN(bidAmount),
+      tokenFills: bid.tokenFills,
+      escrowed: false,
+      escrowCustody: PublicKey.default,
+      escrowAmount: new BN(0),
+      escrowFee: new BN(0),
+      sealed: false,
+      commitment: new Array(32).fill(0),
+      pendingAmount: new BN(0),
+      pendingSlot: new BN(0),
+      vesting: new Array(10).fill({ amount: new BN(0), claimed: new BN(0) }),
       bump: bid.bump,
     };
     expect(JSON.stringify(bid)).to.equal(JSON.stringify(bidExpected));
 
+    // check price history
+    let priceHistory = await lpd.program.account.priceHistory.fetch(
+      lpd.priceHistory.publicKey
+    );
+    expect(priceHistory.numCandles.toNumber()).to.equal(1);
+    expect(priceHistory.candles[0].close.toNumber()).to.equal(100);
+    expect(priceHistory.candles[0].volume.toNumber()).to.equal(bidAmount);
+
     // check seller's balance account
     let sellerBalance = await lpd.program.account.sellerBalance.fetch(
       lpd.seller.balanceAccount
@@ -33,6 +33,8 @@ export class LaunchpadTester {
   authority: { publicKey: PublicKey; bump: number };
   launchpad: { publicKey: PublicKey; bump: number };
   auction: { publicKey: PublicKey; bump: number };
+  priceHistory: { publicKey: PublicKey; bump: number };
+  limitBook: { publicKey: PublicKey; bump: number };
   lookupTable: { publicKey: PublicKey; bump: number };
 
   pricingCustody: {
@@ -101,6 +103,12 @@ export class LaunchpadTester {
     this.authority = await this.findProgramAddress("transfer_authority");
     this.launchpad = await this.findProgramAddress("launchpad");
     this.auction = await this.findProgramAddress("auction", "test auction");
+    this.priceHistory = await this.findProgramAddress("price_history", [
+      this.auction.publicKey,
+    ]);
+    this.limitBook = await this.findProgramAddress("limit_book", [
+      this.auction.publicKey,
+    ]);
     let slot = await this.provider.connection.getSlot();
     this.lookupTable = {
       publicKey: AddressLookupTableProgram.createLookupTable({
@@ -613,6 +621,7 @@ export class LaunchpadTester {
             transferAuthority: this.authority.publicKey,
             launchpad: this.launchpad.publicKey,
             custody: custody.custody,
+            custodyTokenMint: custody.mint.publicKey,
             custodyTokenAccount: custody.tokenAccount,
             receivingTokenAccount: receivingTokenAccount,
             receivingSolAccount: receivingSolAccount,
@@ -643,6 +652,8 @@ export class LaunchpadTester {
             transferAuthority: this.authority.publicKey,
             launchpad: this.launchpad.publicKey,
             auction: this.auction.publicKey,
+            priceHistory: this.priceHistory.publicKey,
+            limitBook: this.limitBook.publicKey,
             tokenProgram: spl.TOKEN_PROGRAM_ID,
           })
           .remainingAccounts(this.dispensingAccountMetas)
@@ -724,6 +735,8 @@ export class LaunchpadTester {
           transferAuthority: this.authority.publicKey,
           launchpad: this.launchpad.publicKey,
           auction: this.auction.publicKey,
+          priceHistory: this.priceHistory.publicKey,
+          limitBook: this.limitBook.publicKey,
           pricingCustody: this.pricingCustody.custody,
           systemProgram: SystemProgram.programId,
           tokenProgram: spl.TOKEN_PROGRAM_ID,
@@ -848,6 +861,8 @@ export class LaunchpadTester {
           launchpad: this.launchpad.publicKey,
           auction: this.auction.publicKey,
           dispensingCustody: this.dispensingCustodies[custodyId].tokenAccount,
+          dispensingCustodyMint:
+            this.dispensingCustodies[custodyId].mint.publicKey,
           tokenProgram: spl.TOKEN_PROGRAM_ID,
         })
         .signers([this.seller.wallet])
@@ -922,6 +937,7 @@ export class LaunchpadTester {
       await this.program.methods
         .withdrawFunds({
           amount: new BN(amount),
+          nativeSol: false,
         })
         .accounts({
           owner: this.seller.wallet.publicKey,
@@ -929,9 +945,11 @@ export class LaunchpadTester {
           launchpad: this.launchpad.publicKey,
           auction: this.auction.publicKey,
           custody: custody.custody,
+          custodyTokenMint: custody.mint.publicKey,
           custodyTokenAccount: custody.tokenAccount,
           sellerBalance: this.seller.balanceAccount,
           receivingAccount: receivingAccount,
+          systemProgram: SystemProgram.programId,
           tokenProgram: spl.TOKEN_PROGRAM_ID,
         })
         .signers([this.seller.wallet])
@@ -964,11 +982,11 @@ export class LaunchpadTester {
     }
   };
 
-  getAuctionPrice = async (uiAmount: number) => {
+  getAuctionPrice = async (amount: number) => {
     try {
       return await this.program.methods
         .getAuctionPrice({
-          amount: this.toTokenAmount(uiAmount, this.pricingCustody.decimals),
+          amount: new BN(amount),
         })
         .accounts({
           user: this.provider.wallet.publicKey,
@@ -984,6 +1002,37 @@ export class LaunchpadTester {
     }
   };
 
+  getQuote = async (
+    amount: number,
+    budget: number,
+    paymentCustody = this.paymentCustody
+  ) => {
+    try {
+      return await this.program.methods
+        .getQuote({
+          amount: new BN(amount),
+          budget: new BN(budget),
+        })
+        .accounts({
+          user: this.provider.wallet.publicKey,
+          launchpad: this.launchpad.publicKey,
+          auction: this.auction.publicKey,
+          bid: await this.getBidAddress(this.provider.wallet.publicKey),
+          pricingCustody: this.pricingCustody.custody,
+          pricingOracleAccount: this.pricingCustody.oracleAccount,
+          paymentCustody: paymentCustody.custody,
+          paymentOracleAccount: paymentCustody.oracleAccount,
+        })
+        .remainingAccounts(this.dispensingAccountMetas)
+        .view();
+    } catch (err) {
+      if (this.printErrors) {
+        console.log(err);
+      }
+      throw err;
+    }
+  };
+
   placeBidNoLookupTable = async (
     price: number,
     amount: number,
@@ -1003,6 +1052,8 @@ export class LaunchpadTester {
           transferAuthority: this.authority.publicKey,
           launchpad: this.launchpad.publicKey,
           auction: this.auction.publicKey,
+          priceHistory: this.priceHistory.publicKey,
+          limitBook: this.limitBook.publicKey,
           sellerBalance: this.seller.balanceAccount,
           bid: await this.getBidAddress(user.wallet.publicKey),
           pricingCustody: this.pricingCustody.custody,
@@ -1010,14 +1061,17 @@ export class LaunchpadTester {
           paymentCustody: this.paymentCustody.custody,
           paymentOracleAccount: this.paymentCustody.oracleAccount,
           paymentTokenAccount: this.paymentCustody.tokenAccount,
+          paymentTokenMint: this.paymentCustody.mint.publicKey,
           recentSlothashes: SYSVAR_SLOT_HASHES_PUBKEY,
           instructions: SYSVAR_INSTRUCTIONS_PUBKEY,
           systemProgram: SystemProgram.programId,
           tokenProgram: spl.TOKEN_PROGRAM_ID,
+          dispensingTokenProgram: spl.TOKEN_PROGRAM_ID,
         })
         .remainingAccounts([
           ...user.receivingAccountMetas,
           ...this.dispensingAccountMetas,
+          ...this.dispensingMintMetas,
         ])
         .signers([user.wallet])
         .rpc();
@@ -1043,6 +1097,8 @@ export class LaunchpadTester {
           transferAuthority: this.authority.publicKey,
           launchpad: this.launchpad.publicKey,
           auction: this.auction.publicKey,
+          priceHistory: this.priceHistory.publicKey,
+          limitBook: this.limitBook.publicKey,
           sellerBalance: this.seller.balanceAccount,
           bid: await this.getBidAddress(user.wallet.publicKey),
           pricingCustody: this.pricingCustody.custody,
@@ -1050,14 +1106,17 @@ export class LaunchpadTester {
           paymentCustody: this.paymentCustody.custody,
           paymentOracleAccount: this.paymentCustody.oracleAccount,
           paymentTokenAccount: this.paymentCustody.tokenAccount,
+          paymentTokenMint: this.paymentCustody.mint.publicKey,
           recentSlothashes: SYSVAR_SLOT_HASHES_PUBKEY,
           instructions: SYSVAR_INSTRUCTIONS_PUBKEY,
           systemProgram: SystemProgram.programId,
           tokenProgram: spl.TOKEN_PROGRAM_ID,
+          dispensingTokenProgram: spl.TOKEN_PROGRAM_ID,
         })
         .remainingAccounts([
           ...user.receivingAccountMetas,
           ...this.dispensingAccountMetas,
+          ...this.dispensingMintMetas,
         ])
         .signers([user.wallet])
         .instruction();